@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use wenyan_to_rust::lex;
+
+fuzz_target!(|src: &str| {
+    let _ = lex::lex(src);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use wenyan_to_rust::{lex, parse};
+
+fuzz_target!(|src: &str| {
+    if let Ok(lex) = lex::lex(src) {
+        let _ = parse::parse(&lex);
+    }
+});
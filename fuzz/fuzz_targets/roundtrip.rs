@@ -0,0 +1,16 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use wenyan_to_rust::unparse::unparse;
+use wenyan_to_rust::{lex, parse};
+
+// whatever `parse` reads, `unparse` writes so that `parse` reads it back the same
+fuzz_target!(|src: &str| {
+    let parsed = match lex::lex(src).ok().and_then(|lex| parse::parse(&lex).ok()) {
+        Some(parsed) => parsed,
+        None => return,
+    };
+    let written = unparse(&parsed);
+    let lex = lex::lex(&written).expect("the output of unparse lexes");
+    let reparsed = parse::parse(&lex).expect("the output of unparse parses");
+    assert_eq!(parsed, reparsed, "{}", written);
+});
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use std::sync::OnceLock;
+use wenyan_to_rust::{compile, romanize, transpile};
+
+// the bundled table is parsed once, not for every input
+static ROMANIZER: OnceLock<romanize::Table> = OnceLock::new();
+
+fuzz_target!(|src: &str| {
+    let romanizer = ROMANIZER.get_or_init(romanize::Table::pinyin);
+    for &optimize in &[false, true] {
+        let _ = transpile(src, romanizer, &compile::Options { optimize });
+    }
+});
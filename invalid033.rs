@@ -1,10 +0,0 @@
-fn main() {
-    let _ans1 = 2.0 + 3.0;
-    let _ans2 = 1.0 + 3.0;
-    let _ans3 = 3.0 + 3.0;
-    let WU4 : (); // undefined
-    let JI3 = _ans1;
-    let GENG1 = _ans2;
-    let XIN1 = _ans3;
-    println!("");
-}
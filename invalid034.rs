@@ -1,3 +0,0 @@
-fn main() {
-    let WU4 : (); // undefined
-}
@@ -1,17 +0,0 @@
-fn main() {
-    let _ans1 = 2.0 + 3.0;
-    let _ans2 = 2.0 + 3.0;
-    let _ans3 = 1.0 + 3.0;
-    let _ans4 = 3.0 + 3.0;
-    let BING3 = _ans3;
-    let DING1 = _ans4;
-    println!("{} {}", _ans1, _ans2);
-    let WU4 : (); // undefined
-    let _ans5 = 2.0 + 3.0;
-    let _ans6 = 1.0 + 3.0;
-    let _ans7 = 3.0 + 3.0;
-    let WU4 = _ans5;
-    let JI3 = _ans6;
-    let GENG1 = _ans7;
-    println!("");
-}
@@ -1,4 +0,0 @@
-fn main() {
-    let WU4 : (); // undefined
-    let JIA3 : (); // undefined
-}
@@ -0,0 +1,98 @@
+use crate::compile;
+use crate::ir;
+use crate::js;
+use crate::parse;
+use crate::romanize::Romanizer;
+
+/// A language that a parsed program is compiled to
+pub trait Backend {
+    /// What `--emit` selects it by, e.g. `rust`
+    fn name(&self) -> &'static str;
+
+    /// The extension of the files written with `--out-dir`, e.g. `rs`
+    fn extension(&self) -> &'static str;
+
+    fn compile(
+        &self,
+        parsed: &[parse::Statement],
+        romanizer: &dyn Romanizer,
+        options: &compile::Options,
+    ) -> Result<String, compile::Error>;
+}
+
+/// `compile::compile`
+pub struct Rust;
+
+impl Backend for Rust {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn extension(&self) -> &'static str {
+        "rs"
+    }
+
+    fn compile(
+        &self,
+        parsed: &[parse::Statement],
+        romanizer: &dyn Romanizer,
+        options: &compile::Options,
+    ) -> Result<String, compile::Error> {
+        compile::compile(parsed, romanizer, options)
+    }
+}
+
+/// `js::compile`, which has nothing to optimize
+pub struct Js;
+
+impl Backend for Js {
+    fn name(&self) -> &'static str {
+        "js"
+    }
+
+    fn extension(&self) -> &'static str {
+        "js"
+    }
+
+    fn compile(
+        &self,
+        parsed: &[parse::Statement],
+        romanizer: &dyn Romanizer,
+        _options: &compile::Options,
+    ) -> Result<String, compile::Error> {
+        js::compile(&ir::lower(parsed), romanizer)
+    }
+}
+
+/// `ir::listing`, the lowered program that the other backends compile
+pub struct Ir;
+
+impl Backend for Ir {
+    fn name(&self) -> &'static str {
+        "ir"
+    }
+
+    fn extension(&self) -> &'static str {
+        "ir"
+    }
+
+    fn compile(
+        &self,
+        parsed: &[parse::Statement],
+        _romanizer: &dyn Romanizer,
+        _options: &compile::Options,
+    ) -> Result<String, compile::Error> {
+        Ok(ir::listing(&ir::lower(parsed)))
+    }
+}
+
+/// Every backend, the default first
+pub const BACKENDS: [&dyn Backend; 3] = [&Rust, &Js, &Ir];
+
+/// The backend that `--emit` calls `name`
+pub fn by_name(name: &str) -> Option<&'static dyn Backend> {
+    BACKENDS
+        .iter()
+        .copied()
+        .find(|backend| backend.name() == name)
+}
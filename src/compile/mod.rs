@@ -1,654 +1,1386 @@
+use crate::fold;
 use crate::identbimap;
+use crate::ir;
 use crate::lex;
 use crate::parse;
+use crate::range;
 use big_s::S;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-type Line = (usize, String);
+/// A line of the output, and the statement it comes from, if any (see `compile_with_origins`)
+struct Line {
+    indent: usize,
+    text: String,
+    origin: Option<usize>,
+}
+
+impl Line {
+    /// A line such as `fn main() {` that no statement gives
+    fn outside(text: String) -> Self {
+        Line {
+            indent: 0,
+            text,
+            origin: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// also when the declaration is in a block that has already been left
+    UseBeforeDeclaration(parse::Identifier),
+    /// valid wenyan that this compiler cannot translate yet
+    Unsupported(&'static str),
+}
+
+/// How to compile; `Options::default()` gives the plainest output
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// `-O`: the values not yet named are written where they are used, if at all, instead of each getting an `_ans`;
+    /// the values known when compiling are computed then, and the variables never assigned to are replaced by their values.
+    /// If every number is provably an integer that `f64` represents exactly, the numbers are `i64`.
+    pub optimize: bool,
+}
 
 struct Env {
     ans_counter: usize,
     rand_counter: usize,
     indent_level: usize,
-    variables_not_yet_named: Vec<String>,
+    /// the values on the stack, as `ir::lower` resolved it
+    unnamed: BTreeMap<ir::Temp, Unnamed>,
+    /// the methods of the runtime stack `_stack` called so far, see `compile_stack`
+    stack_methods: HashSet<&'static str>,
+    /// the temporaries that hold a `Value` of the runtime stack
+    spilled_temps: HashSet<ir::Temp>,
+    /// the variables that hold a `Value` of the runtime stack
+    spilled_bindings: HashSet<identbimap::BindingId>,
     ident_map: identbimap::IdentBiMap,
+    optimize: bool,
+    /// with `-O`, the variables that have no `let` since their values are known
+    constants: HashMap<identbimap::BindingId, fold::Const>,
+    /// with `-O`, whether the numbers are `i64` rather than `f64`; see `range::all_integers`
+    integers: bool,
+    /// the statement being compiled, as `ir::Op::Origin` tells
+    origin: Option<usize>,
+}
+
+impl Env {
+    fn line(&self, text: String) -> Line {
+        Line {
+            indent: self.indent_level,
+            text,
+            origin: self.origin,
+        }
+    }
+}
+
+/// An expression of the output
+#[derive(Clone)]
+struct Expr {
+    text: String,
+    precedence: u8,
+    /// with `-O`, the value if known when compiling
+    value: Option<fold::Const>,
+    /// whether it is a `Value` of the runtime stack, see `compile_stack`
+    spilled: bool,
+}
+
+impl Expr {
+    fn new(text: String, precedence: u8) -> Self {
+        Expr {
+            text,
+            precedence,
+            value: None,
+            spilled: false,
+        }
+    }
+
+    fn literal(text: String, value: Option<fold::Const>) -> Self {
+        Expr {
+            precedence: precedence::of_literal(&text),
+            text,
+            value,
+            spilled: false,
+        }
+    }
+
+    fn constant(env: &Env, value: fold::Const) -> Self {
+        Expr::literal(compile_constant(env, &value), Some(value))
+    }
+
+    /// A `Value` of the runtime stack converted with `from`, such as `f64` where a number is needed
+    fn converted(self, into: &str) -> Self {
+        if self.spilled {
+            Expr::new(format!("{}::from({})", into, self.text), precedence::ATOM)
+        } else {
+            self
+        }
+    }
+
+    /// A variable that holds a `Value` of the runtime stack, which is not `Copy`, is read as a copy
+    fn cloned(self) -> Self {
+        Expr {
+            spilled: true,
+            ..Expr::new(format!("{}.clone()", self.text), precedence::ATOM)
+        }
+    }
+
+    /// Anything but a `Value` of the runtime stack converted to one
+    fn converted_to_value(self) -> Self {
+        if self.spilled {
+            self
+        } else {
+            Expr::new(format!("Value::from({})", self.text), precedence::ATOM)
+        }
+    }
+
+    /// Parenthesized unless it binds at least as tightly as `min_precedence`
+    fn operand(&self, min_precedence: u8) -> String {
+        if self.precedence < min_precedence {
+            format!("({})", self.text)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+/// A value not yet named, i.e. on the stack of wenyan
+#[derive(Clone)]
+enum Unnamed {
+    /// bound to a temporary such as `_ans1`
+    Bound(String),
+    /// with `-O`, an expression without side effects that is not bound yet.
+    /// It is written where it is used, bound by `bind_pending` before anything could change what it reads,
+    /// or dropped if never used.
+    Pending(Expr),
+}
+
+/// The precedence of Rust expressions, the higher the tighter
+mod precedence {
+    pub const OR: u8 = 1;
+    pub const AND: u8 = 2;
+    pub const COMPARISON: u8 = 3;
+    pub const ADD: u8 = 4;
+    pub const MUL: u8 = 5;
+    pub const CAST: u8 = 6;
+    pub const UNARY: u8 = 7;
+    /// literals, variables, method calls and indexing
+    pub const ATOM: u8 = 8;
+
+    pub fn of_binary(op: &str) -> u8 {
+        match op {
+            "||" => OR,
+            "&&" => AND,
+            "+" | "-" => ADD,
+            "*" | "/" | "%" => MUL,
+            _ => COMPARISON,
+        }
+    }
+
+    /// `-1.0` is a negation
+    pub fn of_literal(lit: &str) -> u8 {
+        if lit.starts_with('-') {
+            UNARY
+        } else {
+            ATOM
+        }
+    }
+}
+
+impl Unnamed {
+    /// To be used as a whole, such as the right-hand side of `let`
+    fn whole(&self) -> &str {
+        match self {
+            Unnamed::Bound(name) => name,
+            Unnamed::Pending(expr) => &expr.text,
+        }
+    }
+
+    fn value(&self) -> Option<&fold::Const> {
+        match self {
+            Unnamed::Bound(_) => None,
+            Unnamed::Pending(expr) => expr.value.as_ref(),
+        }
+    }
+
+    fn into_expr(self) -> Expr {
+        match self {
+            Unnamed::Bound(name) => Expr::new(name, precedence::ATOM),
+            Unnamed::Pending(expr) => expr,
+        }
+    }
+}
+
+/// The identifiers and keywords in a Rust expression, string literals skipped; `r#loop` gives `r` and `loop`
+fn words(expr: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut start = None;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in expr.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c.is_alphanumeric() || c == '_' {
+            start.get_or_insert(i);
+        } else {
+            if let Some(s) = start.take() {
+                words.push(&expr[s..i]);
+            }
+            in_string = c == '"';
+        }
+    }
+    if let Some(s) = start {
+        words.push(&expr[s..]);
+    }
+    words
+}
+
+/// Whether `expr` refers to the variable `name`
+fn mentions(expr: &str, name: &str) -> bool {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    words(expr).contains(&name)
 }
 
 fn compile_optional_literal(
     env: &Env,
     lit: Option<&parse::Data>,
     default_type: lex::Type,
-) -> String {
-    match lit {
+) -> Result<String, Error> {
+    Ok(match lit {
         None => match default_type {
-            lex::Type::Shu4 => S("0.0"),
+            lex::Type::Shu4 => S(if env.integers { "0" } else { "0.0" }),
             lex::Type::Lie4 => S("vec![]"),
             lex::Type::Yan2 => S("\"\""),
             lex::Type::Yao2 => S("false"),
         },
-        Some(v) => compile_literal(&env, v),
+        Some(v) => compile_literal(&env, v)?,
+    })
+}
+
+fn compile_optional_expr(
+    env: &Env,
+    lit: Option<&parse::Data>,
+    default_type: lex::Type,
+) -> Result<Expr, Error> {
+    let mut expr = Expr::literal(
+        compile_optional_literal(env, lit, default_type)?,
+        compile_optional_constant(env, lit, default_type)?,
+    );
+    if lit.map_or(Ok(false), |data| is_spilled(env, data))? {
+        expr = expr.cloned();
     }
+    Ok(expr)
 }
 
-fn compile_literal(env: &Env, v: &parse::Data) -> String {
-    match v.clone() {
-        parse::Data::BoolValue(true) => S("true"),
-        parse::Data::BoolValue(false) => S("false"),
-        parse::Data::Identifier(ident) => env.ident_map.translate_from_hanzi(&ident),
-        parse::Data::IntNum(intnum) => format!("{}.0", intnum),
-        parse::Data::StringLiteral(strlit) => format!("\"{}\"", strlit), // FIXME properly escape
+/// Whether `data` is a variable that holds a `Value` of the runtime stack
+fn is_spilled(env: &Env, data: &parse::Data) -> Result<bool, Error> {
+    Ok(match data {
+        parse::Data::Identifier(ident) => env
+            .spilled_bindings
+            .contains(&env.ident_map.binding_id(ident)?),
+        _ => false,
+    })
+}
+
+/// `Error::Unsupported` if `data` holds a `Value` of the runtime stack, which is not known to be a list or a number
+fn not_spilled(env: &Env, data: &parse::Data, what: &'static str) -> Result<(), Error> {
+    if is_spilled(env, data)? {
+        Err(Error::Unsupported(what))
+    } else {
+        Ok(())
     }
 }
 
-/// It is possible to have three conflicting information on the number of variables declared.
-/// Let's say we have `吾有三數。曰三。曰九。名之曰「庚」。曰「辛」。曰「壬」。曰「癸」。書之。`
-/// Then `how_many_variables` is  `3`, `type_` is `Type::Shu4`, `data_arr` is `vec![3, 9]` and `idents` are the idents.
-/// This compiles to
-/// ```
-/// var 庚 = 3;
-/// var 辛 = 9;
-/// var 壬 = 0;
-/// console.log();
-/// ```
-
-/// `吾有三數。曰三。曰九。曰二十七。名之曰「甲」。書之。` becomes
-/// ```
-/// var 甲 = 3;
-/// var _ans1 = 9;
-/// var _ans2 = 27;
-/// console.log(_ans1, _ans2);
-/// ```
-
-/// `吾有三數。曰三。曰九。曰二十七。名之曰「乙」。曰「丙」。書之。` is
-/// ```
-/// var 乙 = 3;
-/// var 丙 = 9;
-/// var _ans3 = 27;
-/// console.log(_ans3);
-/// ```
-
-/// and `吾有三數。曰三。曰九。曰二十七。名之曰「丁」。曰「戊」。曰「己」。書之。` is, naturally,
-/// ```
-/// var 丁 = 3;
-/// var 戊 = 9;
-/// var 己 = 27;
-/// console.log();
-/// ```
-
-/// Therefore, `how_many_variables` always determines how many variables are to be defined;
-/// `data_arr` is truncated or padded so that its length matches `how_many_variables`,
-/// `idents` fills the open spots,
-/// and remaining spots (if any) will be accessible by 書之 .
-fn compile_define(
-    mut env: &mut Env,
-    decl: &parse::DeclareStatement,
-    idents: &[parse::Identifier],
-) -> Vec<Line> {
-    let parse::DeclareStatement {
-        how_many_variables,
-        type_,
-        data_arr,
-    } = decl;
-    let mut ans = vec![];
-
-    for i in 0..*how_many_variables {
-        match idents.get(i) {
-            None => {
-                // no more ident; ans_counter and variables_not_yet_named come into play
-                ans.push((
-                    env.indent_level,
-                    format!(
-                        "let _ans{} = {};",
-                        get_new_unnamed_var(&mut env),
-                        compile_optional_literal(&env, data_arr.get(i), *type_)
-                    ),
-                ));
-            }
-            Some(ident) => {
-                ans.push((
-                    env.indent_level,
-                    format!(
-                        "let {}{} = {};",
-                        ifmutable_thenmut(&env, &ident),
-                        env.ident_map.translate_from_hanzi(&ident),
-                        compile_optional_literal(&env, data_arr.get(i), *type_)
-                    ),
-                ));
-            }
-        }
+/// With `-O`, the value of `data` if known when compiling
+fn constant(env: &Env, data: &parse::Data) -> Result<Option<fold::Const>, Error> {
+    if !env.optimize {
+        return Ok(None);
     }
+    Ok(match data {
+        parse::Data::Identifier(ident) => env
+            .constants
+            .get(&env.ident_map.binding_id(ident)?)
+            .cloned(),
+        _ => fold::Const::of_data(data),
+    })
+}
 
-    ans
+fn compile_optional_constant(
+    env: &Env,
+    lit: Option<&parse::Data>,
+    default_type: lex::Type,
+) -> Result<Option<fold::Const>, Error> {
+    Ok(match lit {
+        None if env.optimize => match default_type {
+            lex::Type::Shu4 => Some(fold::Const::Num(0.0)),
+            lex::Type::Lie4 => None,
+            lex::Type::Yan2 => Some(fold::Const::Str(S(""))),
+            lex::Type::Yao2 => Some(fold::Const::Bool(false)),
+        },
+        None => None,
+        Some(v) => constant(env, v)?,
+    })
 }
 
-fn compile_forenum(mut env: &mut Env, num: i64, statements: &[parse::Statement]) -> Vec<Line> {
-    let mut r = vec![(env.indent_level, format!("for _ in 0..{} {{", num,))];
-    env.indent_level += 1;
-    for st in statements {
-        r.append(&mut compile_statement(&mut env, &st));
+/// The 0-based index from a 1-based one held in a variable; with `-O`, a known index is written as a literal
+fn compile_index(env: &Env, ident: &parse::Identifier) -> Result<String, Error> {
+    let data = parse::Data::Identifier(ident.clone());
+    not_spilled(env, &data, "a value of the runtime stack as an index")?;
+    Ok(match env.constants.get(&env.ident_map.binding_id(ident)?) {
+        #[allow(clippy::cast_possible_truncation)]
+        Some(fold::Const::Num(x)) if x.fract() == 0.0 && *x >= 1.0 => {
+            format!("{} - 1", *x as i64)
+        }
+        _ => format!("({} as usize) - 1", compile_ident(env, ident)?),
+    })
+}
+
+/// In integer mode, the numbers folded are integers as well
+fn compile_constant(env: &Env, value: &fold::Const) -> String {
+    match value {
+        #[allow(clippy::cast_possible_truncation)]
+        fold::Const::Num(x) if env.integers => (*x as i64).to_string(),
+        _ => value.to_rust(),
     }
-    env.indent_level -= 1;
-    r.push((env.indent_level, S("}")));
-    r
 }
 
-fn compile_dataorqi2(env: &mut Env, a: &parse::OrQi2<parse::Data>) -> String {
-    match a {
-        parse::OrQi2::Qi2 => {
-            let qi = env
-                .variables_not_yet_named
-                .last()
-                .unwrap_or(&S("f64::NAN"))
-                .to_string();
-
-            //《文言陰符》曰『言「其」者。取至近之魚而棄其餘。』
-            env.variables_not_yet_named = vec![];
-            qi
-        }
-        parse::OrQi2::NotQi2(data) => compile_literal(&env, &data),
-    }
-}
-
-/// 吾有三數。曰三曰五曰二名之曰「甲」。加其以五。
-/// is to be translated as
-/// ```
-/// var 甲 = 3;
-/// var _ans1 = 5;
-/// var _ans2 = 2;
-/// const _ans3 = _ans2 + 5;
-/// ```
-
-/// 加其以五。書之。
-/// is to be translated as
-/// ```
-/// const _ans1 = undefined + 5;
-/// console.log(_ans1);
-/// ```
-/// Thus, when we do not have anything to reference, I must pad with `f64::NAN`
-
-/// Both
-/// 加一以三。加二以三。減其以其
-/// and
-/// 加一以三。加二以三。減其於其
-/// are compiled to
-/// ```
-/// const _ans1 = 1 + 3;
-/// const _ans2 = 2 + 3;
-/// const _ans3 = _ans2 - undefined;
-/// ```
-
-fn compile_math(mut env: &mut Env, math: &parse::MathKind) -> Vec<Line> {
-    match math {
-        parse::MathKind::BooleanAlgebra(ident1, ident2, op) => {
-            let data1 = parse::OrQi2::NotQi2(parse::Data::Identifier(ident1.clone()));
-            let data2 = parse::OrQi2::NotQi2(parse::Data::Identifier(ident2.clone()));
-            compile_math_binary(
-                &mut env,
-                op.to_str(),
-                &data1,
-                lex::Preposition::Yi3, /* whichever is fine */
-                &data2,
-            )
-        }
-        parse::MathKind::ArithBinaryMath(op, data1, prep, data2) => {
-            compile_math_binary(&mut env, op.to_str(), &data1, *prep, &data2)
-        }
-        parse::MathKind::ModMath(op, data1, prep, data2) => {
-            compile_math_binary(&mut env, op.to_str(), &data1, *prep, &data2)
-        }
-        parse::MathKind::ArithUnaryMath(data) => {
-            let a = compile_dataorqi2(&mut env, data);
-            let r = vec![(
-                env.indent_level,
-                format!("let _ans{} = !{};", get_new_unnamed_var(&mut env), a,),
-            )];
+/// A variable is written as its value if it is known
+fn compile_ident(env: &Env, ident: &parse::Identifier) -> Result<String, Error> {
+    match env.constants.get(&env.ident_map.binding_id(ident)?) {
+        Some(value) => Ok(compile_constant(env, value)),
+        None => env.ident_map.translate_from_hanzi(ident),
+    }
+}
 
-            r
+fn compile_literal(env: &Env, v: &parse::Data) -> Result<String, Error> {
+    Ok(match v.clone() {
+        parse::Data::BoolValue(true) => S("true"),
+        parse::Data::BoolValue(false) => S("false"),
+        parse::Data::Identifier(ident) => compile_ident(env, &ident)?,
+        parse::Data::IntNum(intnum) if env.integers => intnum.to_string(),
+        parse::Data::IntNum(intnum) => format!("{}.0", intnum),
+        parse::Data::StringLiteral(strlit) => format!("\"{}\"", strlit), // FIXME properly escape
+    })
+}
+
+/// The value of `ir::Operand::Temp` and the like, which the IR takes off the stack only once
+fn take_unnamed(env: &mut Env, temp: ir::Temp) -> Unnamed {
+    env.unnamed
+        .remove(&temp)
+        .expect("the IR only takes the values on the stack")
+}
+
+fn take_operand(env: &mut Env, a: &ir::Operand) -> Result<Expr, Error> {
+    Ok(match a {
+        ir::Operand::Temp(temp) => {
+            let mut expr = take_unnamed(env, *temp).into_expr();
+            expr.spilled |= env.spilled_temps.contains(temp);
+            expr
         }
+        ir::Operand::Undefined => Expr::literal(
+            S("f64::NAN"),
+            Some(fold::Const::Num(f64::NAN)).filter(|_| env.optimize),
+        ),
+        ir::Operand::Data(data) => {
+            let mut expr = Expr::literal(compile_literal(&env, &data)?, constant(env, data)?);
+            if is_spilled(env, data)? {
+                expr = expr.cloned();
+            }
+            expr
+        }
+    })
+}
+
+/// The list that `充` or `銜` works on, which must be a variable
+fn compile_list(env: &Env, list: &ir::Operand) -> Result<String, Error> {
+    match list {
+        ir::Operand::Data(data @ parse::Data::Identifier(ident)) => {
+            not_spilled(env, data, "a value of the runtime stack as a list")?;
+            env.ident_map.translate_from_hanzi(ident)
+        }
+        _ => Err(Error::Unsupported("filling or concatenating to 其")),
     }
 }
 
 fn compile_math_binary(
-    mut env: &mut Env,
+    env: &mut Env,
     opstr: &str,
-    data1: &parse::OrQi2<parse::Data>,
-    prep: lex::Preposition,
-    data2: &parse::OrQi2<parse::Data>,
-) -> Vec<Line> {
-    let left = compile_dataorqi2(
-        &mut env,
-        match prep {
-            lex::Preposition::Yi3 => &data1,
-            lex::Preposition::Yu2 => &data2,
-        },
-    );
+    left: &ir::Operand,
+    right: &ir::Operand,
+) -> Result<Expr, Error> {
+    // the operators are all left-associative
+    let p = precedence::of_binary(opstr);
+    // as JavaScript converts them
+    let into = if opstr == "&&" || opstr == "||" {
+        "bool"
+    } else {
+        "f64"
+    };
+    let left = take_operand(env, left)?.converted(into);
+    let right = take_operand(env, right)?.converted(into);
+    let folded = match (&left.value, &right.value) {
+        (Some(a), Some(b)) => fold::binary(opstr, a, b),
+        _ => None,
+    };
+    Ok(match folded {
+        Some(value) => Expr::constant(&env, value),
+        None => Expr::new(
+            format!("{} {} {}", left.operand(p), opstr, right.operand(p + 1)),
+            p,
+        ),
+    })
+}
 
-    let right = compile_dataorqi2(
-        &mut env,
-        match prep {
-            lex::Preposition::Yi3 => &data2,
-            lex::Preposition::Yu2 => &data1,
-        },
-    );
+fn compile_value(env: &mut Env, v: &parse::Value<ir::Operand>) -> Result<Expr, Error> {
+    Ok(match v {
+        parse::Value::Simple(a) => take_operand(env, a)?,
+        parse::Value::Length(a) => Expr::new(
+            format!(
+                "{}.len() as {}",
+                take_list(env, a)?.operand(precedence::ATOM),
+                number_type(env)
+            ),
+            precedence::CAST,
+        ),
+        parse::Value::Index(a, index) => Expr::new(
+            format!(
+                "{}[{} - 1]",
+                take_list(env, a)?.operand(precedence::ATOM),
+                index
+            ),
+            precedence::ATOM,
+        ),
+        parse::Value::IndexByIdent(a, index) => Expr::new(
+            format!(
+                "{}[{}]",
+                take_list(env, a)?.operand(precedence::ATOM),
+                compile_index(&env, &index)?
+            ),
+            precedence::ATOM,
+        ),
+    })
+}
 
-    let r = vec![(
-        env.indent_level,
-        format!(
-            "let _ans{} = {} {} {};",
-            get_new_unnamed_var(&mut env),
-            left,
-            opstr,
-            right,
+/// An operand that must be a list
+fn take_list(env: &mut Env, a: &ir::Operand) -> Result<Expr, Error> {
+    let list = take_operand(env, a)?;
+    if list.spilled {
+        return Err(Error::Unsupported("a value of the runtime stack as a list"));
+    }
+    Ok(list)
+}
+
+fn compile_expr(env: &mut Env, expr: &ir::Expr) -> Result<Expr, Error> {
+    Ok(match expr {
+        ir::Expr::Init(type_, data) => compile_optional_expr(&env, data.as_ref(), *type_)?,
+        ir::Expr::Value(v) => compile_value(env, v)?,
+        ir::Expr::Binary(op, left, right) => compile_math_binary(env, op, left, right)?,
+        ir::Expr::Not(a) => {
+            let a = take_operand(env, a)?.converted("bool");
+            match a.value.as_ref().and_then(fold::not) {
+                Some(value) => Expr::constant(&env, value),
+                None => Expr::new(
+                    format!("!{}", a.operand(precedence::UNARY)),
+                    precedence::UNARY,
+                ),
+            }
+        }
+        ir::Expr::Rest(a) => Expr::new(
+            format!(
+                "&{}[1..].to_vec()",
+                take_list(env, a)?.operand(precedence::ATOM)
+            ),
+            precedence::UNARY,
         ),
-    )];
+        ir::Expr::Concat(list, elems) => Expr::new(
+            format!(
+                "[&{}[..], {}].concat()",
+                compile_list(env, list)?,
+                elems
+                    .iter()
+                    .map(|e| {
+                        let data = parse::Data::Identifier(e.clone());
+                        not_spilled(env, &data, "a value of the runtime stack as a list")?;
+                        Ok(format!("&{}[..]", env.ident_map.translate_from_hanzi(&e)?))
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .join(", ")
+            ),
+            precedence::ATOM,
+        ),
+    })
+}
 
-    r
+/// Whether the value must be evaluated right where it is pushed
+fn may_panic(expr: &ir::Expr) -> bool {
+    matches!(
+        expr,
+        ir::Expr::Value(parse::Value::Index(..) | parse::Value::IndexByIdent(..))
+            | ir::Expr::Rest(_)
+    )
+}
+
+/// `let name = value;`, where the value may refer to an earlier binding of the same name
+fn compile_declare(
+    env: &mut Env,
+    ident: &parse::Identifier,
+    value: &ir::Expr,
+) -> Result<Vec<Line>, Error> {
+    let expr = compile_expr(env, value)?;
+    let mutability = match declare_with_value(env, ident, expr.value.clone())? {
+        Some(mutability) => mutability,
+        None => return Ok(vec![]),
+    };
+    if expr.spilled {
+        let id = env.ident_map.binding_id(ident)?;
+        env.spilled_bindings.insert(id);
+    }
+    let name = env.ident_map.translate_from_hanzi(ident)?;
+    let mut r = bind_pending_mentioning(env, &name);
+    r.push(env.line(if env.ident_map.is_deferred(ident)? {
+        format!("let {}{};", mutability, name)
+    } else {
+        format!("let {}{} = {};", mutability, name, expr.text)
+    }));
+    Ok(r)
 }
 
-/// 加一以三。加六以九。名之曰「甲」。曰「乙」。
-/// is to be translated as
-/// ```
-/// const _ans1 = 1 + 3;
-/// const _ans2 = 6 + 9;
-/// var JIA3 = _ans1;
-/// var YI3 = _ans2;
-/// ```
-
-/// 加二以三。加一以三。加三以三。名之曰「甲」。名之曰「乙」。書之
-/// is to be translated as
-/// ```
-/// const _ans1 = 2 + 3;
-/// const _ans2 = 1 + 3;
-/// const _ans3 = 3 + 3;
-/// var JIA3 = _ans3;
-/// var YI3 = _ans2;
-/// console.log(_ans1);
-/// ```
-/// That is, [_ans1, _ans2, _ans3] is matched from the end by the first 名之曰,
-/// leaving [_ans1, _ans2]; then, this is matched from the end by the second 名之曰,
-/// leaving [_ans1].
-
-fn compile_name_multi_statement(mut env: &mut Env, idents: &[parse::Identifier]) -> Vec<Line> {
+/// A value named by nothing is `undefined`, which is `()`
+fn compile_name_multi_statement(
+    env: &mut Env,
+    named: &[(parse::Identifier, Option<ir::Temp>)],
+) -> Result<Vec<Line>, Error> {
     let mut res = vec![];
-    for i in 0..idents.len() {
-        res.push((
-            env.indent_level,
-            if env.variables_not_yet_named.len() + i < idents.len() {
-                // negative index is to be filled with undefined
-                format!(
-                    "let {}{} : (); // undefined",
-                    ifmutable_thenmut(&env, &idents[i]),
-                    env.ident_map.translate_from_hanzi(&idents[i])
-                )
-            } else {
-                let tmpvarname = env.variables_not_yet_named
-                    [env.variables_not_yet_named.len() + i - idents.len()]
-                .clone();
+    for (ident, temp) in named {
+        let value = temp.and_then(|t| env.unnamed[&t].value().cloned());
+        let mutability = match declare_with_value(env, ident, value)? {
+            Some(mutability) => mutability,
+            None => continue,
+        };
+        if matches!(temp, Some(t) if env.spilled_temps.contains(t)) {
+            let id = env.ident_map.binding_id(ident)?;
+            env.spilled_bindings.insert(id);
+        }
+        let name = env.ident_map.translate_from_hanzi(ident)?;
+        // the other values must still read the variable that `name` shadows
+        res.append(&mut bind_pending(env, |t, expr| {
+            Some(t) != *temp && mentions(expr, &name)
+        }));
+        res.push(env.line(match temp {
+            _ if env.ident_map.is_deferred(ident)? => format!("let {}{};", mutability, name),
+            None => format!("let {}{} : (); // undefined", mutability, name),
+            Some(t) => format!("let {}{} = {};", mutability, name, env.unnamed[t].whole()),
+        }));
+    }
+    for (_, temp) in named {
+        if let Some(t) = temp {
+            take_unnamed(env, *t);
+        }
+    }
+    Ok(res)
+}
+
+/// An operand of a comparison; a cast is parenthesized, since `as f64 <` would be taken for the start of generics
+fn compile_unaryifexpr(env: &mut Env, v: &parse::Value<ir::Operand>) -> Result<Expr, Error> {
+    match v {
+        parse::Value::Simple(a) => take_operand(env, a),
+        _ => {
+            let expr = compile_value(env, v)?;
+            Ok(Expr::new(expr.operand(precedence::ATOM), precedence::ATOM))
+        }
+    }
+}
 
+fn compile_ifcond(env: &mut Env, cond: &ir::Cond, keyword: &str) -> Result<Line, Error> {
+    let cond = match cond {
+        ir::Cond::Binary(left, op, right) => {
+            let mut left = compile_unaryifexpr(env, left)?;
+            let mut right = compile_unaryifexpr(env, right)?;
+            // a `Value` of the runtime stack is compared with another
+            if left.spilled || right.spilled {
+                left = left.converted_to_value();
+                right = right.converted_to_value();
+            }
+            let folded = match (&left.value, &right.value) {
+                (Some(a), Some(b)) => fold::compare(op, a, b).map(|b| b.to_string()),
+                _ => None,
+            };
+            folded.unwrap_or_else(|| {
                 format!(
-                    "let {}{} = {};",
-                    ifmutable_thenmut(&env, &idents[i]),
-                    env.ident_map.translate_from_hanzi(&idents[i]),
-                    tmpvarname.clone()
+                    "{} {} {}",
+                    left.operand(precedence::COMPARISON + 1),
+                    op,
+                    right.operand(precedence::COMPARISON + 1)
                 )
-            },
-        ));
+            })
+        }
+        ir::Cond::Unary(a) => compile_unaryifexpr(env, a)?.converted("bool").text,
+        ir::Cond::Not(a) => format!(
+            "!{}",
+            take_operand(env, a)?
+                .converted("bool")
+                .operand(precedence::UNARY)
+        ),
+    };
+    Ok(env.line(format!("{} {} {{", keyword, cond)))
+}
+
+fn compile_if(
+    env: &mut Env,
+    branches: &[ir::Branch],
+    else_: &[ir::Op],
+) -> Result<Vec<Line>, Error> {
+    let mut r = vec![];
+    let mut outer = None;
+    // the `if`s in an `else`, see below
+    let mut nested = 0;
+    for (i, branch) in branches.iter().enumerate() {
+        if i == 0 {
+            r.append(&mut compile_ops(env, &branch.pops)?);
+            let ifline = compile_ifcond(env, &branch.cond, "if")?;
+            // the values not yet named are bound before the branches, which may use them;
+            // the `Pop`s of the other conditions then only drop bound values, which writes nothing
+            r.append(&mut bind_pending(env, |_, _| true));
+            r.push(ifline);
+            outer = Some(env.unnamed.clone());
+        } else {
+            // but taking a value off a runtime stack does, so that the condition is then an `if` in an `else`
+            env.indent_level += 1;
+            let mut pops = compile_ops(env, &branch.pops)?;
+            env.indent_level -= 1;
+            if pops.is_empty() {
+                r.push(compile_ifcond(env, &branch.cond, "} else if")?);
+            } else {
+                r.push(env.line(S("} else {")));
+                env.indent_level += 1;
+                nested += 1;
+                r.append(&mut pops);
+                r.push(compile_ifcond(env, &branch.cond, "if")?);
+            }
+        }
+        compile_block(env, &mut r, &branch.body)?;
     }
-    if env.variables_not_yet_named.len() > idents.len() {
-        env.variables_not_yet_named
-            .truncate(env.variables_not_yet_named.len() - idents.len());
+
+    if else_.is_empty() {
+        // the scope is still there for `IdentBiMap`
+        env.ident_map.enter_scope();
+        env.ident_map.exit_scope();
     } else {
-        env.variables_not_yet_named = vec![]
+        r.push(env.line(S("} else {")));
+        compile_block(env, &mut r, else_)?;
     }
+    r.push(env.line(S("}")));
+    for _ in 0..nested {
+        env.indent_level -= 1;
+        r.push(env.line(S("}")));
+    }
+    // the IR then drops the values that a condition took
+    if let Some(outer) = outer {
+        env.unnamed = outer;
+    }
+    Ok(r)
+}
 
-    res
+/// A value of the runtime stack, where the ways through a block may leave values of different types,
+/// `NUMBER` being the type of the numbers
+const VALUE: &str = r#"/// a value of the runtime stack
+#[derive(Clone, Default, PartialEq, PartialOrd)]
+enum Value {
+    Num(NUMBER),
+    Str(String),
+    Bool(bool),
+    List(Vec<Value>),
+    /// what `其` takes off the stack when it is empty
+    #[default]
+    Undefined,
 }
 
-fn compile_rvalue_noqi2(
-    mut env: &mut Env,
-    rv: &parse::Value<parse::Data>,
-    paren_when_casted: bool,
-) -> String {
-    match rv {
-        parse::Value::Simple(d) => compile_dataorqi2(&mut env, &parse::OrQi2::NotQi2(d.clone())),
-        parse::Value::Length(d) => {
-            if paren_when_casted {
-                format!(
-                    "({}.len() as f64)",
-                    compile_dataorqi2(&mut env, &parse::OrQi2::NotQi2(d.clone()))
-                )
-            } else {
-                format!(
-                    "{}.len() as f64",
-                    compile_dataorqi2(&mut env, &parse::OrQi2::NotQi2(d.clone()))
-                )
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Num(x) => write!(f, "{}", x),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::List(elems) => {
+                let elems = elems.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "[ {} ]", elems.join(", "))
             }
+            Value::Undefined => write!(f, "undefined"),
         }
-        parse::Value::Index(d, ind) => format!(
-            "{}[{} - 1]",
-            compile_dataorqi2(&mut env, &parse::OrQi2::NotQi2(d.clone())),
-            ind
-        ),
-        parse::Value::IndexByIdent(d, ident) => format!(
-            "{}[({} as usize) - 1]",
-            compile_dataorqi2(&mut env, &parse::OrQi2::NotQi2(d.clone())),
-            env.ident_map.translate_from_hanzi(&ident)
-        ),
     }
 }
 
-fn compile_unaryifexpr(mut env: &mut Env, unary: &parse::UnaryIfExpr) -> String {
-    match unary {
-        parse::UnaryIfExpr::Simple(data1) => compile_dataorqi2(&mut env, data1),
-        parse::UnaryIfExpr::Complex(rv) => compile_rvalue_noqi2(&mut env, &rv, true),
+impl From<NUMBER> for Value {
+    fn from(x: NUMBER) -> Self {
+        Value::Num(x)
     }
 }
 
-fn compile_ifcond(mut env: &mut Env, ifcond: &parse::IfCond, keyword: &str) -> Line {
-    match ifcond {
-        parse::IfCond::Binary(data1, op, data2) => (
-            env.indent_level,
-            format!(
-                "{} {} {} {} {{",
-                keyword,
-                compile_unaryifexpr(&mut env, data1),
-                op.to_str(),
-                compile_unaryifexpr(&mut env, data2),
-            ),
-        ),
-        parse::IfCond::Unary(data1) => (
-            env.indent_level,
-            format!("{} {} {{", keyword, compile_unaryifexpr(&mut env, data1)),
-        ),
-        parse::IfCond::NotQi2 => (
-            env.indent_level,
-            format!(
-                "{} !{} {{",
-                keyword,
-                compile_dataorqi2(&mut env, &parse::OrQi2::Qi2),
-            ),
-        ),
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
     }
 }
 
-fn compile_if(
-    mut env: &mut Env,
-    ifcase: &parse::CondPlusStatements,
-    elseifcases: &[parse::CondPlusStatements],
-    elsecase: &[parse::Statement],
-) -> Vec<Line> {
-    let (ifcond, ifstmts) = ifcase;
-    let mut r = vec![compile_ifcond(&mut env, ifcond, "if")];
-    compile_indent(&mut env, &mut r, &ifstmts);
-    for (elseifcond, elseifstmts) in elseifcases {
-        r.push(compile_ifcond(&mut env, elseifcond, "} else if"));
-        compile_indent(&mut env, &mut r, &elseifstmts)
-    }
-
-    if !elsecase.is_empty() {
-        r.push((env.indent_level, S("} else {")));
-        compile_indent(&mut env, &mut r, &elsecase)
-    }
-    r.push((env.indent_level, S("}")));
-    r
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(elems: Vec<T>) -> Self {
+        Value::List(elems.into_iter().map(Into::into).collect())
+    }
+}
+"#;
+
+/// How a value of the runtime stack is used where a number or a boolean is needed, as in JavaScript
+const CONVERSIONS: &str = r#"
+impl From<Value> for f64 {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Num(x) => x,
+            Value::Bool(b) => f64::from(u8::from(b)),
+            Value::Str(s) if s.trim().is_empty() => 0.0,
+            Value::Str(s) => s.trim().parse().unwrap_or(f64::NAN),
+            Value::List(_) | Value::Undefined => f64::NAN,
+        }
+    }
+}
+
+impl From<Value> for bool {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Num(x) => x != 0.0 && !x.is_nan(),
+            Value::Str(s) => !s.is_empty(),
+            Value::Bool(b) => b,
+            Value::List(_) => true,
+            Value::Undefined => false,
+        }
+    }
+}
+"#;
+
+/// The type of the runtime stack, with only the methods that `used` lists, not to get warnings about the others.
+/// Its values are `Value`s, their numbers being `number`.
+fn compile_stack(used: &HashSet<&'static str>, number: &str) -> Vec<Line> {
+    let mut methods = vec![];
+    if ["pop", "take", "clear", "leave"]
+        .iter()
+        .any(|method| used.contains(method))
+    {
+        methods.push(
+            "    fn shrunk(&mut self) {
+        let len = self.values.len();
+        for low in &mut self.lows {
+            *low = (*low).min(len);
+        }
+    }",
+        );
+    }
+    if used.contains("pop") {
+        methods.push(
+            "    fn pop(&mut self) -> Value {
+        let value = self.values.pop().unwrap_or_default();
+        self.shrunk();
+        value
+    }",
+        );
+    }
+    if used.contains("take") {
+        methods.push(
+            "    fn take(&mut self) -> Vec<Value> {
+        let values = std::mem::take(&mut self.values);
+        self.shrunk();
+        values
+    }",
+        );
+    }
+    if used.contains("clear") {
+        methods.push(
+            "    fn clear(&mut self) {
+        self.values.clear();
+        self.shrunk();
+    }",
+        );
+    }
+    methods.push(
+        "    fn enter(&mut self) {
+        self.lows.push(self.values.len());
+    }",
+    );
+    if used.contains("restore") {
+        methods.push(
+            "    fn restore(&mut self, entry: &[Value]) {
+        self.values = entry.to_vec();
+        *self.lows.last_mut().unwrap() = entry.len();
+    }",
+        );
+    }
+    if used.contains("leave") {
+        methods.push(
+            "    fn leave(&mut self) {
+        let low = self.lows.pop().unwrap();
+        self.values.truncate(low);
+        self.shrunk();
+    }",
+        );
+    }
+    // a number is only needed from a value taken off the stack, which is never done in integer mode
+    let conversions = if number == "f64" { CONVERSIONS } else { "" };
+    let text = format!(
+        "{}{}
+/// the values before the blocks whose ways through leave different numbers of them
+struct Stack {{
+    values: Vec<Value>,
+    /// for each of these blocks being run, how many of the values before it are left
+    lows: Vec<usize>,
+}}
+
+impl Stack {{
+{}
+}}
+",
+        VALUE.replace("NUMBER", number),
+        conversions,
+        methods.join("\n\n")
+    );
+    // followed by an empty line
+    text.split('\n')
+        .map(|line| Line::outside(line.to_string()))
+        .collect()
+}
+
+/// A call to a method of `_stack`
+fn call_stack(env: &mut Env, method: &'static str, args: &str) -> String {
+    env.stack_methods.insert(method);
+    format!("_stack.{}({})", method, args)
 }
 
-fn get_new_unnamed_var(mut env: &mut Env) -> usize {
+/// `let _ansN = expr;`, returning the line and `_ansN`
+fn new_temporary(env: &mut Env, expr: &str) -> (Line, Unnamed) {
     env.ans_counter += 1;
-    env.variables_not_yet_named
-        .push(format!("_ans{}", env.ans_counter));
-    env.ans_counter
-}
-
-fn compile_statement(mut env: &mut Env, st: &parse::Statement) -> Vec<Line> {
-    match st {
-        parse::Statement::ArrayCat {
-            append_to: parse::OrQi2::NotQi2(ident),
-            elems,
-        } => vec![(
-            env.indent_level,
-            format!(
-                "let _ans{} = [&{}[..], {}].concat();",
-                get_new_unnamed_var(&mut env),
-                env.ident_map.translate_from_hanzi(&ident),
-                elems
+    let name = format!("_ans{}", env.ans_counter);
+    let line = env.line(format!("let {} = {};", name, expr));
+    (line, Unnamed::Bound(name))
+}
+
+/// Pushes a value that has to be evaluated right here, e.g. since it may panic
+fn bind_unnamed(env: &mut Env, temp: ir::Temp, expr: &str) -> Line {
+    let (line, unnamed) = new_temporary(env, expr);
+    env.unnamed.insert(temp, unnamed);
+    line
+}
+
+/// Pushes a value without side effects, which with `-O` is evaluated where it is used
+fn push_unnamed(env: &mut Env, temp: ir::Temp, expr: Expr) -> Vec<Line> {
+    if env.optimize {
+        env.unnamed.insert(temp, Unnamed::Pending(expr));
+        vec![]
+    } else {
+        vec![bind_unnamed(env, temp, &expr.text)]
+    }
+}
+
+/// Binds the pending values for which `needs_binding(temporary, expression)` holds.
+/// A value known when compiling is never bound, since it reads nothing.
+fn bind_pending(env: &mut Env, needs_binding: impl Fn(ir::Temp, &str) -> bool) -> Vec<Line> {
+    let mut r = vec![];
+    let temps = env.unnamed.keys().copied().collect::<Vec<_>>();
+    for t in temps {
+        if let Unnamed::Pending(Expr {
+            text, value: None, ..
+        }) = &env.unnamed[&t]
+        {
+            if needs_binding(t, text) {
+                let expr = text.clone();
+                let (line, unnamed) = new_temporary(env, &expr);
+                r.push(line);
+                env.unnamed.insert(t, unnamed);
+            }
+        }
+    }
+    r
+}
+
+/// Binds the pending values that read `name`, which is about to be declared, assigned to or modified
+fn bind_pending_mentioning(env: &mut Env, name: &str) -> Vec<Line> {
+    bind_pending(env, |_, expr| mentions(expr, name))
+}
+
+/// Drops values of the stack. A pending value is dropped, unless it reads a variable,
+/// which would then be unused if it is its only read; such a value is bound to `_ansN` that is never used.
+fn discard_unnamed(env: &mut Env, temps: &[ir::Temp]) -> Vec<Line> {
+    let reads_variable = temps
+        .iter()
+        .copied()
+        .filter(|t| {
+            words(env.unnamed[t].whole())
+                .iter()
+                .any(|w| env.ident_map.is_variable(w))
+        })
+        .collect::<Vec<_>>();
+    let r = bind_pending(env, |t, _| reads_variable.contains(&t));
+    for t in temps {
+        take_unnamed(env, *t);
+    }
+    r
+}
+
+fn compile_op(env: &mut Env, op: &ir::Op) -> Result<Vec<Line>, Error> {
+    use ir::Op::*;
+    // With `-O`, the values not yet named must be bound before a block, which may use them any number of times,
+    // and before `break` and `continue`, after which nothing is reached.
+    // The other ops bind those they have to.
+    let mut r = match op {
+        Repeat(..) | RepeatBy(..) | ForEach { .. } | Loop(_) | Break | Continue => {
+            bind_pending(env, |_, _| true)
+        }
+        _ => vec![],
+    };
+    r.append(&mut compile_op_inner(env, op)?);
+    Ok(r)
+}
+
+fn compile_op_inner(env: &mut Env, op: &ir::Op) -> Result<Vec<Line>, Error> {
+    Ok(match op {
+        ir::Op::Push(temp, expr) => {
+            let rust = compile_expr(env, expr)?;
+            if rust.spilled {
+                env.spilled_temps.insert(*temp);
+            }
+            if may_panic(expr) {
+                vec![bind_unnamed(env, *temp, &rust.text)]
+            } else {
+                push_unnamed(env, *temp, rust)
+            }
+        }
+        // `top` is taken by the op that follows
+        ir::Op::Pop { top: _, dropped } => discard_unnamed(env, dropped),
+        ir::Op::Name(named) => compile_name_multi_statement(env, named)?,
+        ir::Op::Declare(ident, value) => compile_declare(env, ident, value)?,
+        ir::Op::Print(temps) => {
+            let values = temps
+                .iter()
+                .map(|t| take_unnamed(env, *t))
+                .collect::<Vec<_>>();
+            vec![env.line(format!(
+                "println!(\"{}\"{});",
+                "{} ".repeat(values.len()).trim_end(),
+                values
                     .iter()
-                    .map(|e| format!("&{}[..]", env.ident_map.translate_from_hanzi(&e)))
+                    .map(|unnamed| format!(", {}", unnamed.whole()))
                     .collect::<Vec<_>>()
-                    .join(", ")
-            ),
-        )],
-        parse::Statement::Continue => vec![(env.indent_level, S("continue;"))],
-        parse::Statement::Break => vec![(env.indent_level, S("break;"))],
-        parse::Statement::ArrayFill {
-            what_to_fill: parse::OrQi2::NotQi2(ident),
-            elems,
-        } => vec![(
-            env.indent_level,
-            if let [e] = elems.as_slice() {
-                format!(
-                    "{}.push({});",
-                    env.ident_map.translate_from_hanzi(&ident),
-                    compile_literal(&env, e)
-                )
+                    .join("")
+            ))]
+        }
+        ir::Op::Flush(temps) => discard_unnamed(env, temps),
+        ir::Op::Spill(temps) => {
+            let mut r = bind_pending(env, |t, _| temps.contains(&t));
+            for t in temps {
+                // nothing reads the temporary again
+                let value = env.unnamed[t].whole().to_string();
+                r.push(env.line(format!("_stack.values.push(Value::from({}));", value)));
+            }
+            r
+        }
+        // the top goes to the last temporary; a value missing is `undefined`, as with `Operand::Undefined`
+        ir::Op::Unspill(temps) => temps
+            .iter()
+            .rev()
+            .map(|t| {
+                let expr = call_stack(env, "pop", "");
+                env.spilled_temps.insert(*t);
+                bind_unnamed(env, *t, &expr)
+            })
+            .collect(),
+        ir::Op::PrintSpilled(temps) => {
+            let values = temps
+                .iter()
+                .map(|t| {
+                    let value = take_unnamed(env, *t).into_expr();
+                    format!("{}.to_string()", value.operand(precedence::ATOM))
+                })
+                .collect::<Vec<_>>();
+            let above = if values.is_empty() {
+                S("")
+            } else {
+                format!(".chain(vec![{}])", values.join(", "))
+            };
+            let spilled = call_stack(env, "take", "");
+            vec![env.line(format!(
+                "println!(\"{{}}\", {}.iter().map(|v| v.to_string()){}.collect::<Vec<_>>().join(\" \"));",
+                spilled, above
+            ))]
+        }
+        ir::Op::DropSpilled => {
+            let call = call_stack(env, "clear", "");
+            vec![env.line(format!("{};", call))]
+        }
+        ir::Op::Enter(block) => {
+            let call = call_stack(env, "enter", "");
+            let mut r = vec![env.line(format!("{};", call))];
+            if let Some(block) = block {
+                r.push(env.line(format!("let _entry{} = _stack.values.clone();", block)));
+            }
+            r
+        }
+        ir::Op::Restore(block) => {
+            let call = call_stack(env, "restore", &format!("&_entry{}", block));
+            vec![env.line(format!("{};", call))]
+        }
+        ir::Op::Leave => {
+            let call = call_stack(env, "leave", "");
+            vec![env.line(format!("{};", call))]
+        }
+        // the blocks left by the jump
+        ir::Op::Unmark(n) => {
+            vec![env.line(format!("_stack.lows.truncate(_stack.lows.len() - {});", n))]
+        }
+        ir::Op::Assign(parse::Lvalue::Simple(_), rvalue) if env.ident_map.assign() => {
+            // rustc warns about a value never read
+            let rvalue = compile_value(env, rvalue)?.text;
+            vec![env.line(format!("let _ = {};", rvalue))]
+        }
+        ir::Op::Assign(lvalue, rvalue) => {
+            let rvalue = compile_value(env, rvalue)?;
+            let rvalue = match lvalue {
+                parse::Lvalue::Simple(ident)
+                    if is_spilled(env, &parse::Data::Identifier(ident.clone()))? =>
+                {
+                    rvalue.converted_to_value().text
+                }
+                // the type of the variable is not known here
+                _ if rvalue.spilled => {
+                    return Err(Error::Unsupported(
+                        "assigning a value of the runtime stack to a variable",
+                    ))
+                }
+                parse::Lvalue::Index(ident, _) | parse::Lvalue::IndexByIdent(ident, _) => {
+                    let data = parse::Data::Identifier(ident.clone());
+                    not_spilled(env, &data, "a value of the runtime stack as a list")?;
+                    rvalue.text
+                }
+                parse::Lvalue::Simple(_) => rvalue.text,
+            };
+            let name = match lvalue {
+                parse::Lvalue::Simple(ident)
+                | parse::Lvalue::Index(ident, _)
+                | parse::Lvalue::IndexByIdent(ident, _) => {
+                    env.ident_map.translate_from_hanzi(ident)?
+                }
+            };
+            let mut r = bind_pending_mentioning(env, &name);
+            r.push(env.line(format!("{} = {};", compile_lvalue(&env, lvalue)?, rvalue)));
+            r
+        }
+        ir::Op::Fill(list, elems) => {
+            let name = compile_list(env, list)?;
+            for e in elems {
+                not_spilled(env, e, "filling a list with a value of the runtime stack")?;
+            }
+            let mut r = bind_pending_mentioning(env, &name);
+            r.push(env.line(if let [e] = elems.as_slice() {
+                format!("{}.push({});", name, compile_literal(&env, e)?)
             } else {
                 format!(
                     "{}.append(&mut vec![{}]);",
-                    env.ident_map.translate_from_hanzi(&ident),
+                    name,
                     elems
                         .iter()
                         .map(|e| compile_literal(&env, e))
-                        .collect::<Vec<_>>()
+                        .collect::<Result<Vec<_>, Error>>()?
                         .join(", ")
                 )
-            },
-        )],
-        parse::Statement::ArrayFill {
-            what_to_fill: parse::OrQi2::Qi2,
-            elems: _,
-        }
-        | parse::Statement::ArrayCat {
-            append_to: parse::OrQi2::Qi2,
-            elems: _,
-        } => unimplemented!("filling qi2"),
-        parse::Statement::If {
-            ifcase,
-            elseifcases,
-            elsecase,
-        } => compile_if(&mut env, ifcase, elseifcases, elsecase),
-        parse::Statement::Reference { rvalue } => vec![(
-            env.indent_level,
-            format!(
-                "let _ans{} = {};",
-                get_new_unnamed_var(&mut env),
-                compile_rvalue_noqi2(&mut env, rvalue, false)
-            ),
-        )],
-
-        parse::Statement::NameMulti { idents } => compile_name_multi_statement(&mut env, &idents),
-        parse::Statement::Flush => {
-            env.variables_not_yet_named = vec![];
-            vec![]
-        }
-        parse::Statement::Math { math } => compile_math(&mut env, math),
-        parse::Statement::ReferenceWhatIsLeft { data } => vec![(
-            env.indent_level,
-            format!(
-                "let  _ans{} = &{}[1..].to_vec();",
-                get_new_unnamed_var(&mut env),
-                compile_dataorqi2(&mut env, &parse::OrQi2::NotQi2(data.clone()))
-            ),
-        )],
-        parse::Statement::Declare(parse::DeclareStatement {
-            how_many_variables,
-            type_,
-            data_arr,
-        }) => (0..*how_many_variables)
-            .map(|i| {
-                (
-                    env.indent_level,
-                    format!(
-                        "let _ans{} = {};",
-                        get_new_unnamed_var(&mut env),
-                        compile_optional_literal(&env, data_arr.get(i), *type_)
-                    ),
-                )
-            })
-            .collect(),
-        parse::Statement::Print => {
-            let r = format!(
-                "println!(\"{}\"{});",
-                "{} ".repeat(env.variables_not_yet_named.len()).trim_end(),
-                env.variables_not_yet_named
-                    .iter()
-                    .map(|varname| format!(", {}", varname))
-                    .collect::<Vec<_>>()
-                    .join("")
-            );
-            env.variables_not_yet_named = vec![];
-            return vec![(env.indent_level, r)];
+            }));
+            r
         }
-        parse::Statement::Assignment { lvalue, rvalue } => vec![(
-            env.indent_level,
-            format!(
-                "{} = {};",
-                compile_lvalue(&env, lvalue),
-                compile_rvalue(&mut env, rvalue),
-            ),
-        )],
-        parse::Statement::InitDefine { type_, data, name } => vec![(
-            env.indent_level,
-            format!(
-                "let {}{} = {};",
-                ifmutable_thenmut(&env, &name),
-                env.ident_map.translate_from_hanzi(&name),
-                compile_optional_literal(&env, Some(data), *type_)
-            ),
-        )],
-        parse::Statement::Define { decl, idents } => compile_define(&mut env, decl, &idents),
-        parse::Statement::ForEnum { num, statements } => {
-            compile_forenum(&mut env, *num, &statements)
+        ir::Op::If { branches, else_ } => compile_if(env, branches, else_)?,
+        ir::Op::Repeat(num, body) => {
+            let mut r = vec![env.line(format!("for _ in 0..{} {{", num))];
+            compile_block(env, &mut r, body)?;
+            r.push(env.line(S("}")));
+            r
         }
-        parse::Statement::ForEnumIdent { ident, statements } => {
-            compile_forenum_ident(&mut env, ident, statements)
+        ir::Op::RepeatBy(count, body) => compile_forenum_ident(env, count, body)?,
+        ir::Op::ForEach {
+            list,
+            elem: elem_ident,
+            body,
+        } => {
+            // the list is still there after the loop; it is copied if the loop changes it
+            let data = parse::Data::Identifier(list.clone());
+            not_spilled(env, &data, "a value of the runtime stack as a list")?;
+            let name = env.ident_map.translate_from_hanzi(list)?;
+            let list = if ir::modifies(body, list) {
+                format!("{}.clone()", name)
+            } else {
+                format!("{}.iter().cloned()", name)
+            };
+            env.ident_map.enter_scope();
+            let mutability = declare(env, elem_ident);
+            let elem = env.ident_map.translate_from_hanzi(elem_ident)?;
+            let mut r = vec![];
+            // an element always assigned to before it is read is declared in the body
+            if env.ident_map.is_deferred(elem_ident)? {
+                r.push(env.line(format!("for _ in {} {{", list)));
+                env.indent_level += 1;
+                r.push(env.line(format!("let {}{};", mutability, elem)));
+                env.indent_level -= 1;
+            } else {
+                r.push(env.line(format!("for {}{} in {} {{", mutability, elem, list)));
+            }
+            compile_indent(env, &mut r, body)?;
+            env.ident_map.exit_scope();
+            r.push(env.line(S("}")));
+            r
         }
-        parse::Statement::ForArr { list, elem, stmts } => {
-            let mut r = vec![(
-                env.indent_level,
-                format!(
-                    "for {} in {} {{",
-                    env.ident_map.translate_from_hanzi(&elem),
-                    env.ident_map.translate_from_hanzi(list)
-                ),
-            )];
-            compile_indent(&mut env, &mut r, &stmts);
-            r.push((env.indent_level, S("}")));
+        ir::Op::Loop(body) => {
+            let mut r = vec![env.line(S("loop {"))];
+            compile_block(env, &mut r, body)?;
+            r.push(env.line(S("}")));
             r
         }
-        parse::Statement::Loop { statements } => compile_loop(&mut env, statements),
+        ir::Op::Break => vec![env.line(S("break;"))],
+        ir::Op::Continue => vec![env.line(S("continue;"))],
+        ir::Op::Origin(i) => {
+            env.origin = Some(*i);
+            vec![]
+        }
+    })
+}
+
+fn compile_ops(env: &mut Env, ops: &[ir::Op]) -> Result<Vec<Line>, Error> {
+    let mut r = vec![];
+    for op in ops {
+        r.append(&mut compile_op(env, op)?);
     }
+    Ok(r)
 }
 
-fn compile_indent(mut env: &mut Env, r: &mut Vec<Line>, stmts: &[parse::Statement]) {
+/// The lines after the block come from the statement it belongs to again.
+/// The values not yet named are as before the block, whatever it took; the IR then drops those that are gone.
+fn compile_indent(env: &mut Env, r: &mut Vec<Line>, ops: &[ir::Op]) -> Result<(), Error> {
+    let origin = env.origin;
+    let unnamed = env.unnamed.clone();
     env.indent_level += 1;
-    for st in stmts {
-        r.append(&mut compile_statement(&mut env, &st));
-    }
+    r.append(&mut compile_ops(env, ops)?);
     env.indent_level -= 1;
+    env.origin = origin;
+    env.unnamed = unnamed;
+    Ok(())
 }
 
-fn compile_lvalue(env: &Env, lvalue: &parse::Lvalue) -> String {
-    match lvalue {
+/// A block has its own scope
+fn compile_block(env: &mut Env, r: &mut Vec<Line>, ops: &[ir::Op]) -> Result<(), Error> {
+    env.ident_map.enter_scope();
+    compile_indent(env, r, ops)?;
+    env.ident_map.exit_scope();
+    Ok(())
+}
+
+fn compile_lvalue(env: &Env, lvalue: &parse::Lvalue) -> Result<String, Error> {
+    Ok(match lvalue {
         parse::Lvalue::Index(ident, index) => format!(
             "{}[{} - 1]",
-            env.ident_map.translate_from_hanzi(&ident),
+            env.ident_map.translate_from_hanzi(&ident)?,
             index
         ),
-        parse::Lvalue::Simple(ident) => env.ident_map.translate_from_hanzi(&ident),
+        parse::Lvalue::Simple(ident) => env.ident_map.translate_from_hanzi(&ident)?,
         parse::Lvalue::IndexByIdent(ident, index) => format!(
-            "{}[({} as usize) - 1]",
-            env.ident_map.translate_from_hanzi(&ident),
-            env.ident_map.translate_from_hanzi(&index),
+            "{}[{}]",
+            env.ident_map.translate_from_hanzi(&ident)?,
+            compile_index(&env, &index)?,
         ),
+    })
+}
+
+/// To be called on every declaration, in order; see `identbimap::IdentBiMap`
+fn declare(env: &mut Env, name: &parse::Identifier) -> &'static str {
+    if env.ident_map.declare(&name) {
+        "mut "
+    } else {
+        ""
     }
 }
 
-fn compile_rvalue(mut env: &mut Env, rvalue: &parse::Value<parse::OrQi2<parse::Data>>) -> String {
-    match rvalue {
-        parse::Value::Index(data, index) => {
-            format!("{}[{} - 1]", compile_dataorqi2(&mut env, data), index)
-        }
-        parse::Value::Simple(data) => compile_dataorqi2(&mut env, data),
-        parse::Value::Length(data) => {
-            format!("({}.len() as f64)", compile_dataorqi2(&mut env, data))
+/// Like `declare`, but with `-O` a binding never assigned to whose value is known gets no `let`;
+/// it is written as its value wherever it is read. Returns `None` in that case.
+fn declare_with_value(
+    env: &mut Env,
+    name: &parse::Identifier,
+    value: Option<fold::Const>,
+) -> Result<Option<&'static str>, Error> {
+    let mutability = declare(env, name);
+    Ok(match value {
+        Some(value)
+            if env.optimize && mutability.is_empty() && !env.ident_map.is_deferred(name)? =>
+        {
+            let id = env.ident_map.binding_id(name)?;
+            env.constants.insert(id, value);
+            None
         }
-        parse::Value::IndexByIdent(data, index) => format!(
-            "{}[({} as usize) - 1]",
-            compile_dataorqi2(&mut env, data),
-            env.ident_map.translate_from_hanzi(&index),
-        ),
-    }
+        _ => Some(mutability),
+    })
 }
 
-fn ifmutable_thenmut(env: &Env, name: &parse::Identifier) -> &'static str {
-    if env.ident_map.is_mutable(&name) {
-        "mut "
+/// `f64`, or `i64` in integer mode
+fn number_type(env: &Env) -> &'static str {
+    if env.integers {
+        "i64"
     } else {
-        ""
+        "f64"
     }
 }
 
 fn compile_forenum_ident(
-    mut env: &mut Env,
-    ident: &parse::OrQi2<parse::Identifier>,
-    statements: &[parse::Statement],
-) -> Vec<Line> {
+    env: &mut Env,
+    count: &ir::Operand,
+    body: &[ir::Op],
+) -> Result<Vec<Line>, Error> {
+    // as `for (let i = 0; i < n; i++)`, the bound is read again on every iteration, which matters if the loop assigns to it
+    let read_once = match count {
+        ir::Operand::Data(parse::Data::Identifier(ident)) => !ir::assigns(body, ident),
+        _ => true,
+    };
+    if read_once {
+        let bound = take_operand(env, count)?.converted("f64");
+        let mut r = vec![env.line(format!(
+            "for _ in 0..{} {{",
+            compile_loop_count(&env, &bound)
+        ))];
+        compile_block(env, &mut r, body)?;
+        r.push(env.line(S("}")));
+        return Ok(r);
+    }
+
+    let (zero, one) = if env.integers {
+        ("0", "1")
+    } else {
+        ("0.0", "1.0")
+    };
     env.rand_counter += 1;
     let rand_n = env.rand_counter;
+    let head = format!(
+        "while _rand{} < {} {{",
+        rand_n,
+        take_operand(env, count)?
+            .converted("f64")
+            .operand(precedence::ATOM),
+    );
+    // counted first, so that `continue` does not skip it
+    let mut count_line = env.line(format!("_rand{} += {};", rand_n, one));
+    count_line.indent += 1;
     let mut r = vec![
-        (env.indent_level, format!("let mut _rand{} = 0.0;", rand_n,)),
-        (
-            env.indent_level,
-            format!(
-                "while _rand{} < {} {{",
-                rand_n,
-                compile_dataorqi2(&mut env, &parse::OrQi2::from(ident)),
-            ),
-        ),
+        env.line(format!("let mut _rand{} = {};", rand_n, zero)),
+        env.line(head),
+        count_line,
     ];
-    compile_indent(&mut env, &mut r, statements);
-    r.append(&mut vec![
-        (env.indent_level + 1, format!("_rand{} += 1.0;", rand_n,)),
-        (env.indent_level, S("}")),
-    ]);
-    r
+    compile_block(env, &mut r, body)?;
+    r.push(env.line(S("}")));
+    Ok(r)
 }
 
-fn compile_loop(mut env: &mut Env, statements: &[parse::Statement]) -> Vec<Line> {
-    let mut r = vec![(env.indent_level, S("loop {"))];
-    compile_indent(&mut env, &mut r, statements);
-    r.push((env.indent_level, S("}")));
+/// How many times `for (let i = 0; i < n; i++)` runs: `n` rounded up, and none if `n` is negative or `NaN`,
+/// which `as usize` gives by saturating
+fn compile_loop_count(env: &Env, bound: &Expr) -> String {
+    match bound.value {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Some(fold::Const::Num(n)) => (n.ceil() as usize).to_string(),
+        _ if env.integers => bound.operand(precedence::ATOM),
+        // a method call would not tell the type of a literal
+        _ => format!("(f64::ceil({}) as usize)", bound.text),
+    }
+}
+
+/// A comment telling which name in the output stands for which identifier in the source
+fn compile_ident_mapping(ident_map: &identbimap::IdentBiMap) -> Vec<Line> {
+    let mapping = ident_map.mapping();
+    if mapping.is_empty() {
+        return vec![];
+    }
+    let mut r = vec![Line::outside(S("// identifiers:"))];
+    for (parse::Identifier(hanzi), ascii) in mapping {
+        r.push(Line::outside(format!(
+            "//   「{}」 {}",
+            hanzi.escape_debug(),
+            ascii
+        )));
+    }
     r
 }
 
-use std::collections::HashMap;
-pub fn compile(parsed: &[parse::Statement], conversion_table: &HashMap<String, String>) -> String {
-    let mut ans = vec![(0, S("fn main() {"))];
+use crate::romanize::Romanizer;
+pub fn compile(
+    parsed: &[parse::Statement],
+    romanizer: &dyn Romanizer,
+    options: &Options,
+) -> Result<String, Error> {
+    compile_with_origins(parsed, romanizer, options).map(|(src, _)| src)
+}
+
+/// Also returns, for each line of the output, the statement that the line was generated from,
+/// as its index in `cst::preorder(parsed)`: that of a block for its `{` and `}`, one inside for the lines of the block.
+/// Lines such as `fn main() {` that do not come from any statement get `None`.
+pub fn compile_with_origins(
+    parsed: &[parse::Statement],
+    romanizer: &dyn Romanizer,
+    options: &Options,
+) -> Result<(String, Vec<Option<usize>>), Error> {
+    let program = ir::lower(parsed);
     let mut env = Env {
         ans_counter: 0,
         rand_counter: 0,
         indent_level: 1,
-        variables_not_yet_named: vec![],
-        ident_map: identbimap::IdentBiMap::new(&parsed, &conversion_table),
+        unnamed: BTreeMap::new(),
+        stack_methods: HashSet::new(),
+        spilled_temps: HashSet::new(),
+        spilled_bindings: HashSet::new(),
+        ident_map: identbimap::IdentBiMap::new(&parsed, romanizer)?,
+        optimize: options.optimize,
+        constants: HashMap::new(),
+        integers: options.optimize && range::all_integers(&program),
+        origin: None,
     };
 
-    for st in parsed {
-        ans.append(&mut compile_statement(&mut env, &st));
+    let mut ans = compile_ident_mapping(&env.ident_map);
+    if let Some(attributes) = romanizer.crate_attributes() {
+        ans.push(Line::outside(attributes.to_string()));
     }
+    let mut main = compile_ops(&mut env, &program.ops)?;
+    if !env.stack_methods.is_empty() {
+        let number = if env.integers { "i64" } else { "f64" };
+        ans.append(&mut compile_stack(&env.stack_methods, number));
+        main.insert(
+            0,
+            Line {
+                indent: 1,
+                text: S("let mut _stack = Stack { values: vec![], lows: vec![] };"),
+                origin: None,
+            },
+        );
+    }
+    ans.push(Line::outside(S("fn main() {")));
+    ans.append(&mut main);
+    ans.push(Line::outside(S("}")));
 
-    ans.push((0, S("}")));
-
-    ans.iter()
-        .map(|(indent, src)| format!("{}{}\n", "    ".repeat(*indent), src))
+    let src = ans
+        .iter()
+        .map(|line| format!("{}{}\n", "    ".repeat(line.indent), line.text))
         .collect::<Vec<_>>()
-        .join("")
+        .join("");
+    Ok((src, ans.iter().map(|line| line.origin).collect()))
 }
@@ -0,0 +1,82 @@
+use crate::compile;
+use crate::romanize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    /// a line that is neither `key = value`, a comment nor empty; the line number is 1-indexed
+    Malformed(usize),
+    UnknownKey(usize, String),
+    /// a value other than `true` or `false` for a flag such as `optimize`
+    NotABool(usize, String),
+    Io(std::io::Error),
+    Romanize(romanize::Error),
+}
+
+/// The contents of a config file such as `default.conf`:
+///
+/// ```text
+/// # comment
+/// romanization = wade-giles
+/// romanization_table = path/to/table.json
+/// optimize = true
+/// ```
+#[derive(Debug, Default)]
+pub struct Config {
+    pub romanization: Option<romanize::Scheme>,
+    pub romanization_table: Option<PathBuf>,
+    pub optimize: bool,
+}
+
+impl Config {
+    pub fn parse(src: &str) -> Result<Self, Error> {
+        let mut config = Config::default();
+        for (i, line) in src.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut kv = line.splitn(2, '=');
+            let key = kv.next().unwrap_or("").trim();
+            let value = kv.next().ok_or(Error::Malformed(i + 1))?.trim();
+            match key {
+                "romanization" => {
+                    config.romanization = Some(value.parse().map_err(Error::Romanize)?);
+                }
+                "romanization_table" => config.romanization_table = Some(PathBuf::from(value)),
+                "optimize" => {
+                    config.optimize = value
+                        .parse()
+                        .map_err(|_| Error::NotABool(i + 1, value.to_string()))?;
+                }
+                _ => return Err(Error::UnknownKey(i + 1, key.to_string())),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Relative paths in the file are taken relative to the directory the file is in
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let mut config = Config::parse(&std::fs::read_to_string(path).map_err(Error::Io)?)?;
+        if let (Some(table), Some(dir)) = (&config.romanization_table, path.parent()) {
+            config.romanization_table = Some(dir.join(table));
+        }
+        Ok(config)
+    }
+
+    pub fn compile_options(&self) -> compile::Options {
+        compile::Options {
+            optimize: self.optimize,
+        }
+    }
+
+    /// Pinyin with tones unless specified otherwise
+    pub fn romanizer(&self) -> Result<Box<dyn romanize::Romanizer + Sync>, Error> {
+        let table = match &self.romanization_table {
+            Some(path) => Some(romanize::read_table(path).map_err(Error::Romanize)?),
+            None => None,
+        };
+        romanize::romanizer(self.romanization.unwrap_or(romanize::Scheme::Pinyin), table)
+            .map_err(Error::Romanize)
+    }
+}
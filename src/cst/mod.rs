@@ -0,0 +1,143 @@
+use crate::diagnostic::Diagnostic;
+use crate::lex;
+use crate::parse;
+
+/// A statement as written: its tokens, and the statements of its blocks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// the indices in `Cst::tokens` of its tokens, including those of the statements inside
+    pub tokens: std::ops::Range<usize>,
+    /// in the order they are written, which is that of `parse::Statement::blocks`
+    pub children: Vec<Node>,
+}
+
+/// The source as written, trivia included, with the statements it consists of.
+/// The AST that `parse` gives is the same program without the layout; see `preorder` for which node is which statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cst {
+    pub src: String,
+    pub tokens: lex::Tokens,
+    pub statements: Vec<Node>,
+}
+
+/// Why `src` could not be parsed, and where
+#[derive(Debug)]
+pub struct Error {
+    pub diagnostic: Diagnostic,
+    pub span: lex::Span,
+}
+
+/// The statements of `stmts` and of the blocks inside, each followed by those of its blocks;
+/// the nth is that of the nth node of `Cst::preorder`
+pub fn preorder(stmts: &[parse::Statement]) -> Vec<&parse::Statement> {
+    let mut r = vec![];
+    for st in stmts {
+        r.push(st);
+        for block in st.blocks() {
+            r.append(&mut preorder(block));
+        }
+    }
+    r
+}
+
+impl Cst {
+    /// Lexes and parses `src`, giving the AST as well
+    pub fn parse(src: &str) -> Result<(Cst, Vec<parse::Statement>), Error> {
+        let tokens = lex::lex_with_trivia(src).map_err(|(e, span)| Error {
+            diagnostic: Diagnostic::Lex(e),
+            span,
+        })?;
+        let lex = tokens
+            .tokens
+            .iter()
+            .map(|token| token.lex.clone())
+            .collect::<Vec<_>>();
+        let (stmts, ranges) = parse::parse_with_ranges(&lex).map_err(|(e, at)| Error {
+            diagnostic: Diagnostic::Parse(e),
+            span: match tokens.tokens.get(at) {
+                Some(token) => token.span.clone(),
+                None => src.len()..src.len(),
+            },
+        })?;
+
+        // `ranges` is in preorder, and each range contains those of the statements inside
+        let mut statements = vec![];
+        let mut open: Vec<Node> = vec![];
+        for range in ranges {
+            while let Some(node) = open.pop() {
+                if node.tokens.end > range.start {
+                    open.push(node);
+                    break;
+                }
+                close(node, &mut open, &mut statements);
+            }
+            open.push(Node {
+                tokens: range,
+                children: vec![],
+            });
+        }
+        while let Some(node) = open.pop() {
+            close(node, &mut open, &mut statements);
+        }
+
+        let cst = Cst {
+            src: src.to_string(),
+            tokens,
+            statements,
+        };
+        Ok((cst, stmts))
+    }
+
+    /// Every node, each followed by its children; the nth is that of the nth statement of `preorder`
+    pub fn preorder(&self) -> Vec<&Node> {
+        fn walk<'a>(nodes: &'a [Node], r: &mut Vec<&'a Node>) {
+            for node in nodes {
+                r.push(node);
+                walk(&node.children, r);
+            }
+        }
+        let mut r = vec![];
+        walk(&self.statements, &mut r);
+        r
+    }
+
+    /// From the first token of `node` to its last, without the trivia before or after
+    pub fn span(&self, node: &Node) -> lex::Span {
+        let tokens = &self.tokens.tokens[node.tokens.clone()];
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => first.span.start..last.span.end,
+            _ => 0..0,
+        }
+    }
+
+    pub fn text(&self, node: &Node) -> &str {
+        &self.src[self.span(node)]
+    }
+
+    /// The innermost statement that `offset` is in
+    pub fn node_at(&self, offset: usize) -> Option<&Node> {
+        let mut nodes = &self.statements;
+        let mut found = None;
+        while let Some(node) = nodes.iter().find(|node| self.span(node).contains(&offset)) {
+            found = Some(node);
+            nodes = &node.children;
+        }
+        found
+    }
+
+    /// The token that `offset` is in, if not in trivia
+    pub fn token_at(&self, offset: usize) -> Option<&lex::Token> {
+        self.tokens
+            .tokens
+            .iter()
+            .find(|token| token.span.contains(&offset))
+    }
+}
+
+/// Makes `node` a child of the innermost node still open, or a statement at the top
+fn close(node: Node, open: &mut [Node], statements: &mut Vec<Node>) {
+    match open.last_mut() {
+        Some(parent) => parent.children.push(node),
+        None => statements.push(node),
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{compile, lex, parse};
+use std::fmt;
+
+/// Why a program could not be transpiled, whichever stage found it
+#[derive(Debug)]
+pub enum Diagnostic {
+    Lex(lex::Error),
+    Parse(parse::Error),
+    Compile(compile::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    /// the program is not valid wenyan
+    Invalid,
+    /// valid wenyan, or possibly so, that this compiler does not handle yet
+    Unsupported,
+}
+
+impl Diagnostic {
+    pub fn category(&self) -> Category {
+        match self {
+            Diagnostic::Lex(_)
+            | Diagnostic::Parse(
+                parse::Error::SomethingWentWrong(_)
+                | parse::Error::UnexpectedEOF
+                | parse::Error::InvalidVariableCount
+                | parse::Error::JumpOutsideLoop,
+            )
+            | Diagnostic::Compile(compile::Error::UseBeforeDeclaration(_)) => Category::Invalid,
+            Diagnostic::Parse(
+                parse::Error::Unsupported(_) | parse::Error::UnsupportedStatement(_),
+            )
+            | Diagnostic::Compile(compile::Error::Unsupported(_)) => Category::Unsupported,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.category() == Category::Unsupported {
+            write!(f, "not supported yet: ")?;
+        }
+        match self {
+            Diagnostic::Lex(e) => match e {
+                lex::Error::UnexpectedCharAfter(before, c) => {
+                    write!(f, "unexpected {} after {}", c, before)
+                }
+                lex::Error::UnexpectedEOFAfter(c) => {
+                    write!(f, "unexpected end of input after {}", c)
+                }
+                lex::Error::NonterminatedIdentifier => write!(f, "identifier missing its 」"),
+                lex::Error::EmptyIdentifier => write!(f, "empty identifier 「」"),
+                lex::Error::NonterminatedStringLiteral => {
+                    write!(f, "string literal missing its 」」")
+                }
+                lex::Error::UnrecognizedCharacter(c) => write!(f, "unrecognized character {:?}", c),
+            },
+            Diagnostic::Parse(e) => match e {
+                // the position is that of the parser, which helps in reporting a bug
+                parse::Error::SomethingWentWrong(pos) => {
+                    write!(
+                        f,
+                        "syntax error (detected at {}:{})",
+                        pos.file(),
+                        pos.line()
+                    )
+                }
+                parse::Error::UnexpectedEOF => write!(f, "unexpected end of input"),
+                parse::Error::InvalidVariableCount => {
+                    write!(f, "the number of variables declared must be positive")
+                }
+                parse::Error::JumpOutsideLoop => write!(f, "乃止 or 乃止是遍 outside a loop"),
+                parse::Error::Unsupported(what) => write!(f, "{}", what),
+                parse::Error::UnsupportedStatement(token) => {
+                    write!(f, "a statement beginning with {:?}", token)
+                }
+            },
+            Diagnostic::Compile(e) => match e {
+                compile::Error::UseBeforeDeclaration(parse::Identifier(name)) => {
+                    write!(f, "「{}」 is used before it is declared", name)
+                }
+                compile::Error::Unsupported(what) => write!(f, "{}", what),
+            },
+        }
+    }
+}
+
+impl From<lex::Error> for Diagnostic {
+    fn from(e: lex::Error) -> Self {
+        Diagnostic::Lex(e)
+    }
+}
+
+impl From<parse::Error> for Diagnostic {
+    fn from(e: parse::Error) -> Self {
+        Diagnostic::Parse(e)
+    }
+}
+
+impl From<compile::Error> for Diagnostic {
+    fn from(e: compile::Error) -> Self {
+        Diagnostic::Compile(e)
+    }
+}
@@ -0,0 +1,597 @@
+use crate::lex;
+use crate::parse;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    List(Vec<Value>),
+    /// what `名之曰` and `其` give when there is nothing left to take; `let x : ();` in the transpiled code
+    Undefined,
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // same as what `println!("{}", x)` gives for `x: f64`
+            Value::Num(x) => write!(f, "{}", x),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::List(elems) => write!(
+                f,
+                "[ {} ]",
+                elems
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Value::Undefined => write!(f, "undefined"),
+        }
+    }
+}
+
+impl Value {
+    /// JavaScript's notion of truthiness
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Num(x) => *x != 0.0 && !x.is_nan(),
+            Value::Str(s) => !s.is_empty(),
+            Value::Bool(b) => *b,
+            Value::List(_) => true,
+            Value::Undefined => false,
+        }
+    }
+
+    fn default_of(type_: lex::Type) -> Value {
+        match type_ {
+            lex::Type::Shu4 => Value::Num(0.0),
+            lex::Type::Lie4 => Value::List(vec![]),
+            lex::Type::Yan2 => Value::Str(String::new()),
+            lex::Type::Yao2 => Value::Bool(false),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    UndefinedVariable(parse::Identifier),
+    NotAList(Value),
+    NotANumber(Value),
+    IndexOutOfRange(Value, i64),
+    TypeMismatch(&'static str, Value, Value),
+    Unsupported(&'static str),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// How a block was exited
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+}
+
+/// Values that are not yet named live in `stack`, which follows the rules that `ir::lower` resolves when compiling,
+/// but on values, as the program runs, so that the two can be compared.
+/// A block sees the values before it, a loop on every iteration, and at its end the stack goes back to those of them
+/// that are still there: each block being run has a low-water mark, lowered when values are taken off the stack.
+/// Variables are scoped by block as in the transpiled code.
+pub struct Interpreter<W: Write> {
+    /// the innermost scope is the last
+    scopes: Vec<HashMap<parse::Identifier, Value>>,
+    /// bottom first
+    stack: Vec<Value>,
+    /// for each block being run, the innermost last, how many of the values before it are left
+    lows: Vec<usize>,
+    out: W,
+}
+
+impl<W: Write> Interpreter<W> {
+    pub fn new(out: W) -> Self {
+        Interpreter {
+            scopes: vec![HashMap::new()],
+            stack: vec![],
+            lows: vec![],
+            out,
+        }
+    }
+
+    pub fn into_output(self) -> W {
+        self.out
+    }
+
+    pub fn output_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// On an error, what `parsed` printed and declared up to it stays, but the stack is back to what it was before
+    pub fn run(&mut self, parsed: &[parse::Statement]) -> Result<(), Error> {
+        let before = self.stack.clone();
+        if let Err(e) = self.exec_stmts(parsed) {
+            self.stack = before;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// The variables of the outermost scope
+    pub fn variables(&self) -> &HashMap<parse::Identifier, Value> {
+        &self.scopes[0]
+    }
+
+    /// The values not yet named, bottom first
+    pub fn stack(&self) -> &[Value] {
+        &self.stack
+    }
+
+    fn declare(&mut self, ident: &parse::Identifier, value: Value) {
+        self.scopes.last_mut().unwrap().insert(ident.clone(), value);
+    }
+
+    fn lookup_mut(&mut self, ident: &parse::Identifier) -> Result<&mut Value, Error> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(ident))
+            .ok_or_else(|| Error::UndefinedVariable(ident.clone()))
+    }
+
+    fn lookup(&self, ident: &parse::Identifier) -> Result<Value, Error> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(ident))
+            .cloned()
+            .ok_or_else(|| Error::UndefinedVariable(ident.clone()))
+    }
+
+    fn eval_data(&self, data: &parse::Data) -> Result<Value, Error> {
+        Ok(match data {
+            parse::Data::StringLiteral(s) => Value::Str(s.clone()),
+            parse::Data::BoolValue(b) => Value::Bool(*b),
+            parse::Data::Identifier(ident) => self.lookup(ident)?,
+            #[allow(clippy::cast_precision_loss)]
+            parse::Data::IntNum(n) => Value::Num(*n as f64),
+        })
+    }
+
+    /// After values are taken off the stack
+    fn shrunk(&mut self) {
+        let len = self.stack.len();
+        for low in &mut self.lows {
+            *low = (*low).min(len);
+        }
+    }
+
+    /// 《文言陰符》曰『言「其」者。取至近之魚而棄其餘。』
+    fn take_qi2(&mut self) -> Value {
+        let qi = self.stack.pop().unwrap_or(Value::Undefined);
+        self.stack.clear();
+        self.shrunk();
+        qi
+    }
+
+    fn eval_data_or_qi2(&mut self, data: &parse::OrQi2<parse::Data>) -> Result<Value, Error> {
+        match data {
+            parse::OrQi2::Qi2 => Ok(self.take_qi2()),
+            parse::OrQi2::NotQi2(data) => self.eval_data(data),
+        }
+    }
+
+    fn eval_value<T>(
+        &mut self,
+        value: &parse::Value<T>,
+        eval_base: impl FnOnce(&mut Self, &T) -> Result<Value, Error>,
+    ) -> Result<Value, Error> {
+        match value {
+            parse::Value::Simple(base) => eval_base(self, base),
+            parse::Value::Length(base) => match eval_base(self, base)? {
+                #[allow(clippy::cast_precision_loss)]
+                Value::List(elems) => Ok(Value::Num(elems.len() as f64)),
+                #[allow(clippy::cast_precision_loss)]
+                Value::Str(s) => Ok(Value::Num(s.chars().count() as f64)),
+                v => Err(Error::NotAList(v)),
+            },
+            parse::Value::Index(base, index) => {
+                let list = eval_base(self, base)?;
+                index_list(list, *index)
+            }
+            parse::Value::IndexByIdent(base, ident) => {
+                let list = eval_base(self, base)?;
+                let index = as_index(self.lookup(ident)?)?;
+                index_list(list, index)
+            }
+        }
+    }
+
+    fn eval_unaryifexpr(&mut self, unary: &parse::UnaryIfExpr) -> Result<Value, Error> {
+        match unary {
+            parse::UnaryIfExpr::Simple(data) => self.eval_data_or_qi2(data),
+            parse::UnaryIfExpr::Complex(value) => {
+                self.eval_value(value, |this, data| this.eval_data(data))
+            }
+        }
+    }
+
+    fn eval_ifcond(&mut self, cond: &parse::IfCond) -> Result<bool, Error> {
+        match cond {
+            parse::IfCond::Unary(unary) => Ok(self.eval_unaryifexpr(unary)?.truthy()),
+            parse::IfCond::NotQi2 => Ok(!self.take_qi2().truthy()),
+            parse::IfCond::Binary(left, op, right) => {
+                let left = self.eval_unaryifexpr(left)?;
+                let right = self.eval_unaryifexpr(right)?;
+                compare(&left, op.to_str(), &right)
+            }
+        }
+    }
+
+    fn eval_math(&mut self, math: &parse::MathKind) -> Result<Value, Error> {
+        match math {
+            parse::MathKind::ArithBinaryMath(op, data1, prep, data2) => {
+                let (left, right) = self.eval_operands(data1, *prep, data2)?;
+                binary(op.to_str(), left, right)
+            }
+            parse::MathKind::ModMath(op, data1, prep, data2) => {
+                let (left, right) = self.eval_operands(data1, *prep, data2)?;
+                binary(op.to_str(), left, right)
+            }
+            parse::MathKind::ArithUnaryMath(data) => {
+                Ok(Value::Bool(!self.eval_data_or_qi2(data)?.truthy()))
+            }
+            parse::MathKind::BooleanAlgebra(ident1, ident2, op) => {
+                binary(op.to_str(), self.lookup(ident1)?, self.lookup(ident2)?)
+            }
+        }
+    }
+
+    /// The left operand is evaluated first, just as in the transpiled code
+    fn eval_operands(
+        &mut self,
+        data1: &parse::OrQi2<parse::Data>,
+        prep: lex::Preposition,
+        data2: &parse::OrQi2<parse::Data>,
+    ) -> Result<(Value, Value), Error> {
+        let (left, right) = match prep {
+            lex::Preposition::Yi3 => (data1, data2),
+            lex::Preposition::Yu2 => (data2, data1),
+        };
+        let left = self.eval_data_or_qi2(left)?;
+        let right = self.eval_data_or_qi2(right)?;
+        Ok((left, right))
+    }
+
+    fn assign(&mut self, lvalue: &parse::Lvalue, value: Value) -> Result<(), Error> {
+        let (ident, index) = match lvalue {
+            parse::Lvalue::Simple(ident) => {
+                *self.lookup_mut(ident)? = value;
+                return Ok(());
+            }
+            parse::Lvalue::Index(ident, index) => (ident, *index),
+            parse::Lvalue::IndexByIdent(ident, index) => (ident, as_index(self.lookup(index)?)?),
+        };
+        match self.lookup_mut(ident)? {
+            Value::List(elems) => match usize::try_from(index - 1) {
+                Ok(i) if i < elems.len() => {
+                    elems[i] = value;
+                    Ok(())
+                }
+                _ => Err(Error::IndexOutOfRange(Value::List(elems.clone()), index)),
+            },
+            v => Err(Error::NotAList(v.clone())),
+        }
+    }
+
+    fn print(&mut self) -> Result<(), Error> {
+        let line = self
+            .stack
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(self.out, "{}", line)?;
+        self.stack.clear();
+        self.shrunk();
+        Ok(())
+    }
+
+    /// The stack is matched from the end, the first ident naming the earliest of the values;
+    /// the idents without a value are `undefined`
+    fn name_multi(&mut self, idents: &[parse::Identifier]) {
+        let len = self.stack.len();
+        for (i, ident) in idents.iter().enumerate() {
+            let value = match (len + i).checked_sub(idents.len()) {
+                Some(j) => self.stack[j].clone(),
+                None => Value::Undefined,
+            };
+            self.declare(ident, value);
+        }
+        self.stack.truncate(len.saturating_sub(idents.len()));
+        self.shrunk();
+    }
+
+    /// Runs `stmts` in a new scope, after declaring `binding` (the element of `ForArr`) in it.
+    /// Whichever way the block is left, the values pushed in it are dropped, leaving those before it that are left.
+    fn exec_block(
+        &mut self,
+        binding: Option<(&parse::Identifier, Value)>,
+        stmts: &[parse::Statement],
+    ) -> Result<Flow, Error> {
+        self.lows.push(self.stack.len());
+        self.scopes.push(HashMap::new());
+        if let Some((ident, value)) = binding {
+            self.declare(ident, value);
+        }
+        let flow = self.exec_stmts(stmts);
+        self.scopes.pop();
+        let low = self.lows.pop().unwrap();
+        self.stack.truncate(low);
+        flow
+    }
+
+    /// Every iteration starts from the values before the loop, whatever the previous one took,
+    /// and the stack after the loop is what the last one left
+    fn exec_iteration(
+        &mut self,
+        entry: &[Value],
+        binding: Option<(&parse::Identifier, Value)>,
+        stmts: &[parse::Statement],
+    ) -> Result<Flow, Error> {
+        self.stack = entry.to_vec();
+        self.exec_block(binding, stmts)
+    }
+
+    fn exec_stmts(&mut self, stmts: &[parse::Statement]) -> Result<Flow, Error> {
+        for st in stmts {
+            match self.exec(st)? {
+                Flow::Normal => {}
+                flow => return Ok(flow),
+            }
+        }
+        Ok(Flow::Normal)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn exec(&mut self, st: &parse::Statement) -> Result<Flow, Error> {
+        match st {
+            parse::Statement::Declare(parse::DeclareStatement {
+                how_many_variables,
+                type_,
+                data_arr,
+            }) => {
+                for i in 0..*how_many_variables {
+                    let v = match data_arr.get(i) {
+                        Some(data) => self.eval_data(data)?,
+                        None => Value::default_of(*type_),
+                    };
+                    self.stack.push(v);
+                }
+            }
+            parse::Statement::Define { decl, idents } => {
+                for i in 0..decl.how_many_variables {
+                    let v = match decl.data_arr.get(i) {
+                        Some(data) => self.eval_data(data)?,
+                        None => Value::default_of(decl.type_),
+                    };
+                    match idents.get(i) {
+                        Some(ident) => self.declare(ident, v),
+                        None => self.stack.push(v),
+                    }
+                }
+            }
+            parse::Statement::InitDefine { data, name, .. } => {
+                let v = self.eval_data(data)?;
+                self.declare(name, v);
+            }
+            parse::Statement::Print => self.print()?,
+            parse::Statement::Flush => {
+                self.stack.clear();
+                self.shrunk();
+            }
+            parse::Statement::NameMulti { idents } => self.name_multi(idents),
+            parse::Statement::Math { math } => {
+                let v = self.eval_math(math)?;
+                self.stack.push(v);
+            }
+            parse::Statement::Reference { rvalue } => {
+                let v = self.eval_value(rvalue, |this, data| this.eval_data(data))?;
+                self.stack.push(v);
+            }
+            parse::Statement::ReferenceWhatIsLeft { data } => {
+                let elems = as_list(self.eval_data(data)?)?;
+                self.stack
+                    .push(Value::List(elems.into_iter().skip(1).collect()));
+            }
+            parse::Statement::Assignment { lvalue, rvalue } => {
+                let v = self.eval_value(rvalue, Self::eval_data_or_qi2)?;
+                self.assign(lvalue, v)?;
+            }
+            parse::Statement::ArrayFill {
+                what_to_fill: parse::OrQi2::NotQi2(ident),
+                elems,
+            } => {
+                let elems = elems
+                    .iter()
+                    .map(|e| self.eval_data(e))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match self.lookup_mut(ident)? {
+                    Value::List(list) => list.extend(elems),
+                    v => return Err(Error::NotAList(v.clone())),
+                }
+            }
+            parse::Statement::ArrayCat {
+                append_to: parse::OrQi2::NotQi2(ident),
+                elems,
+            } => {
+                let mut result = as_list(self.lookup(ident)?)?;
+                for e in elems {
+                    result.extend(as_list(self.lookup(e)?)?);
+                }
+                self.stack.push(Value::List(result));
+            }
+            parse::Statement::ArrayFill {
+                what_to_fill: parse::OrQi2::Qi2,
+                ..
+            }
+            | parse::Statement::ArrayCat {
+                append_to: parse::OrQi2::Qi2,
+                ..
+            } => return Err(Error::Unsupported("filling qi2")),
+            parse::Statement::If {
+                ifcase,
+                elseifcases,
+                elsecase,
+            } => {
+                // a condition is only evaluated if those before do not hold
+                for (cond, stmts) in std::iter::once(ifcase).chain(elseifcases) {
+                    if self.eval_ifcond(cond)? {
+                        return self.exec_block(None, stmts);
+                    }
+                }
+                return self.exec_block(None, elsecase);
+            }
+            parse::Statement::ForEnum { num, statements } => {
+                let entry = self.stack.clone();
+                for _ in 0..*num {
+                    if let Flow::Break = self.exec_iteration(&entry, None, statements)? {
+                        break;
+                    }
+                }
+            }
+            parse::Statement::ForEnumIdent { ident, statements } => {
+                let bound = match ident {
+                    parse::OrQi2::Qi2 => Some(as_num(self.take_qi2())?),
+                    parse::OrQi2::NotQi2(_) => None,
+                };
+                let entry = self.stack.clone();
+                let mut counter = 0.0;
+                loop {
+                    // an identifier is read again on every iteration, as in `while _rand1 < JIA3`
+                    let bound = match (bound, ident) {
+                        (Some(b), _) => b,
+                        (None, parse::OrQi2::NotQi2(i)) => as_num(self.lookup(i)?)?,
+                        (None, parse::OrQi2::Qi2) => unreachable!(),
+                    };
+                    let keep_going = counter < bound; // false if NaN
+                    if !keep_going {
+                        break;
+                    }
+                    if let Flow::Break = self.exec_iteration(&entry, None, statements)? {
+                        break;
+                    }
+                    counter += 1.0;
+                }
+            }
+            parse::Statement::ForArr { list, elem, stmts } => {
+                let elems = as_list(self.lookup(list)?)?;
+                let entry = self.stack.clone();
+                for e in elems {
+                    if let Flow::Break = self.exec_iteration(&entry, Some((elem, e)), stmts)? {
+                        break;
+                    }
+                }
+            }
+            parse::Statement::Loop { statements } => {
+                let entry = self.stack.clone();
+                loop {
+                    if let Flow::Break = self.exec_iteration(&entry, None, statements)? {
+                        break;
+                    }
+                }
+            }
+            parse::Statement::Break => return Ok(Flow::Break),
+            parse::Statement::Continue => return Ok(Flow::Continue),
+        }
+        Ok(Flow::Normal)
+    }
+}
+
+/// `undefined` is `NaN`, as in JavaScript
+fn as_num(v: Value) -> Result<f64, Error> {
+    match v {
+        Value::Num(x) => Ok(x),
+        Value::Undefined => Ok(f64::NAN),
+        v => Err(Error::NotANumber(v)),
+    }
+}
+
+fn as_list(v: Value) -> Result<Vec<Value>, Error> {
+    match v {
+        Value::List(elems) => Ok(elems),
+        v => Err(Error::NotAList(v)),
+    }
+}
+
+/// as in `[(x as usize) - 1]`
+#[allow(clippy::cast_possible_truncation)]
+fn as_index(v: Value) -> Result<i64, Error> {
+    Ok(as_num(v)? as i64)
+}
+
+/// 1-indexed
+fn index_list(list: Value, index: i64) -> Result<Value, Error> {
+    let elems = as_list(list)?;
+    usize::try_from(index - 1)
+        .ok()
+        .and_then(|i| elems.get(i))
+        .cloned()
+        .ok_or(Error::IndexOutOfRange(Value::List(elems.clone()), index))
+}
+
+/// `op` as written in Rust and JavaScript; `+` concatenates if either operand is a string, as in JavaScript
+fn binary(op: &str, left: Value, right: Value) -> Result<Value, Error> {
+    match (op, left, right) {
+        ("+", Value::Str(a), b) => Ok(Value::Str(format!("{}{}", a, b))),
+        ("+", a, Value::Str(b)) => Ok(Value::Str(format!("{}{}", a, b))),
+        ("&&", a, b) => Ok(Value::Bool(a.truthy() && b.truthy())),
+        ("||", a, b) => Ok(Value::Bool(a.truthy() || b.truthy())),
+        (op, a, b) => {
+            let (a, b) = (as_num(a)?, as_num(b)?);
+            Ok(Value::Num(match op {
+                "+" => a + b,
+                "-" => a - b,
+                "*" => a * b,
+                "/" => a / b,
+                // same sign rule as JavaScript
+                "%" => a % b,
+                _ => unreachable!("not an arithmetic operator: {}", op),
+            }))
+        }
+    }
+}
+
+/// `op` as written in Rust and JavaScript
+fn compare(left: &Value, op: &'static str, right: &Value) -> Result<bool, Error> {
+    use std::cmp::Ordering;
+    let ord = match (left, right) {
+        (Value::Num(a), Value::Num(b)) => a.partial_cmp(b),
+        (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        // as `NaN`
+        (Value::Num(_), Value::Undefined) | (Value::Undefined, Value::Num(_)) => None,
+        (a, b) => match op {
+            "==" => return Ok(a == b),
+            "!=" => return Ok(a != b),
+            _ => return Err(Error::TypeMismatch(op, a.clone(), b.clone())),
+        },
+    };
+    // NaN compares false with everything, except for `!=`
+    Ok(match op {
+        "==" => ord == Some(Ordering::Equal),
+        "!=" => ord != Some(Ordering::Equal),
+        "<=" => ord == Some(Ordering::Less) || ord == Some(Ordering::Equal),
+        ">=" => ord == Some(Ordering::Greater) || ord == Some(Ordering::Equal),
+        ">" => ord == Some(Ordering::Greater),
+        "<" => ord == Some(Ordering::Less),
+        _ => unreachable!("not a comparison: {}", op),
+    })
+}
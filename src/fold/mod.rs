@@ -0,0 +1,87 @@
+use crate::parse;
+
+/// A value known when compiling, with `-O`.
+/// Numbers are `f64` as in JavaScript, so that folding gives exactly what the compiled program would compute.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Const {
+    Num(f64),
+    Bool(bool),
+    Str(String),
+}
+
+impl Const {
+    /// `None` for identifiers; see `compile` for those bound to a constant
+    pub fn of_data(data: &parse::Data) -> Option<Self> {
+        match data {
+            parse::Data::IntNum(n) => Some(Const::Num(*n as f64)),
+            parse::Data::BoolValue(b) => Some(Const::Bool(*b)),
+            parse::Data::StringLiteral(s) => Some(Const::Str(s.clone())),
+            parse::Data::Identifier(_) => None,
+        }
+    }
+
+    /// A Rust literal, written the way `compile` writes the source literals: `3.0`, `true`, `"甲"`
+    pub fn to_rust(&self) -> String {
+        match self {
+            Const::Num(x) if x.is_nan() => String::from("f64::NAN"),
+            Const::Num(x) if x.is_infinite() && *x > 0.0 => String::from("f64::INFINITY"),
+            Const::Num(x) if x.is_infinite() => String::from("f64::NEG_INFINITY"),
+            // `{:?}` is the shortest representation that reads back to the same number, and always has a `.` or an `e`
+            Const::Num(x) => format!("{:?}", x),
+            Const::Bool(b) => b.to_string(),
+            Const::Str(s) => format!("\"{}\"", s), // FIXME properly escape, as in compile_literal
+        }
+    }
+}
+
+/// `op` is as written in the output: `+`, `-`, `*`, `/`, `%`, `&&` or `||`.
+/// Division by zero gives an infinity or NaN, and the sign of `%` follows the dividend, as in both JavaScript and Rust.
+/// The operands of a type the operator does not take (e.g. `"甲" + 1`) are left alone, since they do not compile in Rust anyway.
+pub fn binary(op: &str, a: &Const, b: &Const) -> Option<Const> {
+    match (a, b) {
+        (Const::Num(a), Const::Num(b)) => match op {
+            "+" => Some(Const::Num(a + b)),
+            "-" => Some(Const::Num(a - b)),
+            "*" => Some(Const::Num(a * b)),
+            "/" => Some(Const::Num(a / b)),
+            "%" => Some(Const::Num(a % b)),
+            _ => None,
+        },
+        (Const::Bool(a), Const::Bool(b)) => match op {
+            "&&" => Some(Const::Bool(*a && *b)),
+            "||" => Some(Const::Bool(*a || *b)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// `!`, which only takes booleans in Rust
+pub fn not(a: &Const) -> Option<Const> {
+    match a {
+        Const::Bool(a) => Some(Const::Bool(!a)),
+        _ => None,
+    }
+}
+
+/// `op` is one of `==`, `!=`, `<`, `>`, `<=` and `>=`; any comparison with NaN is false except `!=`
+pub fn compare(op: &str, a: &Const, b: &Const) -> Option<bool> {
+    let ordering = match (a, b) {
+        (Const::Num(a), Const::Num(b)) => a.partial_cmp(b),
+        (Const::Bool(a), Const::Bool(b)) => a.partial_cmp(b),
+        (Const::Str(a), Const::Str(b)) => a.partial_cmp(b),
+        _ => return None,
+    };
+    use std::cmp::Ordering::{Equal, Greater, Less};
+    Some(match (op, ordering) {
+        ("!=", None) => true,
+        (_, None) => false,
+        ("==", Some(o)) => o == Equal,
+        ("!=", Some(o)) => o != Equal,
+        ("<", Some(o)) => o == Less,
+        (">", Some(o)) => o == Greater,
+        ("<=", Some(o)) => o != Greater,
+        (">=", Some(o)) => o != Less,
+        _ => return None,
+    })
+}
@@ -1,263 +1,877 @@
+use crate::compile::Error;
 use crate::parse;
-use big_s::S;
-use bimap_plus_map::BiMapPlusMap;
-use std::collections::HashMap;
-
-type Table = HashMap<String, String>;
-
-fn to_pinyin(ident: parse::Identifier, conversion_table: &Table) -> String {
-    let parse::Identifier(i) = ident;
-    let vec = i
-        .chars()
-        .map(|c| match conversion_table.get(&format!("{:X}", c as u32)) {
-            None => S("_"),
-            Some(a) => a.split(' ').collect::<Vec<_>>()[0].to_string(),
-        })
-        .collect::<Vec<_>>();
-    vec.join("")
-}
+use crate::romanize::Romanizer;
+use bimap::BiMap;
+use std::collections::HashSet;
 
 type Hanzi = parse::Identifier;
 type Ascii = String;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Type {
-    Mutable,
+/// The strict and reserved keywords of Rust 2018; written as raw identifiers such as `r#loop`
+const KEYWORDS: [&str; 51] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Keywords that cannot be raw identifiers
+const NON_RAW_KEYWORDS: [&str; 5] = ["crate", "self", "Self", "super", "_"];
+
+/// The items of the std prelude, the primitive types and the macros that the compiled code uses.
+/// Some of them (such as `None`) would be taken as patterns by `let`; the others would merely be confusing.
+const PRELUDE: [&str; 51] = [
+    "main",
+    "Copy",
+    "Send",
+    "Sized",
+    "Sync",
+    "Unpin",
+    "Drop",
+    "Fn",
+    "FnMut",
+    "FnOnce",
+    "drop",
+    "Box",
+    "ToOwned",
+    "Clone",
+    "PartialEq",
+    "PartialOrd",
+    "Eq",
+    "Ord",
+    "AsRef",
+    "AsMut",
+    "Into",
+    "From",
+    "Default",
+    "Iterator",
+    "Extend",
+    "IntoIterator",
+    "DoubleEndedIterator",
+    "ExactSizeIterator",
+    "Option",
+    "Some",
+    "None",
+    "Result",
+    "Ok",
+    "Err",
+    "String",
+    "ToString",
+    "Vec",
+    "bool",
+    "char",
+    "str",
+    "f32",
+    "f64",
+    "i32",
+    "i64",
+    "isize",
+    "u32",
+    "u64",
+    "usize",
+    "println",
+    "print",
+    "vec",
+];
+
+/// `_ans1`, `_rand1`, `_entry1` and so on, and `_stack`, are the temporaries made by the compiler
+pub(crate) fn is_temporary(name: &str) -> bool {
+    name == "_stack"
+        || ["_ans", "_rand", "_entry"].iter().any(|prefix| {
+            name.strip_prefix(prefix).map_or(false, |n| {
+                !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())
+            })
+        })
+}
+
+/// The name of a binding that is never read: `_jia3` for `jia3`, `_loop` for `r#loop`
+fn unread_name(name: &str) -> Ascii {
+    format!("_{}", name.strip_prefix("r#").unwrap_or(name))
+}
+
+/// Makes `name` usable as a variable
+fn reserve(name: Ascii) -> Ascii {
+    if NON_RAW_KEYWORDS.contains(&name.as_str())
+        || PRELUDE.contains(&name.as_str())
+        || is_temporary(&name)
+    {
+        format!("{}_", name)
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{}", name)
+    } else {
+        name
+    }
+}
+
+/// A declaration, i.e. a `let` in the output
+#[derive(Debug)]
+struct Binding {
+    name: Hanzi,
+    /// assigned to while it may already have a value, indexed into on the left of `=`, or pushed to
+    mutable: bool,
+    /// indexed into on the left of `=`, or pushed to
+    mutated: bool,
+    /// otherwise it is written as `_jia3`, which rustc does not warn about
+    read: bool,
+    /// the value it is declared with is never read; it is then declared without one, as `let jia3;`
+    deferred: bool,
+}
+
+/// The index of the scope and the index in `Scope::bindings`
+pub type BindingId = (usize, usize);
+
+/// The declarations and the assignments `name = …` are numbered in the order of the program
+type WriteId = usize;
+
+/// What a statement does to the bindings, in the order it happens; used to find the values that are never read
+#[derive(Debug)]
+enum Flow {
+    Read(BindingId),
+    Declare(BindingId, WriteId),
+    /// an assignment `name = …`
+    Write(BindingId, WriteId),
+    /// the branches of an `if`, the `else` included even if empty
+    Branches(Vec<Vec<Flow>>),
+    /// `head` runs before every iteration, such as the condition of a `while`
+    Loop {
+        head: Vec<Flow>,
+        body: Vec<Flow>,
+        may_skip: bool,
+    },
+    Break,
+    Continue,
+}
+
+/// Where the control goes after `break` and `continue`, as the bindings live there
+struct Jumps<'a> {
+    break_: &'a HashSet<BindingId>,
+    continue_: &'a HashSet<BindingId>,
 }
 
-pub struct IdentBiMap(BiMapPlusMap<Hanzi, Ascii, Option<Type>>);
+/// Backward liveness analysis: the bindings whose current value may be read later,
+/// given those live after `flows`.
+/// The writes of a value that is never read go to `dead`, which is `None` while a loop is not yet at its fixpoint.
+fn live_before(
+    flows: &[Flow],
+    live_after: HashSet<BindingId>,
+    jumps: &Jumps,
+    mut dead: Option<&mut HashSet<(BindingId, WriteId)>>,
+) -> HashSet<BindingId> {
+    let mut live = live_after;
+    for flow in flows.iter().rev() {
+        match flow {
+            Flow::Read(id) => {
+                live.insert(*id);
+            }
+            Flow::Declare(id, write) | Flow::Write(id, write) => {
+                if !live.remove(id) {
+                    if let Some(dead) = dead.as_mut() {
+                        dead.insert((*id, *write));
+                    }
+                }
+            }
+            Flow::Branches(branches) => {
+                let mut ans = HashSet::new();
+                for branch in branches {
+                    ans.extend(live_before(
+                        branch,
+                        live.clone(),
+                        jumps,
+                        dead.as_deref_mut(),
+                    ));
+                }
+                live = ans;
+            }
+            Flow::Loop {
+                head,
+                body,
+                may_skip,
+            } => {
+                // the bindings live at the start of `head`, where both `continue` and the end of `body` go
+                let mut start = HashSet::new();
+                loop {
+                    let loop_jumps = Jumps {
+                        break_: &live,
+                        continue_: &start,
+                    };
+                    let mut after_head = live_before(body, start.clone(), &loop_jumps, None);
+                    if *may_skip {
+                        after_head.extend(live.iter().copied());
+                    }
+                    let new_start = live_before(head, after_head, &loop_jumps, None);
+                    if new_start == start {
+                        break;
+                    }
+                    start = new_start;
+                }
+                if let Some(dead) = dead.as_deref_mut() {
+                    let loop_jumps = Jumps {
+                        break_: &live,
+                        continue_: &start,
+                    };
+                    let mut after_head =
+                        live_before(body, start.clone(), &loop_jumps, Some(&mut *dead));
+                    if *may_skip {
+                        after_head.extend(live.iter().copied());
+                    }
+                    live_before(head, after_head, &loop_jumps, Some(dead));
+                }
+                live = start;
+            }
+            Flow::Break => live.clone_from(jumps.break_),
+            Flow::Continue => live.clone_from(jumps.continue_),
+        }
+    }
+    live
+}
+
+/// Where the control goes after `break` and `continue`, as the bindings that may have a value there
+#[derive(Default)]
+struct Exits {
+    break_: HashSet<BindingId>,
+    continue_: HashSet<BindingId>,
+}
+
+/// Forward analysis: the bindings that may have a value, given those before `flows`.
+/// A write in `dead` is not one, since it is compiled to `let _ = …;`.
+/// The bindings given a value while they may already have one go to `again`; they need `mut`.
+fn assigned_after(
+    flows: &[Flow],
+    assigned_before: HashSet<BindingId>,
+    exits: &mut Exits,
+    dead: &HashSet<WriteId>,
+    again: &mut HashSet<BindingId>,
+) -> HashSet<BindingId> {
+    let mut assigned = assigned_before;
+    for flow in flows {
+        match flow {
+            Flow::Read(_) => {}
+            Flow::Declare(id, write) => {
+                if dead.contains(write) {
+                    assigned.remove(id);
+                } else {
+                    assigned.insert(*id);
+                }
+            }
+            Flow::Write(id, write) => {
+                if !dead.contains(write) && !assigned.insert(*id) {
+                    again.insert(*id);
+                }
+            }
+            Flow::Branches(branches) => {
+                let mut ans = HashSet::new();
+                for branch in branches {
+                    ans.extend(assigned_after(branch, assigned.clone(), exits, dead, again));
+                }
+                assigned = ans;
+            }
+            Flow::Loop {
+                head,
+                body,
+                may_skip,
+            } => {
+                // the bindings that may have a value at the start of `head`
+                let mut start = assigned;
+                loop {
+                    let mut loop_exits = Exits::default();
+                    let after_head =
+                        assigned_after(head, start.clone(), &mut loop_exits, dead, again);
+                    let mut new_start =
+                        assigned_after(body, after_head.clone(), &mut loop_exits, dead, again);
+                    new_start.extend(loop_exits.continue_);
+                    new_start.extend(start.iter().copied());
+                    if new_start == start {
+                        assigned = loop_exits.break_;
+                        if *may_skip {
+                            assigned.extend(after_head);
+                        }
+                        break;
+                    }
+                    start = new_start;
+                }
+            }
+            Flow::Break => exits.break_.extend(std::mem::take(&mut assigned)),
+            Flow::Continue => exits.continue_.extend(std::mem::take(&mut assigned)),
+        }
+    }
+    assigned
+}
+
+/// A block of the source: the whole program, a loop body or a branch of an `if`
+#[derive(Debug)]
+struct Scope {
+    parent: Option<usize>,
+    /// in the order of declaration
+    bindings: Vec<Binding>,
+}
+
+/// Maps each identifier to a single Rust name, and records which declaration every use of it refers to.
+///
+/// The scopes are built by `new` in one pass over the program.
+/// The compiler then walks the program in the same order, calling `enter_scope` and `exit_scope`
+/// around every block and `declare` on every declaration, so that it meets the same bindings again.
+/// Since the Rust code has the same blocks as the source, Rust's shadowing resolves each name
+/// to the same binding as we did.
+pub struct IdentBiMap {
+    names: BiMap<Hanzi, Ascii>,
+    /// the identifiers in the order they first appear
+    order: Vec<Hanzi>,
+    /// `scopes[0]` is the whole program; the others are in the order they are entered
+    scopes: Vec<Scope>,
+    /// the innermost scope is the last; the second element is the number of bindings already met in it
+    cursor: Vec<(usize, usize)>,
+    /// the number of scopes already entered
+    entered: usize,
+    /// what the program does to the bindings; the last element is the block being analysed
+    flows: Vec<Vec<Flow>>,
+    /// the writes met so far, declarations and assignments `name = …`
+    writes: WriteId,
+    /// the writes whose value is never read, of bindings that are read elsewhere; rustc warns about these
+    dead_writes: HashSet<WriteId>,
+}
 
 impl IdentBiMap {
-    pub fn translate_from_hanzi(&self, id: &parse::Identifier) -> Ascii {
-        self.0.bimap_get_by_left(id).unwrap().to_string()
+    /// The binding that `id` refers to at this point of the compilation
+    pub fn binding_id(&self, id: &parse::Identifier) -> Result<BindingId, Error> {
+        self.cursor
+            .iter()
+            .rev()
+            .find_map(|(scope, next)| {
+                self.scopes[*scope].bindings[..*next]
+                    .iter()
+                    .rposition(|b| &b.name == id)
+                    .map(|i| (*scope, i))
+            })
+            .ok_or_else(|| Error::UseBeforeDeclaration(id.clone()))
+    }
+
+    /// The Rust name of the binding that `id` refers to at this point of the compilation
+    pub fn translate_from_hanzi(&self, id: &parse::Identifier) -> Result<Ascii, Error> {
+        let (scope, i) = self.binding_id(id)?;
+        let base = self
+            .base_name(id)
+            .ok_or_else(|| Error::UseBeforeDeclaration(id.clone()))?;
+        if self.scopes[scope].bindings[i].read {
+            Ok(base)
+        } else {
+            Ok(unread_name(&base))
+        }
+    }
+
+    fn base_name(&self, id: &parse::Identifier) -> Option<Ascii> {
+        self.names.get_by_left(id).map(ToString::to_string)
+    }
+
+    /// Each identifier with its translation, in the order they first appear.
+    /// A binding that is never read has `_` prepended to this.
+    pub fn mapping(&self) -> Vec<(&Hanzi, Ascii)> {
+        self.order
+            .iter()
+            .filter_map(|id| Some((id, self.base_name(id)?)))
+            .collect()
+    }
+
+    /// Whether `word` is the name of some variable, raw identifiers written without `r#`
+    pub fn is_variable(&self, word: &str) -> bool {
+        self.names
+            .right_values()
+            .any(|name| name.strip_prefix("r#").unwrap_or(name) == word)
+    }
+
+    /// Whether the binding that `id` refers to is declared without a value, as `let jia3;`
+    pub fn is_deferred(&self, id: &parse::Identifier) -> Result<bool, Error> {
+        let (scope, i) = self.binding_id(id)?;
+        Ok(self.scopes[scope].bindings[i].deferred)
+    }
+
+    /// Meets the next assignment `name = …`; returns whether its value is never read,
+    /// in which case it is compiled to `let _ = …;`
+    pub fn assign(&mut self) -> bool {
+        self.next_write()
     }
 
-    pub fn is_mutable(&self, id: &parse::Identifier) -> bool {
-        let typ = self.0.hashmap_get_by_left(id).unwrap();
-        *typ == Some(Type::Mutable)
+    /// Whether the next write has a value that is never read
+    fn next_write(&mut self) -> bool {
+        self.writes += 1;
+        self.dead_writes.contains(&self.writes)
     }
 
-    pub fn new(parsed: &[parse::Statement], conversion_table: &Table) -> Self {
-        let mut ans = IdentBiMap(BiMapPlusMap::new());
-        for st in parsed {
-            ans.insert_stmt(&st, &conversion_table);
+    pub fn new(parsed: &[parse::Statement], romanizer: &dyn Romanizer) -> Result<Self, Error> {
+        let mut ans = IdentBiMap {
+            names: BiMap::new(),
+            order: vec![],
+            scopes: vec![Scope {
+                parent: None,
+                bindings: vec![],
+            }],
+            cursor: vec![(0, 0)],
+            entered: 1,
+            flows: vec![vec![]],
+            writes: 0,
+            dead_writes: HashSet::new(),
+        };
+        ans.insert_stmts(parsed, romanizer)?;
+        ans.cursor = vec![(0, 0)];
+        ans.entered = 1;
+        ans.writes = 0;
+
+        let flows = ans.flows.pop().unwrap();
+        let nowhere = HashSet::new();
+        let jumps = Jumps {
+            break_: &nowhere,
+            continue_: &nowhere,
+        };
+        let mut dead = HashSet::new();
+        live_before(&flows, HashSet::new(), &jumps, Some(&mut dead));
+        // a binding never read is written `_jia3`, and rustc does not warn about its values
+        ans.dead_writes = dead
+            .iter()
+            .filter(|((scope, i), _)| ans.scopes[*scope].bindings[*i].read)
+            .map(|(_, write)| *write)
+            .collect();
+
+        let mut again = HashSet::new();
+        assigned_after(
+            &flows,
+            HashSet::new(),
+            &mut Exits::default(),
+            &ans.dead_writes,
+            &mut again,
+        );
+        for flow in &flows {
+            ans.record_deferred(flow);
+        }
+        for scope in &mut ans.scopes {
+            for binding in &mut scope.bindings {
+                binding.mutable = binding.mutated;
+            }
         }
+        for (scope, i) in again {
+            ans.scopes[scope].bindings[i].mutable = true;
+        }
+        Ok(ans)
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.cursor.push((self.entered, 0));
+        self.entered += 1;
+    }
 
-        eprintln!("{:?}", ans.0);
-        ans
+    pub fn exit_scope(&mut self) {
+        self.cursor.pop();
+    }
+
+    /// Meets the next declaration in the current scope, which must be of `id`; returns whether it needs `mut`
+    pub fn declare(&mut self, id: &parse::Identifier) -> bool {
+        let (scope, next) = self.cursor.last_mut().unwrap();
+        let binding = &self.scopes[*scope].bindings[*next];
+        assert_eq!(
+            &binding.name, id,
+            "the compiler must declare in the same order as IdentBiMap::new"
+        );
+        *next += 1;
+        let mutable = binding.mutable;
+        self.next_write();
+        mutable
+    }
+
+    /// Marks the bindings whose declaration in `flow`, blocks included, has a value never read
+    fn record_deferred(&mut self, flow: &Flow) {
+        match flow {
+            Flow::Declare(id, write) if self.dead_writes.contains(write) => {
+                self.binding_mut(*id).deferred = true;
+            }
+            Flow::Branches(branches) => {
+                for flow in branches.iter().flatten() {
+                    self.record_deferred(flow);
+                }
+            }
+            Flow::Loop { head, body, .. } => {
+                for flow in head.iter().chain(body) {
+                    self.record_deferred(flow);
+                }
+            }
+            _ => {}
+        }
     }
 
-    fn insert_ident(&mut self, ident: &parse::Identifier, conversion_table: &Table) {
+    fn name_ident(&mut self, ident: &parse::Identifier, romanizer: &dyn Romanizer) {
         // if already known, no need to do anything
-        if self.0.bimap_get_by_left(&ident).is_some() {
+        if self.names.get_by_left(&ident).is_some() {
             return;
         }
 
         // otherwise, ident is unknown, and hence must be added.
 
-        let mut candidate: Ascii = to_pinyin(ident.clone(), &conversion_table);
+        let mut candidate: Ascii = reserve(romanizer.romanize(ident));
 
         loop {
-            if self.0.bimap_get_by_right(&candidate).is_some() {
+            if self.is_taken(&candidate) {
                 candidate.push('_');
             } else {
-                self.0.insert(ident.clone(), candidate, None);
+                self.names.insert(ident.clone(), candidate);
+                self.order.push(ident.clone());
                 break;
             }
         }
     }
 
-    fn insert_stmts(&mut self, statements: &[parse::Statement], conversion_table: &Table) {
+    /// `name` must differ from the other names both as is and with `_` prepended
+    fn is_taken(&self, name: &str) -> bool {
+        let unread = unread_name(name);
+        is_temporary(&unread)
+            || self.names.right_values().any(|other| {
+                let other_unread = unread_name(other);
+                other == name || other == &unread || other_unread == name || other_unread == unread
+            })
+    }
+
+    fn current_scope(&self) -> usize {
+        self.cursor.last().unwrap().0
+    }
+
+    fn enter_new_scope(&mut self) {
+        let parent = self.current_scope();
+        self.scopes.push(Scope {
+            parent: Some(parent),
+            bindings: vec![],
+        });
+        self.enter_scope();
+    }
+
+    fn record(&mut self, flow: Flow) {
+        self.flows.last_mut().unwrap().push(flow);
+    }
+
+    fn declare_ident(&mut self, ident: &parse::Identifier, romanizer: &dyn Romanizer) {
+        self.name_ident(ident, romanizer);
+        let scope = self.current_scope();
+        self.scopes[scope].bindings.push(Binding {
+            name: ident.clone(),
+            mutable: false,
+            mutated: false,
+            read: false,
+            deferred: false,
+        });
+        self.writes += 1;
+        self.record(Flow::Declare(
+            (scope, self.scopes[scope].bindings.len() - 1),
+            self.writes,
+        ));
+    }
+
+    /// The binding that `ident` refers to at this point, i.e. the latest declaration in the innermost scope that has one
+    fn resolve(&self, ident: &parse::Identifier) -> Result<BindingId, Error> {
+        let mut scope = Some(self.current_scope());
+        while let Some(s) = scope {
+            if let Some(i) = self.scopes[s]
+                .bindings
+                .iter()
+                .rposition(|b| &b.name == ident)
+            {
+                return Ok((s, i));
+            }
+            scope = self.scopes[s].parent;
+        }
+        Err(Error::UseBeforeDeclaration(ident.clone()))
+    }
+
+    fn binding_mut(&mut self, (scope, i): BindingId) -> &mut Binding {
+        &mut self.scopes[scope].bindings[i]
+    }
+
+    fn insert_ident(&mut self, ident: &parse::Identifier) -> Result<(), Error> {
+        let id = self.resolve(ident)?;
+        self.binding_mut(id).read = true;
+        self.record(Flow::Read(id));
+        Ok(())
+    }
+
+    /// `ident = ...`
+    fn assign_ident(&mut self, ident: &parse::Identifier) -> Result<(), Error> {
+        let id = self.resolve(ident)?;
+        self.writes += 1;
+        self.record(Flow::Write(id, self.writes));
+        Ok(())
+    }
+
+    /// `ident[...] = ...` or `ident.push(...)`, which both borrow `ident` mutably
+    fn mutate_ident(&mut self, ident: &parse::Identifier) -> Result<(), Error> {
+        self.insert_ident(ident)?;
+        let id = self.resolve(ident)?;
+        self.binding_mut(id).mutated = true;
+        Ok(())
+    }
+
+    /// Records what `analyse` does into a separate list of flows, and returns it
+    fn nested(
+        &mut self,
+        analyse: impl FnOnce(&mut Self) -> Result<(), Error>,
+    ) -> Result<Vec<Flow>, Error> {
+        self.flows.push(vec![]);
+        analyse(self)?;
+        Ok(self.flows.pop().unwrap())
+    }
+
+    fn insert_loop(
+        &mut self,
+        statements: &[parse::Statement],
+        may_skip: bool,
+        romanizer: &dyn Romanizer,
+    ) -> Result<(), Error> {
+        let body = self.nested(|s| s.insert_block(statements, romanizer))?;
+        self.record(Flow::Loop {
+            head: vec![],
+            body,
+            may_skip,
+        });
+        Ok(())
+    }
+
+    fn insert_stmts(
+        &mut self,
+        statements: &[parse::Statement],
+        romanizer: &dyn Romanizer,
+    ) -> Result<(), Error> {
         for s in statements {
-            self.insert_stmt(&s, &conversion_table)
+            self.insert_stmt(&s, romanizer)?;
         }
+        Ok(())
     }
 
-    fn insert_dat(&mut self, dat: &parse::Data, conversion_table: &Table) {
+    fn insert_block(
+        &mut self,
+        statements: &[parse::Statement],
+        romanizer: &dyn Romanizer,
+    ) -> Result<(), Error> {
+        self.enter_new_scope();
+        self.insert_stmts(statements, romanizer)?;
+        self.exit_scope();
+        Ok(())
+    }
+
+    fn insert_dat(&mut self, dat: &parse::Data) -> Result<(), Error> {
         if let parse::Data::Identifier(id) = dat {
-            self.insert_ident(&id, &conversion_table)
+            self.insert_ident(&id)?;
         }
+        Ok(())
     }
 
-    fn insert_data_or_qi2(&mut self, dat: &parse::OrQi2<parse::Data>, conversion_table: &Table) {
+    fn insert_data_or_qi2(&mut self, dat: &parse::OrQi2<parse::Data>) -> Result<(), Error> {
         if let parse::OrQi2::NotQi2(d1) = dat {
-            self.insert_dat(d1, &conversion_table);
+            self.insert_dat(d1)?;
         }
+        Ok(())
     }
 
-    fn insert_rvaluenoqi2(&mut self, val: &parse::Value<parse::Data>, conversion_table: &Table) {
+    fn insert_rvaluenoqi2(&mut self, val: &parse::Value<parse::Data>) -> Result<(), Error> {
         match val {
             parse::Value::Index(data, _)
             | parse::Value::Simple(data)
-            | parse::Value::Length(data) => self.insert_dat(data, &conversion_table),
+            | parse::Value::Length(data) => self.insert_dat(data),
             parse::Value::IndexByIdent(data, ident) => {
-                self.insert_dat(data, &conversion_table);
-                self.insert_ident(ident, &conversion_table)
+                self.insert_dat(data)?;
+                self.insert_ident(ident)
             }
         }
     }
 
-    fn insert_unaryifexpr(&mut self, unary: &parse::UnaryIfExpr, conversion_table: &Table) {
+    fn insert_unaryifexpr(&mut self, unary: &parse::UnaryIfExpr) -> Result<(), Error> {
         match unary {
-            parse::UnaryIfExpr::Simple(data) => self.insert_data_or_qi2(data, &conversion_table),
-            parse::UnaryIfExpr::Complex(val) => self.insert_rvaluenoqi2(val, &conversion_table),
+            parse::UnaryIfExpr::Simple(data) => self.insert_data_or_qi2(data),
+            parse::UnaryIfExpr::Complex(val) => self.insert_rvaluenoqi2(val),
         }
     }
 
-    fn insert_ifexpr(&mut self, ifexpr: &parse::IfCond, conversion_table: &Table) {
+    fn insert_ifexpr(&mut self, ifexpr: &parse::IfCond) -> Result<(), Error> {
         match ifexpr {
             parse::IfCond::Binary(data1, _, data2) => {
-                self.insert_unaryifexpr(data1, &conversion_table);
-                self.insert_unaryifexpr(data2, &conversion_table);
+                self.insert_unaryifexpr(data1)?;
+                self.insert_unaryifexpr(data2)
             }
-            parse::IfCond::Unary(data) => {
-                self.insert_unaryifexpr(data, &conversion_table);
-            }
-            parse::IfCond::NotQi2 => {}
+            parse::IfCond::Unary(data) => self.insert_unaryifexpr(data),
+            parse::IfCond::NotQi2 => Ok(()),
         }
     }
 
-    fn insert_math(&mut self, math: &parse::MathKind, conversion_table: &Table) {
+    fn insert_math(&mut self, math: &parse::MathKind) -> Result<(), Error> {
         match math {
-            parse::MathKind::ArithUnaryMath(data) => {
-                self.insert_data_or_qi2(data, &conversion_table)
-            }
+            parse::MathKind::ArithUnaryMath(data) => self.insert_data_or_qi2(data),
 
             parse::MathKind::ArithBinaryMath(_, data1, _, data2)
             | parse::MathKind::ModMath(_, data1, _, data2) => {
-                self.insert_data_or_qi2(data1, &conversion_table);
-                self.insert_data_or_qi2(data2, &conversion_table);
+                self.insert_data_or_qi2(data1)?;
+                self.insert_data_or_qi2(data2)
             }
             parse::MathKind::BooleanAlgebra(ident1, ident2, _) => {
-                self.insert_ident(&ident1, &conversion_table);
-                self.insert_ident(&ident2, &conversion_table);
+                self.insert_ident(&ident1)?;
+                self.insert_ident(&ident2)
             }
         }
     }
-    fn insert_rvalue(
-        &mut self,
-        rv: &parse::Value<parse::OrQi2<parse::Data>>,
-        conversion_table: &Table,
-    ) {
+    fn insert_rvalue(&mut self, rv: &parse::Value<parse::OrQi2<parse::Data>>) -> Result<(), Error> {
         match rv {
             parse::Value::Index(data, _)
             | parse::Value::Length(data)
-            | parse::Value::Simple(data) => self.insert_data_or_qi2(data, &conversion_table),
+            | parse::Value::Simple(data) => self.insert_data_or_qi2(data),
             parse::Value::IndexByIdent(data, ident) => {
-                self.insert_data_or_qi2(data, &conversion_table);
-                self.insert_ident(ident, &conversion_table)
+                self.insert_data_or_qi2(data)?;
+                self.insert_ident(ident)
             }
         }
     }
-    fn insert_idents(&mut self, idents: &[parse::Identifier], conversion_table: &Table) {
+    fn insert_idents(&mut self, idents: &[parse::Identifier]) -> Result<(), Error> {
         for ident in idents {
-            self.insert_ident(&ident, &conversion_table)
+            self.insert_ident(&ident)?;
         }
+        Ok(())
     }
-    fn insert_dats(&mut self, data_arr: &[parse::Data], conversion_table: &Table) {
+    fn insert_dats(&mut self, data_arr: &[parse::Data]) -> Result<(), Error> {
         for dat in data_arr {
-            self.insert_dat(dat, &conversion_table);
+            self.insert_dat(dat)?;
         }
+        Ok(())
     }
-    fn insert_stmt(&mut self, st: &parse::Statement, conversion_table: &Table) {
+    fn insert_stmt(
+        &mut self,
+        st: &parse::Statement,
+        romanizer: &dyn Romanizer,
+    ) -> Result<(), Error> {
         use parse::Statement::*;
         match st {
-            ReferenceWhatIsLeft { data } => {
-                self.insert_dat(&data, &conversion_table);
-            }
+            ReferenceWhatIsLeft { data } => self.insert_dat(&data),
             ForArr { list, elem, stmts } => {
-                self.insert_ident(&list, &conversion_table);
-                self.insert_ident(&elem, &conversion_table);
-                self.insert_stmts(&stmts, &conversion_table)
+                self.insert_ident(&list)?;
+                let body = self.nested(|s| {
+                    s.enter_new_scope();
+                    s.declare_ident(&elem, romanizer);
+                    s.insert_stmts(&stmts, romanizer)?;
+                    s.exit_scope();
+                    Ok(())
+                })?;
+                self.record(Flow::Loop {
+                    head: vec![],
+                    body,
+                    may_skip: true,
+                });
+                Ok(())
             }
             ArrayCat { append_to, elems } => {
-                self.insert_data_or_qi2(&parse::OrQi2::from(append_to), &conversion_table);
-                self.insert_idents(&elems, &conversion_table)
+                self.insert_data_or_qi2(&parse::OrQi2::from(append_to))?;
+                self.insert_idents(&elems)
             }
             ArrayFill {
                 what_to_fill,
                 elems,
             } => {
-                self.insert_data_or_qi2(&parse::OrQi2::from(what_to_fill), &conversion_table);
                 if let parse::OrQi2::NotQi2(ident) = what_to_fill {
-                    let ascii = self.0.bimap_get_by_left(&ident).unwrap().clone();
-                    self.0.insert(ident.clone(), ascii, Some(Type::Mutable));
+                    self.mutate_ident(ident)?;
                 }
-                self.insert_dats(&elems, &conversion_table);
+                self.insert_dats(&elems)
             }
             If {
                 ifcase: (ifexpr, ifcase),
                 elseifcases,
                 elsecase,
             } => {
-                self.insert_ifexpr(ifexpr, &conversion_table);
-                self.insert_stmts(&ifcase, &conversion_table);
-                for (elseifexpr, elseifcase) in elseifcases {
-                    self.insert_ifexpr(elseifexpr, &conversion_table);
-                    self.insert_stmts(&elseifcase, &conversion_table)
+                // the conditions are read before any branch is taken
+                self.insert_ifexpr(ifexpr)?;
+                for (elseifexpr, _) in elseifcases {
+                    self.insert_ifexpr(elseifexpr)?;
+                }
+                let mut branches = vec![self.nested(|s| s.insert_block(&ifcase, romanizer))?];
+                for (_, elseifcase) in elseifcases {
+                    branches.push(self.nested(|s| s.insert_block(&elseifcase, romanizer))?);
                 }
-                self.insert_stmts(&elsecase, &conversion_table)
+                branches.push(self.nested(|s| s.insert_block(&elsecase, romanizer))?);
+                self.record(Flow::Branches(branches));
+                Ok(())
             }
-            Reference { rvalue } => self.insert_rvaluenoqi2(rvalue, &conversion_table),
-            NameMulti { idents } => self.insert_idents(&idents, &conversion_table),
-            Math { math } => self.insert_math(math, &conversion_table),
+            Reference { rvalue } => self.insert_rvaluenoqi2(rvalue),
+            NameMulti { idents } => {
+                for ident in idents {
+                    self.declare_ident(ident, romanizer);
+                }
+                Ok(())
+            }
+            Math { math } => self.insert_math(math),
             Assignment {
                 lvalue: parse::Lvalue::Simple(ident),
                 rvalue,
+            } => {
+                self.insert_rvalue(rvalue)?;
+                self.assign_ident(&ident)
             }
-            | Assignment {
+            Assignment {
                 lvalue: parse::Lvalue::Index(ident, _),
                 rvalue,
             } => {
-                self.insert_ident(&ident, &conversion_table);
-                let ascii = self.0.bimap_get_by_left(&ident).unwrap().clone();
-                self.0.insert(ident.clone(), ascii, Some(Type::Mutable));
-                self.insert_rvalue(rvalue, &conversion_table)
+                self.insert_rvalue(rvalue)?;
+                self.mutate_ident(&ident)
             }
             Assignment {
                 lvalue: parse::Lvalue::IndexByIdent(ident, index),
                 rvalue,
             } => {
-                self.insert_ident(&ident, &conversion_table);
-                let ascii = self.0.bimap_get_by_left(&ident).unwrap().clone();
-                self.0.insert(ident.clone(), ascii, Some(Type::Mutable));
-                self.insert_ident(&index, &conversion_table);
-                self.insert_rvalue(rvalue, &conversion_table)
+                self.insert_rvalue(rvalue)?;
+                self.insert_ident(&index)?;
+                self.mutate_ident(&ident)
             }
-            Print | Flush | Break | Continue => {}
-            ForEnum { statements, num: _ } | Loop { statements } => {
-                self.insert_stmts(&statements, &conversion_table)
+            Print | Flush => Ok(()),
+            Break => {
+                self.record(Flow::Break);
+                Ok(())
             }
+            Continue => {
+                self.record(Flow::Continue);
+                Ok(())
+            }
+            ForEnum { statements, num: _ } => self.insert_loop(&statements, true, romanizer),
+            Loop { statements } => self.insert_loop(&statements, false, romanizer),
             Declare(parse::DeclareStatement {
                 how_many_variables: _,
                 type_: _,
                 data_arr,
-            }) => self.insert_dats(data_arr, &conversion_table),
+            }) => self.insert_dats(data_arr),
             InitDefine {
                 name,
                 type_: _,
                 data: dat,
             } => {
-                self.insert_dat(dat, &conversion_table);
-                self.insert_ident(&name, &conversion_table)
+                self.insert_dat(dat)?;
+                self.declare_ident(&name, romanizer);
+                Ok(())
             }
             ForEnumIdent { ident, statements } => {
-                if let parse::OrQi2::NotQi2(i) = ident {
-                    self.insert_ident(&i, &conversion_table);
-                }
-                self.insert_stmts(&statements, &conversion_table)
+                // compiled to a `while` that reads `ident` before every iteration
+                let head = self.nested(|s| match ident {
+                    parse::OrQi2::NotQi2(i) => s.insert_ident(&i),
+                    parse::OrQi2::Qi2 => Ok(()),
+                })?;
+                let body = self.nested(|s| s.insert_block(&statements, romanizer))?;
+                self.record(Flow::Loop {
+                    head,
+                    body,
+                    may_skip: true,
+                });
+                Ok(())
             }
             Define {
                 idents,
                 decl:
                     parse::DeclareStatement {
-                        how_many_variables: _,
+                        how_many_variables,
                         type_: _,
                         data_arr,
                     },
             } => {
-                self.insert_dats(data_arr, &conversion_table);
-                self.insert_idents(&idents, &conversion_table)
+                self.insert_dats(data_arr)?;
+                // idents beyond `how_many_variables` are ignored; see `ir::lower_define`
+                for ident in idents.iter().take(*how_many_variables) {
+                    self.declare_ident(ident, romanizer);
+                }
+                Ok(())
             }
         }
     }
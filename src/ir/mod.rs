@@ -0,0 +1,931 @@
+use crate::lex;
+use crate::parse;
+use crate::unparse::type_name;
+
+/// A value pushed on the stack; they are numbered from 1 in the order of the program
+pub type Temp = usize;
+
+/// A loop that keeps the values before it on the runtime stack, see `Op::Enter`;
+/// they are numbered from 1 in the order of the program
+pub type Block = usize;
+
+/// What an operation reads
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// a literal or a variable
+    Data(parse::Data),
+    /// the value that a `Pop` just before took off the stack
+    Temp(Temp),
+    /// `其` when the stack is empty
+    Undefined,
+}
+
+/// What `Push` evaluates
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// a value of `吾有` or `有`, the default of the type if not given
+    Init(lex::Type, Option<parse::Data>),
+    /// `夫`: a value, an element (1-based) or a length
+    Value(parse::Value<Operand>),
+    /// `op` is written the same in Rust and JavaScript: `+`, `-`, `*`, `/`, `%`, `&&` or `||`.
+    /// The operands are in the order of the operation, whatever the preposition.
+    Binary(&'static str, Operand, Operand),
+    /// `變`
+    Not(Operand),
+    /// `其餘`: all the elements but the first
+    Rest(Operand),
+    /// `銜`: a new list
+    Concat(Operand, Vec<parse::Identifier>),
+}
+
+/// The condition of `若`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cond {
+    Unary(parse::Value<Operand>),
+    /// `op` is one of `==`, `!=`, `<`, `>`, `<=` and `>=`
+    Binary(parse::Value<Operand>, &'static str, parse::Value<Operand>),
+    /// `若其不然者`
+    Not(Operand),
+}
+
+/// A statement lowered so that what it does to the stack is explicit.
+/// Every `其` is a `Pop` before the operation that uses it, and every operation that takes values from the stack
+/// lists them, so that a backend only has to follow the temporaries.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Op {
+    /// Evaluates the expression and pushes it
+    Push(Temp, Expr),
+    /// `其`: takes `top` off the stack, to be read by the operation that follows, and drops the values under it.
+    /// 《文言陰符》曰『言「其」者。取至近之魚而棄其餘。』
+    Pop {
+        top: Temp,
+        dropped: Vec<Temp>,
+    },
+    /// `名之曰`: declares each identifier with the value of the stack that it names, `None` for `undefined`.
+    /// The values named leave the stack.
+    Name(Vec<(parse::Identifier, Option<Temp>)>),
+    /// `吾有…名之曰` and `有…名之曰`: a variable declared with a value that never is on the stack
+    Declare(parse::Identifier, Expr),
+    /// `書之`: prints the values, which are the whole stack, bottom first, and empties it
+    Print(Vec<Temp>),
+    /// `噫` and the end of the program, which drop the whole stack; also the end of a block, which drops the values
+    /// pushed in it, and what follows a block, which drops the values before it that all its ways through took
+    Flush(Vec<Temp>),
+    /// Puts the values on the runtime stack, bottom first, above those already there. There is one for the program,
+    /// only used where what is on the stack after a block depends on the way taken through it, see `Enter`.
+    Spill(Vec<Temp>),
+    /// Takes values off the top of the runtime stack, the last temporary getting the top; a value missing is `undefined`
+    Unspill(Vec<Temp>),
+    /// `書之` while the runtime stack may hold values: prints them, then the values above, and empties it
+    PrintSpilled(Vec<Temp>),
+    /// `其` and `噫` while the runtime stack may hold values, which drop them with the values above
+    DropSpilled,
+    /// Before a block whose values before it are on the runtime stack: from now on, it also counts how many of these
+    /// are left. A loop, with `Some`, also keeps them, to be there again at the start of every iteration.
+    Enter(Option<Block>),
+    /// At the start of every iteration of such a loop
+    Restore(Block),
+    /// After such a block: drops the values pushed in it, leaving those before it that the way taken left
+    Leave,
+    /// At `乃止` and `乃止是遍`, out of this many such blocks in the loop, which are not left by `Leave`
+    Unmark(usize),
+    /// `昔之…者。今…是矣`
+    Assign(parse::Lvalue, parse::Value<Operand>),
+    /// `充`
+    Fill(Operand, Vec<parse::Data>),
+    /// the body of the first branch whose condition holds, otherwise `else_`
+    If {
+        branches: Vec<Branch>,
+        else_: Vec<Op>,
+    },
+    /// `為是百遍`
+    Repeat(i64, Vec<Op>),
+    /// `為是「甲」遍` and `為是其遍`
+    RepeatBy(Operand, Vec<Op>),
+    /// `凡「甲」中之「乙」`
+    ForEach {
+        list: parse::Identifier,
+        elem: parse::Identifier,
+        body: Vec<Op>,
+    },
+    /// `恆為是`
+    Loop(Vec<Op>),
+    Break,
+    Continue,
+    /// The ops that follow in the block, up to the next `Origin`, come from the statement with this index in `cst::preorder`
+    Origin(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Branch {
+    /// the `Pop`s of the `其` in `cond`, which only happen if the conditions before do not hold
+    pub pops: Vec<Op>,
+    pub cond: Cond,
+    pub body: Vec<Op>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    pub ops: Vec<Op>,
+}
+
+/// The stack as the program is lowered
+#[derive(Clone, Default)]
+struct Env {
+    /// the values not yet named, bottom first, above those of the runtime stack
+    stack: Vec<Temp>,
+    /// the last temporary pushed
+    temp_counter: Temp,
+    /// the statements lowered so far, blocks included
+    statement_counter: usize,
+    /// whether a `乃止` or `乃止是遍` was lowered since the start of the block, so that the ops that follow are not reached
+    jumped: bool,
+    /// the loops being lowered, the innermost last
+    loops: Vec<Exits>,
+    /// the first temporary that may be on the runtime stack, `None` if it is empty
+    runtime: Option<Temp>,
+    /// the first temporary on the runtime stack that an op used since the start of the block, see `lower_compound`
+    touched: Option<Temp>,
+    /// how many of the blocks being lowered keep the values before them on the runtime stack
+    marks: usize,
+    /// the last loop that keeps the values before it on the runtime stack
+    block_counter: Block,
+}
+
+/// The jumps of a loop, each with how many of the values before the loop it leaves
+#[derive(Clone)]
+struct Exits {
+    /// the last temporary pushed before the loop
+    before: Temp,
+    /// at `乃止`
+    breaks: Vec<usize>,
+    /// at `乃止是遍`
+    continues: Vec<usize>,
+    /// `Env::marks` at the start of the body
+    marks: usize,
+}
+
+/// How many times the body of a loop runs, if not left by `乃止`
+#[derive(Clone, Copy)]
+enum Runs {
+    /// `為是「甲」遍`, `凡`, or `為是百遍` with a count under 1
+    MaybeNever,
+    AtLeastOnce,
+    /// `恆為是`
+    Forever,
+}
+
+impl Env {
+    /// How many of the values on the stack were pushed before the temporary `before`, included;
+    /// they are always at the bottom
+    fn left(&self, before: Temp) -> usize {
+        self.stack.iter().take_while(|&&t| t <= before).count()
+    }
+
+    /// `乃止` if `to_end`, otherwise `乃止是遍`
+    fn jump(&mut self, ops: &mut Vec<Op>, to_end: bool) {
+        if !self.jumped {
+            let exits = self
+                .loops
+                .last()
+                .expect("the parser only allows 乃止 in a loop");
+            let left = self.left(exits.before);
+            if self.marks > exits.marks {
+                ops.push(Op::Unmark(self.marks - exits.marks));
+            }
+            // what the runtime stack holds was pushed in the loop, and is dropped at the end of the body
+            if matches!(self.runtime, Some(t) if t > exits.before) {
+                ops.push(Op::DropSpilled);
+            }
+            let exits = self.loops.last_mut().unwrap();
+            if to_end {
+                exits.breaks.push(left);
+            } else {
+                exits.continues.push(left);
+            }
+        }
+        self.jumped = true;
+    }
+
+    fn new_temp(&mut self) -> Temp {
+        self.temp_counter += 1;
+        self.temp_counter
+    }
+
+    fn push(&mut self, ops: &mut Vec<Op>, expr: Expr) {
+        let temp = self.new_temp();
+        self.stack.push(temp);
+        ops.push(Op::Push(temp, expr));
+    }
+
+    /// Records that an op uses the runtime stack
+    fn touch(&mut self) {
+        if let Some(t) = self.runtime {
+            self.touched = Some(self.touched.map_or(t, |touched| touched.min(t)));
+        }
+    }
+
+    /// Puts the whole stack on the runtime stack
+    fn spill(&mut self, ops: &mut Vec<Op>) {
+        if let Some(&first) = self.stack.first() {
+            self.runtime = Some(self.runtime.map_or(first, |t| t.min(first)));
+            ops.push(Op::Spill(std::mem::take(&mut self.stack)));
+        }
+        self.touch();
+    }
+
+    /// Drops the whole runtime stack, after the values above it were dropped
+    fn drop_spilled(&mut self, ops: &mut Vec<Op>) {
+        if self.runtime.is_some() {
+            self.touch();
+            ops.push(Op::DropSpilled);
+            self.runtime = None;
+        }
+    }
+
+    fn pop(&mut self, ops: &mut Vec<Op>) -> Operand {
+        let top = match (self.stack.pop(), self.runtime) {
+            (Some(top), _) => {
+                let dropped = std::mem::take(&mut self.stack);
+                ops.push(Op::Pop { top, dropped });
+                Operand::Temp(top)
+            }
+            (None, Some(_)) => {
+                let top = self.new_temp();
+                self.touch();
+                ops.push(Op::Unspill(vec![top]));
+                Operand::Temp(top)
+            }
+            (None, None) => Operand::Undefined,
+        };
+        self.drop_spilled(ops);
+        top
+    }
+
+    fn operand(&mut self, ops: &mut Vec<Op>, a: &parse::OrQi2<parse::Data>) -> Operand {
+        match a {
+            parse::OrQi2::Qi2 => self.pop(ops),
+            parse::OrQi2::NotQi2(data) => Operand::Data(data.clone()),
+        }
+    }
+}
+
+/// The same value, with the operands given by `operand`
+fn value<T>(v: &parse::Value<T>, mut operand: impl FnMut(&T) -> Operand) -> parse::Value<Operand> {
+    match v {
+        parse::Value::Simple(a) => parse::Value::Simple(operand(a)),
+        parse::Value::Index(a, index) => parse::Value::Index(operand(a), *index),
+        parse::Value::IndexByIdent(a, index) => {
+            parse::Value::IndexByIdent(operand(a), index.clone())
+        }
+        parse::Value::Length(a) => parse::Value::Length(operand(a)),
+    }
+}
+
+fn data(d: &parse::Data) -> Operand {
+    Operand::Data(d.clone())
+}
+
+fn lower_unaryifexpr(
+    env: &mut Env,
+    ops: &mut Vec<Op>,
+    unary: &parse::UnaryIfExpr,
+) -> parse::Value<Operand> {
+    match unary {
+        parse::UnaryIfExpr::Simple(a) => parse::Value::Simple(env.operand(ops, a)),
+        parse::UnaryIfExpr::Complex(v) => value(v, data),
+    }
+}
+
+fn lower_ifcond(env: &mut Env, ops: &mut Vec<Op>, cond: &parse::IfCond) -> Cond {
+    match cond {
+        parse::IfCond::Unary(a) => Cond::Unary(lower_unaryifexpr(env, ops, a)),
+        parse::IfCond::Binary(a, op, b) => {
+            let a = lower_unaryifexpr(env, ops, a);
+            let b = lower_unaryifexpr(env, ops, b);
+            Cond::Binary(a, op.to_str(), b)
+        }
+        parse::IfCond::NotQi2 => Cond::Not(env.pop(ops)),
+    }
+}
+
+fn lower_math(env: &mut Env, ops: &mut Vec<Op>, math: &parse::MathKind) {
+    let expr = match math {
+        parse::MathKind::ArithBinaryMath(op, a, prep, b) => {
+            lower_math_binary(env, ops, op.to_str(), a, *prep, b)
+        }
+        parse::MathKind::ModMath(op, a, prep, b) => {
+            lower_math_binary(env, ops, op.to_str(), a, *prep, b)
+        }
+        parse::MathKind::BooleanAlgebra(a, b, op) => Expr::Binary(
+            op.to_str(),
+            data(&parse::Data::Identifier(a.clone())),
+            data(&parse::Data::Identifier(b.clone())),
+        ),
+        parse::MathKind::ArithUnaryMath(a) => Expr::Not(env.operand(ops, a)),
+    };
+    env.push(ops, expr);
+}
+
+/// `於` swaps the operands, and `其` takes the left one first:
+/// `加一以三。加二以三。減其以其` and `加一以三。加二以三。減其於其` both take `2 + 3` as the left operand and drop `1 + 3`,
+/// so that the right operand is `undefined`
+fn lower_math_binary(
+    env: &mut Env,
+    ops: &mut Vec<Op>,
+    op: &'static str,
+    a: &parse::OrQi2<parse::Data>,
+    prep: lex::Preposition,
+    b: &parse::OrQi2<parse::Data>,
+) -> Expr {
+    let (a, b) = match prep {
+        lex::Preposition::Yi3 => (a, b),
+        lex::Preposition::Yu2 => (b, a),
+    };
+    let left = env.operand(ops, a);
+    let right = env.operand(ops, b);
+    Expr::Binary(op, left, right)
+}
+
+/// `how_many_variables` always determines how many variables are defined:
+/// `data_arr` is truncated or padded with the default of the type so that its length matches,
+/// the idents name the first ones (those beyond are ignored), and the others are pushed.
+/// `吾有三數。曰三。曰九。名之曰「甲」。` declares 甲 as 3 and pushes 9 and 0.
+fn lower_define(
+    env: &mut Env,
+    ops: &mut Vec<Op>,
+    decl: &parse::DeclareStatement,
+    idents: &[parse::Identifier],
+) {
+    for i in 0..decl.how_many_variables {
+        let expr = Expr::Init(decl.type_, decl.data_arr.get(i).cloned());
+        match idents.get(i) {
+            Some(ident) => ops.push(Op::Declare(ident.clone(), expr)),
+            None => env.push(ops, expr),
+        }
+    }
+}
+
+/// The stack is matched from the end, the first ident naming the earliest of the values:
+/// after `加二以三。加一以三。加三以三。`, `名之曰「甲」。名之曰「乙」。` names 甲 `3 + 3` and 乙 `1 + 3`,
+/// leaving `2 + 3`, whereas `名之曰「甲」曰「乙」` names 甲 `1 + 3` and 乙 `3 + 3`.
+/// The idents without a value are `undefined`.
+fn lower_name_multi(env: &mut Env, ops: &mut Vec<Op>, idents: &[parse::Identifier]) {
+    // the values under those of the stack are taken off the runtime stack
+    if env.runtime.is_some() {
+        let missing = idents.len().saturating_sub(env.stack.len());
+        if missing > 0 {
+            let temps = (0..missing).map(|_| env.new_temp()).collect::<Vec<_>>();
+            env.touch();
+            ops.push(Op::Unspill(temps.clone()));
+            env.stack.splice(0..0, temps);
+        }
+    }
+    let len = env.stack.len();
+    let named = idents
+        .iter()
+        .enumerate()
+        .map(|(i, ident)| {
+            let j = (len + i).checked_sub(idents.len());
+            (ident.clone(), j.map(|j| env.stack[j]))
+        })
+        .collect();
+    env.stack.truncate(len.saturating_sub(idents.len()));
+    ops.push(Op::Name(named));
+}
+
+/// Gives the stack before the branches and how many of those values each way out leaves, see `lower_compound`
+fn lower_if(
+    env: &mut Env,
+    ops: &mut Vec<Op>,
+    ifcase: &parse::CondPlusStatements,
+    elseifcases: &[parse::CondPlusStatements],
+    elsecase: &[parse::Statement],
+    marked: bool,
+) -> (Vec<Temp>, Vec<usize>) {
+    let mut branches = vec![];
+    let mut entry = vec![];
+    let mut runtime = None;
+    let mut left = vec![];
+    for (i, (cond, stmts)) in std::iter::once(ifcase).chain(elseifcases).enumerate() {
+        let mut pops = vec![];
+        // the first condition is always evaluated, the others only if those before do not hold
+        let cond = lower_ifcond(env, &mut pops, cond);
+        if i == 0 {
+            if marked {
+                ops.append(&mut pops);
+                env.spill(ops);
+                ops.push(Op::Enter(None));
+                env.marks += 1;
+            }
+            entry = env.stack.clone();
+            runtime = env.runtime;
+        }
+        let fallthrough = env.stack.clone();
+        let (body, end) = lower_block(env, stmts);
+        left.extend(end);
+        env.stack = fallthrough;
+        branches.push(Branch { pops, cond, body });
+    }
+    // without `若非`, the end of an empty block is the way through that no branch takes
+    let (else_, end) = lower_block(env, elsecase);
+    left.extend(end);
+    ops.push(Op::If { branches, else_ });
+    env.runtime = runtime;
+    if marked {
+        env.marks -= 1;
+    }
+    (entry, left)
+}
+
+/// Drops the whole stack, if there is anything on it
+fn flush(env: &mut Env, ops: &mut Vec<Op>) {
+    if !env.stack.is_empty() {
+        ops.push(Op::Flush(std::mem::take(&mut env.stack)));
+    }
+}
+
+/// A block sees the values before it, as many times as it runs, but the values pushed in it are dropped at its end:
+/// `書之` in a loop prints those before the loop on every iteration, but right after `云云`,
+/// `書之` prints nothing the block pushed and `其` is never one of them.
+/// Gives the ops and how many of the values before the block are left at its end, `None` if the end is not reached.
+fn lower_block(env: &mut Env, stmts: &[parse::Statement]) -> (Vec<Op>, Option<usize>) {
+    let before = env.temp_counter;
+    let jumped = std::mem::replace(&mut env.jumped, false);
+    let runtime = env.runtime;
+    let mut ops = vec![];
+    for st in stmts {
+        lower_statement(env, &mut ops, st);
+    }
+    let left = env.left(before);
+    if env.stack.len() > left {
+        ops.push(Op::Flush(env.stack.split_off(left)));
+    }
+    // a runtime stack that the block started only holds values pushed in it
+    if runtime.is_none() && !env.jumped {
+        env.drop_spilled(&mut ops);
+    }
+    env.runtime = runtime;
+    let end = if env.jumped { None } else { Some(left) };
+    env.jumped = jumped;
+    (ops, end)
+}
+
+/// After a block, `entry` being the stack before it and `left` how many of those values each way out of it leaves,
+/// all the same
+fn leave_block(env: &mut Env, ops: &mut Vec<Op>, mut entry: Vec<Temp>, left: &[usize]) {
+    // no way out: what follows is not reached
+    env.jumped |= left.is_empty();
+    let taken = entry.split_off(left.first().copied().unwrap_or(entry.len()));
+    env.stack = entry;
+    if !taken.is_empty() {
+        ops.push(Op::Flush(taken));
+    }
+}
+
+/// Every iteration starts from the stack before the loop, whatever the previous one took.
+/// Gives that stack and how many of those values each way out leaves, see `lower_compound`.
+fn lower_loop(
+    env: &mut Env,
+    ops: &mut Vec<Op>,
+    stmts: &[parse::Statement],
+    runs: Runs,
+    marked: bool,
+    loop_: impl FnOnce(Vec<Op>) -> Op,
+) -> (Vec<Temp>, Vec<usize>) {
+    let block = if marked {
+        env.block_counter += 1;
+        env.spill(ops);
+        ops.push(Op::Enter(Some(env.block_counter)));
+        env.marks += 1;
+        Some(env.block_counter)
+    } else {
+        None
+    };
+    let entry = env.stack.clone();
+    env.loops.push(Exits {
+        before: env.temp_counter,
+        breaks: vec![],
+        continues: vec![],
+        marks: env.marks,
+    });
+    let (mut body, end) = lower_block(env, stmts);
+    let exits = env.loops.pop().unwrap();
+    let mut left = exits.breaks;
+    // as `乃止是遍`, the end of the body leads to the next iteration, and after the last one out of the loop
+    match runs {
+        Runs::Forever => {}
+        Runs::AtLeastOnce => left.extend(end.into_iter().chain(exits.continues)),
+        Runs::MaybeNever => {
+            left.extend(end.into_iter().chain(exits.continues));
+            left.push(entry.len());
+        }
+    }
+    if let Some(block) = block {
+        body.insert(0, Op::Restore(block));
+        env.marks -= 1;
+    }
+    ops.push(loop_(body));
+    (entry, left)
+}
+
+/// A block lowered by `lower`, which gives the stack before it and how many of those values each way out leaves.
+/// After the block, the stack holds the values before it that the way taken left, the last iteration for a loop:
+/// `加一以二。若「甲」者。書之。云云。書之。` prints `3` once, whether `「甲」` holds or not, in a loop as well.
+/// Where the ways out leave different numbers, or the block uses values on the runtime stack from before it,
+/// which then differ from one time it runs to the other, the block is lowered again with `marked`:
+/// the values before it go on the runtime stack, which keeps track of those left.
+fn lower_compound(
+    env: &mut Env,
+    ops: &mut Vec<Op>,
+    lower: impl Fn(&mut Env, &mut Vec<Op>, bool) -> (Vec<Temp>, Vec<usize>),
+) {
+    let (snapshot, len) = (env.clone(), ops.len());
+    let before = env.temp_counter;
+    let touched = env.touched.take();
+    let (entry, left) = lower(env, ops, false);
+    let used = matches!(env.touched, Some(t) if t <= before);
+    if left.iter().min() == left.iter().max() && !used {
+        env.touched = touched.into_iter().chain(env.touched).min();
+        leave_block(env, ops, entry, &left);
+        return;
+    }
+    *env = snapshot;
+    ops.truncate(len);
+    let (_, left) = lower(env, ops, true);
+    if left.is_empty() {
+        env.jumped = true;
+    } else {
+        ops.push(Op::Leave);
+    }
+}
+
+fn lower_statement(env: &mut Env, ops: &mut Vec<Op>, st: &parse::Statement) {
+    // the statements of the blocks are lowered after `st`, as `cst::preorder` lists them
+    ops.push(Op::Origin(env.statement_counter));
+    env.statement_counter += 1;
+    match st {
+        parse::Statement::Declare(decl) => lower_define(env, ops, decl, &[]),
+        parse::Statement::Define { decl, idents } => lower_define(env, ops, decl, idents),
+        parse::Statement::InitDefine { type_, data, name } => ops.push(Op::Declare(
+            name.clone(),
+            Expr::Init(*type_, Some(data.clone())),
+        )),
+        parse::Statement::NameMulti { idents } => lower_name_multi(env, ops, idents),
+        parse::Statement::Print => {
+            let temps = std::mem::take(&mut env.stack);
+            if env.runtime.is_some() {
+                env.touch();
+                ops.push(Op::PrintSpilled(temps));
+                env.runtime = None;
+            } else {
+                ops.push(Op::Print(temps));
+            }
+        }
+        parse::Statement::Flush => {
+            ops.push(Op::Flush(std::mem::take(&mut env.stack)));
+            env.drop_spilled(ops);
+        }
+        parse::Statement::Break => {
+            env.jump(ops, true);
+            ops.push(Op::Break);
+        }
+        parse::Statement::Continue => {
+            env.jump(ops, false);
+            ops.push(Op::Continue);
+        }
+        parse::Statement::Math { math } => lower_math(env, ops, math),
+        parse::Statement::Reference { rvalue } => env.push(ops, Expr::Value(value(rvalue, data))),
+        parse::Statement::ReferenceWhatIsLeft { data: d } => env.push(ops, Expr::Rest(data(d))),
+        parse::Statement::Assignment { lvalue, rvalue } => {
+            let rvalue = value(rvalue, |a| env.operand(ops, a));
+            ops.push(Op::Assign(lvalue.clone(), rvalue));
+        }
+        parse::Statement::ArrayFill {
+            what_to_fill,
+            elems,
+        } => {
+            let list = env.operand(ops, &parse::OrQi2::from(what_to_fill));
+            ops.push(Op::Fill(list, elems.clone()));
+        }
+        parse::Statement::ArrayCat { append_to, elems } => {
+            let list = env.operand(ops, &parse::OrQi2::from(append_to));
+            env.push(ops, Expr::Concat(list, elems.clone()));
+        }
+        parse::Statement::If {
+            ifcase,
+            elseifcases,
+            elsecase,
+        } => lower_compound(env, ops, |env, ops, marked| {
+            lower_if(env, ops, ifcase, elseifcases, elsecase, marked)
+        }),
+        parse::Statement::ForEnum { num, statements } => {
+            let runs = if *num >= 1 {
+                Runs::AtLeastOnce
+            } else {
+                Runs::MaybeNever
+            };
+            lower_compound(env, ops, |env, ops, marked| {
+                lower_loop(env, ops, statements, runs, marked, |body| {
+                    Op::Repeat(*num, body)
+                })
+            });
+        }
+        parse::Statement::ForEnumIdent { ident, statements } => {
+            let count = env.operand(ops, &parse::OrQi2::from(ident));
+            lower_compound(env, ops, |env, ops, marked| {
+                lower_loop(env, ops, statements, Runs::MaybeNever, marked, |body| {
+                    Op::RepeatBy(count.clone(), body)
+                })
+            });
+        }
+        parse::Statement::ForArr { list, elem, stmts } => {
+            lower_compound(env, ops, |env, ops, marked| {
+                lower_loop(env, ops, stmts, Runs::MaybeNever, marked, |body| {
+                    Op::ForEach {
+                        list: list.clone(),
+                        elem: elem.clone(),
+                        body,
+                    }
+                })
+            });
+        }
+        parse::Statement::Loop { statements } => {
+            lower_compound(env, ops, |env, ops, marked| {
+                lower_loop(env, ops, statements, Runs::Forever, marked, Op::Loop)
+            });
+        }
+    }
+}
+
+/// The stack of wenyan resolved when compiling: every value pushed gets a temporary,
+/// and the ops that take values from the stack say which.
+/// Only where what is on the stack after a block depends on the way taken is a stack kept at runtime
+/// (see `lower_compound`).
+pub fn lower(parsed: &[parse::Statement]) -> Program {
+    let mut env = Env::default();
+    let mut ops = vec![];
+    for st in parsed {
+        lower_statement(&mut env, &mut ops, st);
+    }
+    // the values never used
+    flush(&mut env, &mut ops);
+    // as well as the runtime stack, if never read
+    if !any_op(&ops, &|op| {
+        matches!(op, Op::Unspill(_) | Op::PrintSpilled(_))
+    }) {
+        retain_ops(&mut ops, &|op| {
+            !matches!(
+                op,
+                Op::Spill(_)
+                    | Op::DropSpilled
+                    | Op::Enter(_)
+                    | Op::Restore(_)
+                    | Op::Leave
+                    | Op::Unmark(_)
+            )
+        });
+    }
+    Program { ops }
+}
+
+/// Keeps the ops in `ops`, blocks included, that satisfy `keep`
+fn retain_ops(ops: &mut Vec<Op>, keep: &impl Fn(&Op) -> bool) {
+    ops.retain(keep);
+    for op in ops {
+        match op {
+            Op::If { branches, else_ } => {
+                for branch in branches {
+                    retain_ops(&mut branch.pops, keep);
+                    retain_ops(&mut branch.body, keep);
+                }
+                retain_ops(else_, keep);
+            }
+            Op::Repeat(_, body)
+            | Op::RepeatBy(_, body)
+            | Op::ForEach { body, .. }
+            | Op::Loop(body) => retain_ops(body, keep),
+            _ => {}
+        }
+    }
+}
+
+/// Whether any op in `ops`, blocks included, satisfies `pred`
+fn any_op(ops: &[Op], pred: &impl Fn(&Op) -> bool) -> bool {
+    ops.iter().any(|op| {
+        pred(op)
+            || match op {
+                Op::If { branches, else_ } => {
+                    branches
+                        .iter()
+                        .any(|b| any_op(&b.pops, pred) || any_op(&b.body, pred))
+                        || any_op(else_, pred)
+                }
+                Op::Repeat(_, body)
+                | Op::RepeatBy(_, body)
+                | Op::ForEach { body, .. }
+                | Op::Loop(body) => any_op(body, pred),
+                _ => false,
+            }
+    })
+}
+
+/// Whether `ops` may assign to `ident`, or to a variable of the same name
+pub fn assigns(ops: &[Op], ident: &parse::Identifier) -> bool {
+    any_op(
+        ops,
+        &|op| matches!(op, Op::Assign(parse::Lvalue::Simple(i), _) if i == ident),
+    )
+}
+
+/// Whether `ops` may assign to `ident` or change its elements, or those of a variable of the same name
+pub fn modifies(ops: &[Op], ident: &parse::Identifier) -> bool {
+    any_op(ops, &|op| match op {
+        Op::Assign(
+            parse::Lvalue::Simple(i)
+            | parse::Lvalue::Index(i, _)
+            | parse::Lvalue::IndexByIdent(i, _),
+            _,
+        )
+        | Op::Fill(Operand::Data(parse::Data::Identifier(i)), _) => i == ident,
+        _ => false,
+    })
+}
+
+fn write_data(d: &parse::Data) -> String {
+    match d {
+        parse::Data::StringLiteral(s) => format!("{:?}", s),
+        parse::Data::BoolValue(b) => b.to_string(),
+        parse::Data::Identifier(parse::Identifier(name)) => format!("「{}」", name),
+        parse::Data::IntNum(n) => n.to_string(),
+    }
+}
+
+fn write_operand(a: &Operand) -> String {
+    match a {
+        Operand::Data(d) => write_data(d),
+        Operand::Temp(t) => format!("t{}", t),
+        Operand::Undefined => String::from("undefined"),
+    }
+}
+
+fn write_temps(temps: &[Temp]) -> String {
+    temps
+        .iter()
+        .map(|t| format!(" t{}", t))
+        .collect::<Vec<_>>()
+        .join("")
+}
+
+fn write_value(v: &parse::Value<Operand>) -> String {
+    match v {
+        parse::Value::Simple(a) => write_operand(a),
+        parse::Value::Index(a, index) => format!("{}[{}]", write_operand(a), index),
+        parse::Value::IndexByIdent(a, parse::Identifier(index)) => {
+            format!("{}[「{}」]", write_operand(a), index)
+        }
+        parse::Value::Length(a) => format!("len {}", write_operand(a)),
+    }
+}
+
+fn write_expr(expr: &Expr) -> String {
+    match expr {
+        Expr::Init(type_, Some(d)) => format!("{} {}", type_name(*type_), write_data(d)),
+        Expr::Init(type_, None) => format!("{} default", type_name(*type_)),
+        Expr::Value(v) => write_value(v),
+        Expr::Binary(op, a, b) => format!("{} {} {}", write_operand(a), op, write_operand(b)),
+        Expr::Not(a) => format!("!{}", write_operand(a)),
+        Expr::Rest(a) => format!("rest {}", write_operand(a)),
+        Expr::Concat(a, elems) => format!(
+            "concat {}{}",
+            write_operand(a),
+            elems
+                .iter()
+                .map(|parse::Identifier(e)| format!(", 「{}」", e))
+                .collect::<Vec<_>>()
+                .join("")
+        ),
+    }
+}
+
+fn write_cond(cond: &Cond) -> String {
+    match cond {
+        Cond::Unary(a) => write_value(a),
+        Cond::Binary(a, op, b) => format!("{} {} {}", write_value(a), op, write_value(b)),
+        Cond::Not(a) => format!("!{}", write_operand(a)),
+    }
+}
+
+fn write_block(lines: &mut Vec<String>, indent: usize, head: String, body: &[Op]) {
+    lines.push(format!("{}{}", "    ".repeat(indent), head));
+    write_ops(lines, indent + 1, body);
+}
+
+fn write_ops(lines: &mut Vec<String>, indent: usize, ops: &[Op]) {
+    for op in ops {
+        let line = match op {
+            Op::Push(t, expr) => format!("push t{} = {}", t, write_expr(expr)),
+            Op::Pop { top, dropped } if dropped.is_empty() => format!("pop t{}", top),
+            Op::Pop { top, dropped } => format!("pop t{}, drop{}", top, write_temps(dropped)),
+            Op::Name(named) => format!(
+                "name {}",
+                named
+                    .iter()
+                    .map(|(parse::Identifier(name), t)| match t {
+                        Some(t) => format!("「{}」 = t{}", name, t),
+                        None => format!("「{}」 = undefined", name),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Op::Declare(parse::Identifier(name), expr) => {
+                format!("declare 「{}」 = {}", name, write_expr(expr))
+            }
+            Op::Print(temps) => format!("print{}", write_temps(temps)),
+            Op::Flush(temps) => format!("flush{}", write_temps(temps)),
+            Op::Spill(temps) => format!("spill{}", write_temps(temps)),
+            Op::Unspill(temps) => format!("unspill{}", write_temps(temps)),
+            Op::PrintSpilled(temps) => format!("print spilled{}", write_temps(temps)),
+            Op::DropSpilled => String::from("drop spilled"),
+            Op::Enter(None) => String::from("enter"),
+            Op::Enter(Some(block)) => format!("enter b{}", block),
+            Op::Restore(block) => format!("restore b{}", block),
+            Op::Leave => String::from("leave"),
+            Op::Unmark(n) => format!("unmark {}", n),
+            Op::Assign(lvalue, rvalue) => {
+                let lvalue = match lvalue {
+                    parse::Lvalue::Simple(ident) => {
+                        write_data(&parse::Data::Identifier(ident.clone()))
+                    }
+                    parse::Lvalue::Index(parse::Identifier(name), index) => {
+                        format!("「{}」[{}]", name, index)
+                    }
+                    parse::Lvalue::IndexByIdent(
+                        parse::Identifier(name),
+                        parse::Identifier(index),
+                    ) => {
+                        format!("「{}」[「{}」]", name, index)
+                    }
+                };
+                format!("assign {} = {}", lvalue, write_value(rvalue))
+            }
+            Op::Fill(list, elems) => format!(
+                "fill {} with {}",
+                write_operand(list),
+                elems.iter().map(write_data).collect::<Vec<_>>().join(", ")
+            ),
+            Op::If { branches, else_ } => {
+                for (i, branch) in branches.iter().enumerate() {
+                    write_ops(lines, indent, &branch.pops);
+                    let keyword = if i == 0 { "if" } else { "else if" };
+                    let head = format!("{} {}", keyword, write_cond(&branch.cond));
+                    write_block(lines, indent, head, &branch.body);
+                }
+                if !else_.is_empty() {
+                    write_block(lines, indent, String::from("else"), else_);
+                }
+                String::from("end")
+            }
+            Op::Repeat(n, body) => {
+                write_block(lines, indent, format!("repeat {}", n), body);
+                String::from("end")
+            }
+            Op::RepeatBy(count, body) => {
+                write_block(
+                    lines,
+                    indent,
+                    format!("repeat {}", write_operand(count)),
+                    body,
+                );
+                String::from("end")
+            }
+            Op::ForEach { list, elem, body } => {
+                let head = format!("for 「{}」 in 「{}」", elem.0, list.0);
+                write_block(lines, indent, head, body);
+                String::from("end")
+            }
+            Op::Loop(body) => {
+                write_block(lines, indent, String::from("loop"), body);
+                String::from("end")
+            }
+            Op::Break => String::from("break"),
+            Op::Continue => String::from("continue"),
+            Op::Origin(_) => continue,
+        };
+        lines.push(format!("{}{}", "    ".repeat(indent), line));
+    }
+}
+
+/// One op per line but `Origin`, the blocks indented and closed by `end`, such as
+/// ```text
+/// declare 「甲」 = 數 3
+/// repeat 「甲」
+///     push t1 = 言 "問天地好在。"
+///     print t1
+/// end
+/// ```
+pub fn listing(program: &Program) -> String {
+    let mut lines = vec![];
+    write_ops(&mut lines, 0, &program.ops);
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
@@ -0,0 +1,424 @@
+use crate::compile::Error;
+use crate::identbimap::is_temporary;
+use crate::ir;
+use crate::lex;
+use crate::parse;
+use crate::romanize::Romanizer;
+use big_s::S;
+use std::collections::HashMap;
+
+type Line = (usize, String);
+
+/// The reserved words of JavaScript, and the globals that the output must still see; such a name gets `_` appended
+const RESERVED: [&str; 48] = [
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "new",
+    "null",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+    "let",
+    "static",
+    "implements",
+    "interface",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "undefined",
+    "console",
+];
+
+struct Env<'a> {
+    romanizer: &'a dyn Romanizer,
+    rand_counter: usize,
+    indent_level: usize,
+    /// the JavaScript name of each identifier declared so far
+    names: HashMap<parse::Identifier, String>,
+    /// whether the runtime stack `_stack` is used
+    stack: bool,
+}
+
+impl Env<'_> {
+    /// Names `ident` on its first declaration; as with `var`, a later one is the same variable
+    fn declare(&mut self, ident: &parse::Identifier) -> String {
+        if let Some(name) = self.names.get(ident) {
+            return name.clone();
+        }
+        let mut name = self.romanizer.romanize(ident);
+        while RESERVED.contains(&name.as_str())
+            || is_temporary(&name)
+            || self.names.values().any(|other| other == &name)
+        {
+            name.push('_');
+        }
+        self.names.insert(ident.clone(), name.clone());
+        name
+    }
+
+    fn name(&self, ident: &parse::Identifier) -> Result<String, Error> {
+        self.names
+            .get(ident)
+            .cloned()
+            .ok_or_else(|| Error::UseBeforeDeclaration(ident.clone()))
+    }
+
+    fn line(&self, text: String) -> Line {
+        (self.indent_level, text)
+    }
+}
+
+/// Every value pushed is in a `_ansN`, numbered as the temporaries of the IR
+fn temporary(temp: ir::Temp) -> String {
+    format!("_ans{}", temp)
+}
+
+/// The runtime stack, with for each block being run whose values before it are on it, how many of these are left
+const STACK: &str = "\
+const _stack = {
+    values: [],
+    lows: [],
+    shrunk() {
+        this.lows = this.lows.map((low) => Math.min(low, this.values.length));
+    },
+    pop() {
+        const value = this.values.pop();
+        this.shrunk();
+        return value;
+    },
+    take() {
+        const values = this.values.splice(0);
+        this.shrunk();
+        return values;
+    },
+    clear() {
+        this.values.length = 0;
+        this.shrunk();
+    },
+    enter() {
+        this.lows.push(this.values.length);
+    },
+    restore(entry) {
+        this.values = [...entry];
+        this.lows[this.lows.length - 1] = entry.length;
+    },
+    leave() {
+        this.values.length = this.lows.pop();
+        this.shrunk();
+    },
+};
+";
+
+fn compile_literal(env: &Env, v: &parse::Data) -> Result<String, Error> {
+    Ok(match v {
+        parse::Data::BoolValue(b) => b.to_string(),
+        parse::Data::Identifier(ident) => env.name(ident)?,
+        parse::Data::IntNum(n) => n.to_string(),
+        parse::Data::StringLiteral(s) => {
+            serde_json::to_string(s).expect("a string can always be written as JSON")
+        }
+    })
+}
+
+fn compile_operand(env: &Env, a: &ir::Operand) -> Result<String, Error> {
+    match a {
+        ir::Operand::Data(data) => compile_literal(env, data),
+        ir::Operand::Temp(temp) => Ok(temporary(*temp)),
+        ir::Operand::Undefined => Ok(S("undefined")),
+    }
+}
+
+/// Indices are 1-based in wenyan
+fn compile_value(env: &Env, v: &parse::Value<ir::Operand>) -> Result<String, Error> {
+    Ok(match v {
+        parse::Value::Simple(a) => compile_operand(env, a)?,
+        parse::Value::Index(a, index) => format!("{}[{} - 1]", compile_operand(env, a)?, index),
+        parse::Value::IndexByIdent(a, index) => {
+            format!("{}[{} - 1]", compile_operand(env, a)?, env.name(index)?)
+        }
+        parse::Value::Length(a) => format!("{}.length", compile_operand(env, a)?),
+    })
+}
+
+/// The value of the `i`th variable of `吾有`, the default of the type if not given
+fn compile_optional_literal(
+    env: &Env,
+    lit: Option<&parse::Data>,
+    default_type: lex::Type,
+) -> Result<String, Error> {
+    Ok(match lit {
+        None => S(match default_type {
+            lex::Type::Shu4 => "0",
+            lex::Type::Lie4 => "[]",
+            lex::Type::Yan2 => "\"\"",
+            lex::Type::Yao2 => "false",
+        }),
+        Some(v) => compile_literal(env, v)?,
+    })
+}
+
+/// The operands are names or literals, which need no parentheses
+fn compile_expr(env: &Env, expr: &ir::Expr) -> Result<String, Error> {
+    Ok(match expr {
+        ir::Expr::Init(type_, data) => compile_optional_literal(env, data.as_ref(), *type_)?,
+        ir::Expr::Value(v) => compile_value(env, v)?,
+        ir::Expr::Binary(op, a, b) => format!(
+            "{} {} {}",
+            compile_operand(env, a)?,
+            op,
+            compile_operand(env, b)?
+        ),
+        ir::Expr::Not(a) => format!("!{}", compile_operand(env, a)?),
+        ir::Expr::Rest(a) => format!("{}.slice(1)", compile_operand(env, a)?),
+        ir::Expr::Concat(a, elems) => {
+            let elems = elems
+                .iter()
+                .map(|e| env.name(e))
+                .collect::<Result<Vec<_>, Error>>()?;
+            format!("{}.concat({})", compile_operand(env, a)?, elems.join(", "))
+        }
+    })
+}
+
+fn compile_cond(env: &Env, cond: &ir::Cond) -> Result<String, Error> {
+    Ok(match cond {
+        ir::Cond::Unary(a) => compile_value(env, a)?,
+        ir::Cond::Binary(a, op, b) => {
+            format!(
+                "{} {} {}",
+                compile_value(env, a)?,
+                op,
+                compile_value(env, b)?
+            )
+        }
+        ir::Cond::Not(a) => format!("!{}", compile_operand(env, a)?),
+    })
+}
+
+fn compile_block(env: &mut Env, ops: &[ir::Op]) -> Result<Vec<Line>, Error> {
+    env.indent_level += 1;
+    let mut r = vec![];
+    for op in ops {
+        r.append(&mut compile_op(env, op)?);
+    }
+    env.indent_level -= 1;
+    Ok(r)
+}
+
+/// `head`, then the block, then `}`
+fn compile_loop(env: &mut Env, head: String, ops: &[ir::Op]) -> Result<Vec<Line>, Error> {
+    let mut r = vec![env.line(head)];
+    r.append(&mut compile_block(env, ops)?);
+    r.push(env.line(S("}")));
+    Ok(r)
+}
+
+fn compile_counted_loop(env: &mut Env, bound: &str, ops: &[ir::Op]) -> Result<Vec<Line>, Error> {
+    env.rand_counter += 1;
+    let rand = format!("_rand{}", env.rand_counter);
+    let head = format!("for (let {0} = 0; {0} < {1}; {0}++) {{", rand, bound);
+    compile_loop(env, head, ops)
+}
+
+fn compile_if(
+    env: &mut Env,
+    branches: &[ir::Branch],
+    else_: &[ir::Op],
+) -> Result<Vec<Line>, Error> {
+    let mut r = vec![];
+    // the `if`s in an `else`, for the conditions after a value is taken off a runtime stack
+    let mut nested = 0;
+    for (i, branch) in branches.iter().enumerate() {
+        let mut pops = vec![];
+        for op in &branch.pops {
+            pops.append(&mut compile_op(env, op)?);
+        }
+        let cond = compile_cond(env, &branch.cond)?;
+        let keyword = if i == 0 {
+            r.append(&mut pops);
+            "if"
+        } else if pops.is_empty() {
+            "} else if"
+        } else {
+            r.push(env.line(S("} else {")));
+            env.indent_level += 1;
+            nested += 1;
+            r.extend(pops.into_iter().map(|(indent, line)| (indent + 1, line)));
+            "if"
+        };
+        r.push(env.line(format!("{} ({}) {{", keyword, cond)));
+        r.append(&mut compile_block(env, &branch.body)?);
+    }
+    if !else_.is_empty() {
+        r.push(env.line(S("} else {")));
+        r.append(&mut compile_block(env, else_)?);
+    }
+    r.push(env.line(S("}")));
+    for _ in 0..nested {
+        env.indent_level -= 1;
+        r.push(env.line(S("}")));
+    }
+    Ok(r)
+}
+
+/// The values stay in their `_ansN`, so that taking them off the stack writes nothing
+fn compile_op(env: &mut Env, op: &ir::Op) -> Result<Vec<Line>, Error> {
+    Ok(match op {
+        ir::Op::Push(temp, expr) => {
+            // as the reference implementation, `var` for `吾有`
+            let keyword = match expr {
+                ir::Expr::Init(..) => "var",
+                _ => "const",
+            };
+            let expr = compile_expr(env, expr)?;
+            vec![env.line(format!("{} {} = {};", keyword, temporary(*temp), expr))]
+        }
+        ir::Op::Pop { .. } | ir::Op::Flush(_) | ir::Op::Origin(_) => vec![],
+        ir::Op::Name(named) => named
+            .iter()
+            .map(|(ident, temp)| {
+                let value = temp.map_or(S("undefined"), temporary);
+                let name = env.declare(ident);
+                env.line(format!("var {} = {};", name, value))
+            })
+            .collect(),
+        ir::Op::Declare(ident, value) => {
+            let value = compile_expr(env, value)?;
+            let name = env.declare(ident);
+            vec![env.line(format!("var {} = {};", name, value))]
+        }
+        ir::Op::Print(temps) => {
+            let values = temps.iter().copied().map(temporary).collect::<Vec<_>>();
+            vec![env.line(format!("console.log({});", values.join(", ")))]
+        }
+        ir::Op::Spill(temps) => {
+            env.stack = true;
+            let values = temps.iter().copied().map(temporary).collect::<Vec<_>>();
+            vec![env.line(format!("_stack.values.push({});", values.join(", ")))]
+        }
+        // the top goes to the last temporary
+        ir::Op::Unspill(temps) => temps
+            .iter()
+            .rev()
+            .map(|t| env.line(format!("const {} = _stack.pop();", temporary(*t))))
+            .collect(),
+        ir::Op::PrintSpilled(temps) => {
+            let values = std::iter::once(S("..._stack.take()"))
+                .chain(temps.iter().copied().map(temporary))
+                .collect::<Vec<_>>();
+            vec![env.line(format!("console.log({});", values.join(", ")))]
+        }
+        ir::Op::DropSpilled => vec![env.line(S("_stack.clear();"))],
+        ir::Op::Enter(block) => {
+            let mut r = vec![env.line(S("_stack.enter();"))];
+            if let Some(block) = block {
+                r.push(env.line(format!("const _entry{} = [..._stack.values];", block)));
+            }
+            r
+        }
+        ir::Op::Restore(block) => vec![env.line(format!("_stack.restore(_entry{});", block))],
+        ir::Op::Leave => vec![env.line(S("_stack.leave();"))],
+        // the blocks left by the jump
+        ir::Op::Unmark(n) => vec![env.line(format!("_stack.lows.length -= {};", n))],
+        ir::Op::Assign(lvalue, rvalue) => {
+            let rvalue = compile_value(env, rvalue)?;
+            let lvalue = match lvalue {
+                parse::Lvalue::Simple(ident) => env.name(ident)?,
+                parse::Lvalue::Index(ident, index) => {
+                    format!("{}[{} - 1]", env.name(ident)?, index)
+                }
+                parse::Lvalue::IndexByIdent(ident, index) => {
+                    format!("{}[{} - 1]", env.name(ident)?, env.name(index)?)
+                }
+            };
+            vec![env.line(format!("{} = {};", lvalue, rvalue))]
+        }
+        ir::Op::Fill(list, elems) => {
+            let list = compile_operand(env, list)?;
+            let elems = elems
+                .iter()
+                .map(|e| compile_literal(env, e))
+                .collect::<Result<Vec<_>, Error>>()?;
+            vec![env.line(format!("{}.push({});", list, elems.join(", ")))]
+        }
+        ir::Op::If { branches, else_ } => compile_if(env, branches, else_)?,
+        ir::Op::Repeat(num, body) => compile_counted_loop(env, &num.to_string(), body)?,
+        ir::Op::RepeatBy(count, body) => {
+            let bound = compile_operand(env, count)?;
+            compile_counted_loop(env, &bound, body)?
+        }
+        ir::Op::ForEach { list, elem, body } => {
+            let list = env.name(list)?;
+            let elem = env.declare(elem);
+            compile_loop(env, format!("for (var {} of {}) {{", elem, list), body)?
+        }
+        ir::Op::Loop(body) => compile_loop(env, S("while (true) {"), body)?,
+        ir::Op::Break => vec![env.line(S("break;"))],
+        ir::Op::Continue => vec![env.line(S("continue;"))],
+    })
+}
+
+/// JavaScript in the manner of the reference implementation: every value not yet named is in a `_ansN`,
+/// printed by `console.log` and named by `var`, such as
+/// ```
+/// var jia3 = 3;
+/// for (let _rand1 = 0; _rand1 < jia3; _rand1++) {
+///     const _ans1 = "問天地好在。";
+///     console.log(_ans1);
+/// }
+/// ```
+/// The names are given by `romanizer`; `romanize::Scheme::Hanzi` keeps them as the reference implementation does.
+pub fn compile(program: &ir::Program, romanizer: &dyn Romanizer) -> Result<String, Error> {
+    let mut env = Env {
+        romanizer,
+        rand_counter: 0,
+        indent_level: 0,
+        names: HashMap::new(),
+        stack: false,
+    };
+    let mut lines = vec![];
+    for op in &program.ops {
+        lines.append(&mut compile_op(&mut env, op)?);
+    }
+    let lines = lines
+        .iter()
+        .map(|(indent, src)| format!("{}{}\n", "    ".repeat(*indent), src))
+        .collect::<String>();
+    Ok(if env.stack {
+        format!("{}{}", STACK, lines)
+    } else {
+        lines
+    })
+}
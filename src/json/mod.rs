@@ -0,0 +1,371 @@
+use crate::cst::Cst;
+use crate::lex::{self, Lex};
+use crate::parse::{self, Data, IfCond, Lvalue, MathKind, OrQi2, Statement, UnaryIfExpr};
+use crate::unparse::type_name;
+use serde_json::{json, Value};
+
+/// Increased whenever the layout of `tokens` or `ast` changes in a way that can break a reader
+pub const VERSION: u64 = 1;
+
+/// The tokens with their byte spans in the source and the trivia before each, e.g.
+/// `{"kind": "Identifier", "text": "「甲」", "span": [12, 21], "value": "甲", "leading_trivia": [...]}`.
+/// `value` is only given where `text` needs reading: the content of a literal or identifier,
+/// the number of an `IntNum` (`null` if not supported) and the operator of an `ArithBinaryOp`, `LogicBinaryOp` or `IfLogicOp`
+pub fn tokens(src: &str, tokens: &lex::Tokens) -> Value {
+    json!({
+        "tokens": tokens.tokens.iter().map(|token| self::token(src, token)).collect::<Vec<_>>(),
+        "trailing_trivia": trivia(&tokens.trailing_trivia),
+    })
+}
+
+fn token(src: &str, token: &lex::Token) -> Value {
+    let mut r = json!({
+        "kind": kind(&token.lex),
+        "text": &src[token.span.clone()],
+        "span": span(&token.span),
+        "leading_trivia": trivia(&token.leading_trivia),
+    });
+    let value = match &token.lex {
+        Lex::StringLiteral(s) | Lex::Identifier(s) => json!(s),
+        Lex::BoolValue(b) => json!(b.interpret()),
+        Lex::IntNum(n) => json!(parse::interpret_intnum(n).ok()),
+        Lex::ArithBinaryOp(op) => json!(op.to_str()),
+        Lex::LogicBinaryOp(op) => json!(op.to_str()),
+        Lex::IfLogicOp(op) => json!(op.to_str()),
+        _ => return r,
+    };
+    r["value"] = value;
+    r
+}
+
+fn trivia(trivia: &[lex::Trivia]) -> Value {
+    trivia
+        .iter()
+        .map(|trivia| {
+            let kind = match trivia.kind {
+                lex::TriviaKind::Whitespace => "whitespace",
+                lex::TriviaKind::Newline => "newline",
+                lex::TriviaKind::Punctuation => "punctuation",
+                lex::TriviaKind::Comment => "comment",
+            };
+            json!({ "kind": kind, "span": span(&trivia.span) })
+        })
+        .collect()
+}
+
+fn span(span: &lex::Span) -> Value {
+    json!([span.start, span.end])
+}
+
+/// The name of the variant, which does not change with the payload
+fn kind(lex: &Lex) -> &'static str {
+    match lex {
+        Lex::Wu2You3 => "Wu2You3",
+        Lex::Jin1 => "Jin1",
+        Lex::Jin1You3 => "Jin1You3",
+        Lex::Jin1Bu4Fu4Cun2Yi3 => "Jin1Bu4Fu4Cun2Yi3",
+        Lex::Yue1 => "Yue1",
+        Lex::Shu1Zhi1 => "Shu1Zhi1",
+        Lex::Wei2Shi4 => "Wei2Shi4",
+        Lex::Bian4Loop => "Bian4Loop",
+        Lex::Heng2Wei2Shi4 => "Heng2Wei2Shi4",
+        Lex::Yun2Yun2OrYe3(_) => "Yun2Yun2OrYe3",
+        Lex::You3 => "You3",
+        Lex::Ming2Zhi1 => "Ming2Zhi1",
+        Lex::Xi1Zhi1 => "Xi1Zhi1",
+        Lex::Zhi1 => "Zhi1",
+        Lex::Zhi1Shu4Ye3 => "Zhi1Shu4Ye3",
+        Lex::Zhi1Shu1 => "Zhi1Shu1",
+        Lex::Zhi1Yi4 => "Zhi1Yi4",
+        Lex::Zhi1Wu4Ye3 => "Zhi1Wu4Ye3",
+        Lex::Zhe3 => "Zhe3",
+        Lex::Wu2Chang2Guan1 => "Wu2Chang2Guan1",
+        Lex::Qi2 => "Qi2",
+        Lex::Qi2Yu2 => "Qi2Yu2",
+        Lex::Qi2Wu4Ru2Shi4 => "Qi2Wu4Ru2Shi4",
+        Lex::Shi4Yi3 => "Shi4Yi3",
+        Lex::Shi4Shu4Yue1 => "Shi4Shu4Yue1",
+        Lex::Shi4Wei4 => "Shi4Wei4",
+        Lex::Yi3Shi1 => "Yi3Shi1",
+        Lex::Yi1Flush => "Yi1Flush",
+        Lex::Chu2 => "Chu2",
+        Lex::Suo3Yu2Ji3He2 => "Suo3Yu2Ji3He2",
+        Lex::Fu2 => "Fu2",
+        Lex::Ruo4 => "Ruo4",
+        Lex::Ruo4Fei1 => "Ruo4Fei1",
+        Lex::Huo4Ruo4 => "Huo4Ruo4",
+        Lex::Bian4Change => "Bian4Change",
+        Lex::Ruo4Qi2Ran2Zhe3 => "Ruo4Qi2Ran2Zhe3",
+        Lex::Ruo4Qi2Bu4Ran2Zhe3 => "Ruo4Qi2Bu4Ran2Zhe3",
+        Lex::Chong1 => "Chong1",
+        Lex::Fan2 => "Fan2",
+        Lex::Zhong1Zhi1 => "Zhong1Zhi1",
+        Lex::Nai3Zhi3 => "Nai3Zhi3",
+        Lex::Nai3Xing1Shi4Shu4Yue1 => "Nai3Xing1Shi4Shu4Yue1",
+        Lex::Nai3De2 => "Nai3De2",
+        Lex::Nai3Gui1Kong1Wu2 => "Nai3Gui1Kong1Wu2",
+        Lex::Nai3De2Yi3 => "Nai3De2Yi3",
+        Lex::Nai3Zhi3Shi4Bian4 => "Nai3Zhi3Shi4Bian4",
+        Lex::Xian2 => "Xian2",
+        Lex::Chang2 => "Chang2",
+        Lex::ArithBinaryOp(_) => "ArithBinaryOp",
+        Lex::LogicBinaryOp(_) => "LogicBinaryOp",
+        Lex::IfLogicOp(_) => "IfLogicOp",
+        Lex::Type(_) => "Type",
+        Lex::StringLiteral(_) => "StringLiteral",
+        Lex::BoolValue(_) => "BoolValue",
+        Lex::Identifier(_) => "Identifier",
+        Lex::IntNum(_) => "IntNum",
+        Lex::FloatNumKeywords(_) => "FloatNumKeywords",
+        Lex::Preposition(_) => "Preposition",
+    }
+}
+
+/// The statements of `stmts`, each an object whose `kind` is the variant of `parse::Statement`
+/// and whose `span` is that of its node in `cst`, e.g.
+/// `{"kind": "InitDefine", "span": [0, 33], "type": "數", "value": {"kind": "Int", "value": 3}, "name": "甲"}`.
+/// Blocks are arrays of statements under `body`, or `branches` and `else` for `If`
+pub fn ast(cst: &Cst, stmts: &[Statement]) -> Value {
+    let mut out = Ast {
+        spans: cst
+            .preorder()
+            .into_iter()
+            .map(|node| cst.span(node))
+            .collect(),
+        next_statement: 0,
+    };
+    out.block(stmts)
+}
+
+struct Ast {
+    /// the span of each statement in the order of `cst::preorder`
+    spans: Vec<lex::Span>,
+    /// the index in preorder of the next statement to write
+    next_statement: usize,
+}
+
+impl Ast {
+    fn block(&mut self, stmts: &[Statement]) -> Value {
+        stmts.iter().map(|st| self.statement(st)).collect()
+    }
+
+    /// The blocks are written after the statement itself, so as to follow the preorder
+    fn statement(&mut self, st: &Statement) -> Value {
+        let span = self
+            .spans
+            .get(self.next_statement)
+            .map_or(Value::Null, span);
+        self.next_statement += 1;
+        let mut r = match st {
+            Statement::Declare(decl) => declare(decl),
+            Statement::Define { decl, idents } => {
+                let mut r = declare(decl);
+                r["names"] = names(idents);
+                r
+            }
+            Statement::InitDefine { type_, data, name } => json!({
+                "type": type_name(*type_),
+                "value": self::data(data),
+                "name": name.0,
+            }),
+            Statement::NameMulti { idents } => json!({ "names": names(idents) }),
+            Statement::Print | Statement::Flush | Statement::Break | Statement::Continue => {
+                json!({})
+            }
+            Statement::ForEnum { num, statements } => json!({
+                "count": int(*num),
+                "body": self.block(statements),
+            }),
+            Statement::ForEnumIdent { ident, statements } => json!({
+                "count": ident_or_qi2(ident),
+                "body": self.block(statements),
+            }),
+            Statement::ForArr { list, elem, stmts } => json!({
+                "list": list.0,
+                "elem": elem.0,
+                "body": self.block(stmts),
+            }),
+            Statement::Loop { statements } => json!({ "body": self.block(statements) }),
+            Statement::If {
+                ifcase,
+                elseifcases,
+                elsecase,
+            } => {
+                let branches = std::iter::once(ifcase)
+                    .chain(elseifcases)
+                    .map(|(cond, stmts)| {
+                        json!({
+                            "cond": if_cond(cond),
+                            "body": self.block(stmts),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                json!({
+                    "branches": branches,
+                    "else": self.block(elsecase),
+                })
+            }
+            Statement::Math { math } => self::math(math),
+            Statement::Assignment { lvalue, rvalue } => json!({
+                "target": self::lvalue(lvalue),
+                "value": value(rvalue, data_or_qi2),
+            }),
+            Statement::Reference { rvalue } => json!({ "value": value(rvalue, data) }),
+            Statement::ReferenceWhatIsLeft { data } => json!({ "value": self::data(data) }),
+            Statement::ArrayFill {
+                what_to_fill,
+                elems,
+            } => json!({
+                "target": ident_or_qi2(what_to_fill),
+                "values": elems.iter().map(data).collect::<Vec<_>>(),
+            }),
+            Statement::ArrayCat { append_to, elems } => json!({
+                "target": ident_or_qi2(append_to),
+                "values": elems.iter().map(ident).collect::<Vec<_>>(),
+            }),
+        };
+        r["kind"] = json!(statement_kind(st));
+        r["span"] = span;
+        r
+    }
+}
+
+fn statement_kind(st: &Statement) -> &'static str {
+    match st {
+        Statement::Declare(_) => "Declare",
+        Statement::Print => "Print",
+        Statement::ForEnum { .. } => "ForEnum",
+        Statement::ForEnumIdent { .. } => "ForEnumIdent",
+        Statement::ForArr { .. } => "ForArr",
+        Statement::Loop { .. } => "Loop",
+        Statement::InitDefine { .. } => "InitDefine",
+        Statement::Define { .. } => "Define",
+        Statement::If { .. } => "If",
+        Statement::Math { .. } => "Math",
+        Statement::Assignment { .. } => "Assignment",
+        Statement::Reference { .. } => "Reference",
+        Statement::ReferenceWhatIsLeft { .. } => "ReferenceWhatIsLeft",
+        Statement::ArrayFill { .. } => "ArrayFill",
+        Statement::ArrayCat { .. } => "ArrayCat",
+        Statement::Flush => "Flush",
+        Statement::Break => "Break",
+        Statement::Continue => "Continue",
+        Statement::NameMulti { .. } => "NameMulti",
+    }
+}
+
+fn declare(decl: &parse::DeclareStatement) -> Value {
+    json!({
+        "count": decl.how_many_variables,
+        "type": type_name(decl.type_),
+        "values": decl.data_arr.iter().map(data).collect::<Vec<_>>(),
+    })
+}
+
+fn names(idents: &[parse::Identifier]) -> Value {
+    idents.iter().map(|i| json!(i.0)).collect()
+}
+
+/// `op` is that of `to_str`, or `變` for `ArithUnaryMath`
+fn math(math: &MathKind) -> Value {
+    match math {
+        MathKind::ArithBinaryMath(op, a, prep, b) => binary_math(op.to_str(), a, *prep, b),
+        MathKind::ModMath(op, a, prep, b) => binary_math(op.to_str(), a, *prep, b),
+        MathKind::ArithUnaryMath(a) => json!({ "op": "變", "operand": data_or_qi2(a) }),
+        MathKind::BooleanAlgebra(a, b, op) => json!({
+            "op": op.to_str(),
+            "left": ident(a),
+            "right": ident(b),
+        }),
+    }
+}
+
+fn binary_math(op: &str, a: &OrQi2<Data>, prep: lex::Preposition, b: &OrQi2<Data>) -> Value {
+    json!({
+        "op": op,
+        "left": data_or_qi2(a),
+        "preposition": match prep {
+            lex::Preposition::Yi3 => "以",
+            lex::Preposition::Yu2 => "於",
+        },
+        "right": data_or_qi2(b),
+    })
+}
+
+fn if_cond(cond: &IfCond) -> Value {
+    match cond {
+        IfCond::Unary(a) => unary_if_expr(a),
+        IfCond::Binary(a, op, b) => json!({
+            "kind": "Compare",
+            "op": op.to_str(),
+            "left": unary_if_expr(a),
+            "right": unary_if_expr(b),
+        }),
+        IfCond::NotQi2 => json!({ "kind": "Not", "operand": qi2() }),
+    }
+}
+
+fn unary_if_expr(a: &UnaryIfExpr) -> Value {
+    match a {
+        UnaryIfExpr::Simple(a) => data_or_qi2(a),
+        UnaryIfExpr::Complex(v) => value(v, data),
+    }
+}
+
+fn lvalue(lvalue: &Lvalue) -> Value {
+    match lvalue {
+        Lvalue::Simple(i) => ident(i),
+        Lvalue::Index(i, index) => json!({ "kind": "Index", "of": ident(i), "index": int(*index) }),
+        Lvalue::IndexByIdent(i, index) => {
+            json!({ "kind": "Index", "of": ident(i), "index": ident(index) })
+        }
+    }
+}
+
+fn value<T>(v: &parse::Value<T>, write: fn(&T) -> Value) -> Value {
+    match v {
+        parse::Value::Simple(a) => write(a),
+        parse::Value::Index(a, index) => {
+            json!({ "kind": "Index", "of": write(a), "index": int(*index) })
+        }
+        parse::Value::IndexByIdent(a, index) => {
+            json!({ "kind": "Index", "of": write(a), "index": ident(index) })
+        }
+        parse::Value::Length(a) => json!({ "kind": "Length", "of": write(a) }),
+    }
+}
+
+fn qi2() -> Value {
+    json!({ "kind": "Qi2" })
+}
+
+fn int(n: i64) -> Value {
+    json!({ "kind": "Int", "value": n })
+}
+
+fn ident(i: &parse::Identifier) -> Value {
+    json!({ "kind": "Identifier", "name": i.0 })
+}
+
+fn ident_or_qi2(a: &OrQi2<parse::Identifier>) -> Value {
+    match a {
+        OrQi2::NotQi2(i) => ident(i),
+        OrQi2::Qi2 => qi2(),
+    }
+}
+
+fn data_or_qi2(a: &OrQi2<Data>) -> Value {
+    match a {
+        OrQi2::NotQi2(d) => data(d),
+        OrQi2::Qi2 => qi2(),
+    }
+}
+
+fn data(d: &Data) -> Value {
+    match d {
+        Data::StringLiteral(s) => json!({ "kind": "String", "value": s }),
+        Data::BoolValue(b) => json!({ "kind": "Bool", "value": b }),
+        Data::Identifier(i) => ident(i),
+        Data::IntNum(n) => int(*n),
+    }
+}
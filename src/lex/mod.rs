@@ -432,11 +432,75 @@ pub enum Error {
     NonterminatedIdentifier,
     EmptyIdentifier,
     NonterminatedStringLiteral,
+    /// a character outside of identifiers and string literals that is not part of any keyword
+    UnrecognizedCharacter(char),
 }
 
-fn lex_ident_or_str_after_seeing_quote(
-    iter: &mut peek_nth::PeekableNth<std::str::Chars>,
-) -> Result<Lex, Error> {
+/// Byte offsets into the source
+pub type Span = std::ops::Range<usize>;
+
+/// A token, where it is in the source, and the trivia between it and the previous token
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Token {
+    pub leading_trivia: Vec<Trivia>,
+    pub lex: Lex,
+    pub span: Span,
+}
+
+/// What `lex` skips
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub enum TriviaKind {
+    /// spaces, tabs and full-width spaces `　`
+    Whitespace,
+    /// one line break, `\n` or `\r\n`
+    Newline,
+    /// `。` or `、`
+    Punctuation,
+    /// `批曰`, `注曰` or `疏曰` and the string literal after it
+    Comment,
+}
+
+/// The source as tokens, with all that is between them; the spans of the tokens and the trivia cover it without a gap
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Tokens {
+    pub tokens: Vec<Token>,
+    /// after the last token
+    pub trailing_trivia: Vec<Trivia>,
+}
+
+/// The characters of the source, keeping track of the byte offset of the next one
+struct Chars<'a> {
+    iter: peek_nth::PeekableNth<std::str::Chars<'a>>,
+    offset: usize,
+}
+
+impl Iterator for Chars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.iter.next()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+}
+
+impl Chars<'_> {
+    fn peek(&mut self) -> Option<&char> {
+        self.iter.peek()
+    }
+
+    fn peek_nth(&mut self, n: usize) -> Option<&char> {
+        self.iter.peek_nth(n)
+    }
+}
+
+fn lex_ident_or_str_after_seeing_quote(iter: &mut Chars<'_>) -> Result<Lex, Error> {
     let peek = iter.peek();
     match peek {
         None => Err(Error::NonterminatedIdentifier),
@@ -478,231 +542,294 @@ fn lex_ident_or_str_after_seeing_quote(
 }
 
 pub fn lex(input: &str) -> Result<Vec<Lex>, Error> {
-    let mut ans = vec![];
-    let mut iter = input.chars().peekable_nth();
+    match lex_with_trivia(input) {
+        Ok(tokens) => Ok(tokens.tokens.into_iter().map(|token| token.lex).collect()),
+        Err((e, _)) => Err(e),
+    }
+}
+
+fn is_whitespace(c: char) -> bool {
+    c == ' ' || c == '\t' || c == '　'
+}
+
+/// Like `lex`, but keeps what `lex` skips, so that the source can be reproduced;
+/// an error comes with the span of what was being lexed when it was found
+pub fn lex_with_trivia(input: &str) -> Result<Tokens, (Error, Span)> {
+    let mut tokens = vec![];
+    let mut trivia = vec![];
+    let mut iter = Chars {
+        iter: input.chars().peekable_nth(),
+        offset: 0,
+    };
     loop {
+        let start = iter.offset;
         let c = match iter.next() {
             None => break,
             Some(d) => d,
         };
-
-        if c == ' ' || c == '\t' || c == '\n' || c == '\r' || c == '。' || c == '、' || c == '　'
-        {
+        let kind = match c {
+            c if is_whitespace(c) => {
+                while iter.peek().map_or(false, |&c| is_whitespace(c)) {
+                    iter.next();
+                }
+                Some(TriviaKind::Whitespace)
+            }
+            '\r' => {
+                if iter.peek() == Some(&'\n') {
+                    iter.next();
+                }
+                Some(TriviaKind::Newline)
+            }
+            '\n' => Some(TriviaKind::Newline),
+            '。' | '、' => Some(TriviaKind::Punctuation),
+            '批' | '注' | '疏' => {
+                match lex_comment_after(c, &mut iter) {
+                    Ok(()) => {}
+                    Err(e) => return Err((e, start..iter.offset)),
+                }
+                Some(TriviaKind::Comment)
+            }
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            trivia.push(Trivia {
+                kind,
+                span: start..iter.offset,
+            });
             continue;
         }
+        match lex_token(c, &mut iter) {
+            Ok(lex) => tokens.push(Token {
+                leading_trivia: std::mem::take(&mut trivia),
+                lex,
+                span: start..iter.offset,
+            }),
+            Err(e) => return Err((e, start..iter.offset)),
+        }
+    }
+    Ok(Tokens {
+        tokens,
+        trailing_trivia: trivia,
+    })
+}
 
-        ans.push(match c {
-            '長' => Lex::Chang2,
-            '銜' => Lex::Xian2,
-            '凡' => Lex::Fan2,
-            '充' => Lex::Chong1,
-            '變' => Lex::Bian4Change,
-            '也' => Lex::Yun2Yun2OrYe3(YYoY::Ye3),
-            '夫' => Lex::Fu2,
-            '除' => Lex::Chu2,
-            '噫' => Lex::Yi1Flush,
-            '於' => Lex::Preposition(Preposition::Yu2),
-            '加' => Lex::ArithBinaryOp(ArithBinaryOp::Jia1),
-            '減' => Lex::ArithBinaryOp(ArithBinaryOp::Jian3),
-            '乘' => Lex::ArithBinaryOp(ArithBinaryOp::Cheng2),
-            '有' => Lex::You3,
-            '數' => Lex::Type(Type::Shu4),
-            '列' => Lex::Type(Type::Lie4),
-            '言' => Lex::Type(Type::Yan2),
-            '爻' => Lex::Type(Type::Yao2),
-            '曰' => Lex::Yue1,
-            '遍' => Lex::Bian4Loop,
-            '陰' => Lex::BoolValue(BoolValue::Yin1),
-            '陽' => Lex::BoolValue(BoolValue::Yang2),
-            '者' => Lex::Zhe3,
-            '「' => lex_ident_or_str_after_seeing_quote(&mut iter)?,
-            '吾' => match iter.next().ok_or(Error::UnexpectedEOFAfter('吾'))? {
-                '有' => Lex::Wu2You3,
-                '嘗' => get_keyword(&mut iter, &['嘗', '觀'], Lex::Wu2Chang2Guan1)?,
-                a => return Err(Error::UnexpectedCharAfter('吾', a)),
-            },
-            '中' => match iter.next().ok_or(Error::UnexpectedEOFAfter('中'))? {
-                '有' => get_keyword(
-                    &mut iter,
-                    &['有', '陽', '乎'],
-                    Lex::LogicBinaryOp(LogicBinaryOp::Zhong1You3Yang2Hu1),
-                )?,
-                '無' => get_keyword(
-                    &mut iter,
-                    &['無', '陰', '乎'],
-                    Lex::LogicBinaryOp(LogicBinaryOp::Zhong1Wu2Yin1Hu1),
-                )?,
-                '之' => Lex::Zhong1Zhi1,
-                a => return Err(Error::UnexpectedCharAfter('中', a)),
-            },
-            '為' => get_keyword(&mut iter, &['為', '是'], Lex::Wei2Shi4)?,
-            '昔' => get_keyword(&mut iter, &['昔', '之'], Lex::Xi1Zhi1)?,
-            '云' => get_keyword(&mut iter, &['云', '云'], Lex::Yun2Yun2OrYe3(YYoY::Yun2Yun2))?,
-            '恆' => get_keyword(&mut iter, &['恆', '為', '是'], Lex::Heng2Wei2Shi4)?,
-            '所' => get_keyword(&mut iter, &['所', '餘', '幾', '何'], Lex::Suo3Yu2Ji3He2)?,
-            '書' => get_keyword(&mut iter, &['書', '之'], Lex::Shu1Zhi1)?,
-            '名' => get_keyword(&mut iter, &['名', '之'], Lex::Ming2Zhi1)?,
-            '或' => get_keyword(&mut iter, &['或', '若'], Lex::Huo4Ruo4)?,
+/// `批曰「「...」」` and the like; `c` is the first character
+fn lex_comment_after(c: char, iter: &mut Chars<'_>) -> Result<(), Error> {
+    get_keyword(iter, &[c, '曰'], Lex::Yue1)?;
+    match iter.next().ok_or(Error::UnexpectedEOFAfter('曰'))? {
+        '「' => lex_ident_or_str_after_seeing_quote(iter).map(|_| ()),
+        a => Err(Error::UnexpectedCharAfter('曰', a)),
+    }
+}
+
+/// The token beginning with `c`
+fn lex_token(c: char, mut iter: &mut Chars<'_>) -> Result<Lex, Error> {
+    Ok(match c {
+        '長' => Lex::Chang2,
+        '銜' => Lex::Xian2,
+        '凡' => Lex::Fan2,
+        '充' => Lex::Chong1,
+        '變' => Lex::Bian4Change,
+        '也' => Lex::Yun2Yun2OrYe3(YYoY::Ye3),
+        '夫' => Lex::Fu2,
+        '除' => Lex::Chu2,
+        '噫' => Lex::Yi1Flush,
+        '於' => Lex::Preposition(Preposition::Yu2),
+        '加' => Lex::ArithBinaryOp(ArithBinaryOp::Jia1),
+        '減' => Lex::ArithBinaryOp(ArithBinaryOp::Jian3),
+        '乘' => Lex::ArithBinaryOp(ArithBinaryOp::Cheng2),
+        '有' => Lex::You3,
+        '數' => Lex::Type(Type::Shu4),
+        '列' => Lex::Type(Type::Lie4),
+        '言' => Lex::Type(Type::Yan2),
+        '爻' => Lex::Type(Type::Yao2),
+        '曰' => Lex::Yue1,
+        '遍' => Lex::Bian4Loop,
+        '陰' => Lex::BoolValue(BoolValue::Yin1),
+        '陽' => Lex::BoolValue(BoolValue::Yang2),
+        '者' => Lex::Zhe3,
+        '「' => lex_ident_or_str_after_seeing_quote(&mut iter)?,
+        '吾' => match iter.next().ok_or(Error::UnexpectedEOFAfter('吾'))? {
+            '有' => Lex::Wu2You3,
+            '嘗' => get_keyword(&mut iter, &['嘗', '觀'], Lex::Wu2Chang2Guan1)?,
+            a => return Err(Error::UnexpectedCharAfter('吾', a)),
+        },
+        '中' => match iter.next().ok_or(Error::UnexpectedEOFAfter('中'))? {
+            '有' => get_keyword(
+                &mut iter,
+                &['有', '陽', '乎'],
+                Lex::LogicBinaryOp(LogicBinaryOp::Zhong1You3Yang2Hu1),
+            )?,
+            '無' => get_keyword(
+                &mut iter,
+                &['無', '陰', '乎'],
+                Lex::LogicBinaryOp(LogicBinaryOp::Zhong1Wu2Yin1Hu1),
+            )?,
+            '之' => Lex::Zhong1Zhi1,
+            a => return Err(Error::UnexpectedCharAfter('中', a)),
+        },
+        '為' => get_keyword(&mut iter, &['為', '是'], Lex::Wei2Shi4)?,
+        '昔' => get_keyword(&mut iter, &['昔', '之'], Lex::Xi1Zhi1)?,
+        '云' => get_keyword(&mut iter, &['云', '云'], Lex::Yun2Yun2OrYe3(YYoY::Yun2Yun2))?,
+        '恆' => get_keyword(&mut iter, &['恆', '為', '是'], Lex::Heng2Wei2Shi4)?,
+        '所' => get_keyword(&mut iter, &['所', '餘', '幾', '何'], Lex::Suo3Yu2Ji3He2)?,
+        '書' => get_keyword(&mut iter, &['書', '之'], Lex::Shu1Zhi1)?,
+        '名' => get_keyword(&mut iter, &['名', '之'], Lex::Ming2Zhi1)?,
+        '或' => get_keyword(&mut iter, &['或', '若'], Lex::Huo4Ruo4)?,
+        '等' => get_keyword(
+            &mut iter,
+            &['等', '於'],
+            Lex::IfLogicOp(IfLogicOp::Deng3Yu2),
+        )?,
+        '大' => get_keyword(&mut iter, &['大', '於'], Lex::IfLogicOp(IfLogicOp::Da4Yu2))?,
+        '小' => get_keyword(
+            &mut iter,
+            &['小', '於'],
+            Lex::IfLogicOp(IfLogicOp::Xiao3Yu2),
+        )?,
+        '不' => match iter.next().ok_or(Error::UnexpectedEOFAfter('不'))? {
             '等' => get_keyword(
                 &mut iter,
                 &['等', '於'],
-                Lex::IfLogicOp(IfLogicOp::Deng3Yu2),
+                Lex::IfLogicOp(IfLogicOp::Bu4Deng3Yu2),
+            )?,
+            '大' => get_keyword(
+                &mut iter,
+                &['大', '於'],
+                Lex::IfLogicOp(IfLogicOp::Bu4Da4Yu2),
             )?,
-            '大' => get_keyword(&mut iter, &['大', '於'], Lex::IfLogicOp(IfLogicOp::Da4Yu2))?,
             '小' => get_keyword(
                 &mut iter,
                 &['小', '於'],
-                Lex::IfLogicOp(IfLogicOp::Xiao3Yu2),
+                Lex::IfLogicOp(IfLogicOp::Bu4Xiao3Yu2),
             )?,
-            '不' => match iter.next().ok_or(Error::UnexpectedEOFAfter('不'))? {
-                '等' => get_keyword(
-                    &mut iter,
-                    &['等', '於'],
-                    Lex::IfLogicOp(IfLogicOp::Bu4Deng3Yu2),
-                )?,
-                '大' => get_keyword(
-                    &mut iter,
-                    &['大', '於'],
-                    Lex::IfLogicOp(IfLogicOp::Bu4Da4Yu2),
-                )?,
-                '小' => get_keyword(
-                    &mut iter,
-                    &['小', '於'],
-                    Lex::IfLogicOp(IfLogicOp::Bu4Xiao3Yu2),
-                )?,
-                a => return Err(Error::UnexpectedCharAfter('不', a)),
-            },
-            '以' => match iter.peek() {
-                Some('施') => {
-                    iter.next();
-                    Lex::Yi3Shi1
-                }
-                _ => Lex::Preposition(Preposition::Yi3),
-            },
-            '若' => match iter.peek() {
-                Some('其') => {
-                    match iter.peek_nth(1) {
-                        Some('然') => {
-                            iter.next();
-                            iter.next();
-                            get_keyword(&mut iter, &['然', '者'], Lex::Ruo4Qi2Ran2Zhe3)?
-                        }
-                        Some('不') => {
-                            // Note that 若 + 其 + 不等於 is a possibility
-                            match iter.peek_nth(2) {
-                                Some('然') => {
-                                    iter.next();
-                                    iter.next();
-                                    iter.next();
-                                    get_keyword(&mut iter, &['然', '者'], Lex::Ruo4Qi2Bu4Ran2Zhe3)?
-                                }
-                                _ => Lex::Ruo4,
+            a => return Err(Error::UnexpectedCharAfter('不', a)),
+        },
+        '以' => match iter.peek() {
+            Some('施') => {
+                iter.next();
+                Lex::Yi3Shi1
+            }
+            _ => Lex::Preposition(Preposition::Yi3),
+        },
+        '若' => match iter.peek() {
+            Some('其') => {
+                match iter.peek_nth(1) {
+                    Some('然') => {
+                        iter.next();
+                        iter.next();
+                        get_keyword(&mut iter, &['然', '者'], Lex::Ruo4Qi2Ran2Zhe3)?
+                    }
+                    Some('不') => {
+                        // Note that 若 + 其 + 不等於 is a possibility
+                        match iter.peek_nth(2) {
+                            Some('然') => {
+                                iter.next();
+                                iter.next();
+                                iter.next();
+                                get_keyword(&mut iter, &['然', '者'], Lex::Ruo4Qi2Bu4Ran2Zhe3)?
                             }
+                            _ => Lex::Ruo4,
                         }
-                        _ => Lex::Ruo4,
                     }
+                    _ => Lex::Ruo4,
                 }
-                Some('非') => {
-                    iter.next();
-                    Lex::Ruo4Fei1
-                }
-                _ => Lex::Ruo4,
-            },
-            '之' => match iter.peek() {
-                Some('書') => {
-                    iter.next();
-                    Lex::Zhi1Shu1
-                }
-                Some('義') => {
-                    iter.next();
-                    Lex::Zhi1Yi4
-                }
-                Some('術') => {
-                    iter.next();
-                    get_keyword(&mut iter, &['術', '也'], Lex::Zhi1Shu4Ye3)?
-                }
-                Some('物') => {
-                    iter.next();
-                    get_keyword(&mut iter, &['物', '也'], Lex::Zhi1Wu4Ye3)?
-                }
-                _ => Lex::Zhi1,
-            },
-            '今' => match iter.peek() {
-                Some('有') => {
-                    iter.next();
-                    Lex::Jin1You3
-                }
-                Some('不') => {
-                    iter.next();
-                    get_keyword(&mut iter, &['不', '復', '存', '矣'], Lex::Jin1Bu4Fu4Cun2Yi3)?
-                }
-                _ => Lex::Jin1,
-            },
-            '其' => match iter.peek() {
-                Some('餘') => {
-                    iter.next();
-                    Lex::Qi2Yu2
-                }
-                Some('物') => {
-                    iter.next();
-                    get_keyword(&mut iter, &['物', '如', '是'], Lex::Qi2Wu4Ru2Shi4)?
-                }
-                _ => Lex::Qi2,
-            },
-            '是' => match iter.next().ok_or(Error::UnexpectedEOFAfter('是'))? {
-                '矣' => Lex::Shi4Yi3,
-                '謂' => Lex::Shi4Wei4,
-                '術' => get_keyword(&mut iter, &['術', '曰'], Lex::Shi4Shu4Yue1)?,
-                a => return Err(Error::UnexpectedCharAfter('是', a)),
-            },
-            '乃' => match iter.next().ok_or(Error::UnexpectedEOFAfter('乃'))? {
-                '止' => match iter.peek() {
-                    Some('是') => match iter.peek_nth(1) {
-                        // possibly 乃止 + (是矣, 是術曰, 是謂)
-                        Some('遍') => {
-                            iter.next(); // 是
-                            iter.next(); // 遍
-                            Lex::Nai3Zhi3Shi4Bian4
-                        }
-                        _ => Lex::Nai3Zhi3,
-                    },
+            }
+            Some('非') => {
+                iter.next();
+                Lex::Ruo4Fei1
+            }
+            _ => Lex::Ruo4,
+        },
+        '之' => match iter.peek() {
+            Some('書') => {
+                iter.next();
+                Lex::Zhi1Shu1
+            }
+            Some('義') => {
+                iter.next();
+                Lex::Zhi1Yi4
+            }
+            Some('術') => {
+                iter.next();
+                get_keyword(&mut iter, &['術', '也'], Lex::Zhi1Shu4Ye3)?
+            }
+            Some('物') => {
+                iter.next();
+                get_keyword(&mut iter, &['物', '也'], Lex::Zhi1Wu4Ye3)?
+            }
+            _ => Lex::Zhi1,
+        },
+        '今' => match iter.peek() {
+            Some('有') => {
+                iter.next();
+                Lex::Jin1You3
+            }
+            Some('不') => {
+                iter.next();
+                get_keyword(&mut iter, &['不', '復', '存', '矣'], Lex::Jin1Bu4Fu4Cun2Yi3)?
+            }
+            _ => Lex::Jin1,
+        },
+        '其' => match iter.peek() {
+            Some('餘') => {
+                iter.next();
+                Lex::Qi2Yu2
+            }
+            Some('物') => {
+                iter.next();
+                get_keyword(&mut iter, &['物', '如', '是'], Lex::Qi2Wu4Ru2Shi4)?
+            }
+            _ => Lex::Qi2,
+        },
+        '是' => match iter.next().ok_or(Error::UnexpectedEOFAfter('是'))? {
+            '矣' => Lex::Shi4Yi3,
+            '謂' => Lex::Shi4Wei4,
+            '術' => get_keyword(&mut iter, &['術', '曰'], Lex::Shi4Shu4Yue1)?,
+            a => return Err(Error::UnexpectedCharAfter('是', a)),
+        },
+        '乃' => match iter.next().ok_or(Error::UnexpectedEOFAfter('乃'))? {
+            '止' => match iter.peek() {
+                Some('是') => match iter.peek_nth(1) {
+                    // possibly 乃止 + (是矣, 是術曰, 是謂)
+                    Some('遍') => {
+                        iter.next(); // 是
+                        iter.next(); // 遍
+                        Lex::Nai3Zhi3Shi4Bian4
+                    }
                     _ => Lex::Nai3Zhi3,
                 },
+                _ => Lex::Nai3Zhi3,
+            },
 
-                '行' => get_keyword(
-                    &mut iter,
-                    &['行', '是', '術', '曰'],
-                    Lex::Nai3Xing1Shi4Shu4Yue1,
-                )?,
-                '歸' => get_keyword(&mut iter, &['歸', '空', '無'], Lex::Nai3Gui1Kong1Wu2)?,
-                '得' => match iter.peek() {
-                    Some('矣') => {
-                        iter.next();
-                        Lex::Nai3De2Yi3
-                    }
-                    _ => Lex::Nai3De2,
-                },
-                a => return Err(Error::UnexpectedCharAfter('乃', a)),
+            '行' => get_keyword(
+                &mut iter,
+                &['行', '是', '術', '曰'],
+                Lex::Nai3Xing1Shi4Shu4Yue1,
+            )?,
+            '歸' => get_keyword(&mut iter, &['歸', '空', '無'], Lex::Nai3Gui1Kong1Wu2)?,
+            '得' => match iter.peek() {
+                Some('矣') => {
+                    iter.next();
+                    Lex::Nai3De2Yi3
+                }
+                _ => Lex::Nai3De2,
             },
-            '零' | '一' | '二' | '三' | '四' | '五' | '六' | '七' | '八' | '九' | '十' | '百'
-            | '千' | '萬' | '億' | '兆' | '京' | '垓' | '秭' | '穣' | '溝' | '澗' | '正' | '載'
-            | '極' => lex_int_num(c, &mut iter)?,
-            '分' | '釐' | '毫' | '絲' | '忽' | '微' | '纖' | '沙' | '塵' | '埃' | '渺' | '漠' => {
-                Lex::FloatNumKeywords(FloatNumKeywords::from_char(c).expect("Cannot happen"))
-            }
+            a => return Err(Error::UnexpectedCharAfter('乃', a)),
+        },
+        '零' | '一' | '二' | '三' | '四' | '五' | '六' | '七' | '八' | '九' | '十' | '百'
+        | '千' | '萬' | '億' | '兆' | '京' | '垓' | '秭' | '穣' | '溝' | '澗' | '正' | '載'
+        | '極' => lex_int_num(c, &mut iter)?,
+        '分' | '釐' | '毫' | '絲' | '忽' | '微' | '纖' | '沙' | '塵' | '埃' | '渺' | '漠' => {
+            Lex::FloatNumKeywords(FloatNumKeywords::from_char(c).expect("Cannot happen"))
+        }
 
-            a => panic!("unrecognized character {}", a),
-        })
-    }
-    Ok(ans)
+        a => return Err(Error::UnrecognizedCharacter(a)),
+    })
 }
 
 /// Note: cs[0] is assumed to be already parsed
-fn get_keyword(
-    mut iter: &mut peek_nth::PeekableNth<std::str::Chars>,
-    cs: &[char],
-    lex: Lex,
-) -> Result<Lex, Error> {
+fn get_keyword(mut iter: &mut Chars<'_>, cs: &[char], lex: Lex) -> Result<Lex, Error> {
     if cs.len() <= 1 {
         Ok(lex)
     } else {
@@ -717,10 +844,7 @@ fn get_keyword(
     }
 }
 
-fn lex_int_num(
-    initial_char: char,
-    iter: &mut peek_nth::PeekableNth<std::str::Chars>,
-) -> Result<Lex, Error> {
+fn lex_int_num(initial_char: char, iter: &mut Chars<'_>) -> Result<Lex, Error> {
     let mut vec = vec![];
     vec.push(IntNumKeywords::from_char(initial_char).expect("Cannot happen"));
     loop {
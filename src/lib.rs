@@ -0,0 +1,91 @@
+#![warn(clippy::pedantic)]
+#![allow(clippy::non_ascii_literal)]
+pub mod backend;
+pub mod compile;
+pub mod config;
+pub mod cst;
+pub mod diagnostic;
+pub mod eval;
+pub mod fold;
+pub mod identbimap;
+pub mod ir;
+pub mod js;
+pub mod json;
+pub mod lex;
+pub mod lsp;
+pub mod parse;
+pub mod range;
+pub mod repl;
+pub mod romanize;
+pub mod run;
+pub mod unparse;
+
+use std::collections::HashMap;
+
+/// The table from the hex codepoint (e.g. `"7532"`) to the space-separated readings (e.g. `"JIA3"`)
+pub fn pinyin_table() -> HashMap<String, String> {
+    let pinyin_json = include_str!("hanzi2roman-map-pinyin.json");
+    serde_json::from_str(pinyin_json).expect("the bundled table must be valid JSON")
+}
+
+/// Like `pinyin_table`, with the Jyutping readings of the common characters only
+pub fn jyutping_table() -> HashMap<String, String> {
+    let jyutping_json = include_str!("hanzi2roman-map-jyutping.json");
+    serde_json::from_str(jyutping_json).expect("the bundled table must be valid JSON")
+}
+
+pub use diagnostic::Diagnostic;
+
+/// The whole pipeline: lex, parse and compile
+pub fn transpile(
+    src: &str,
+    romanizer: &dyn romanize::Romanizer,
+    options: &compile::Options,
+) -> Result<String, Diagnostic> {
+    transpile_with(&backend::Rust, src, romanizer, options)
+}
+
+/// Like `transpile`, compiling with `backend`
+pub fn transpile_with(
+    backend: &dyn backend::Backend,
+    src: &str,
+    romanizer: &dyn romanize::Romanizer,
+    options: &compile::Options,
+) -> Result<String, Diagnostic> {
+    let lex = lex::lex(src)?;
+    let parsed = parse::parse(&lex)?;
+    Ok(backend.compile(&parsed, romanizer, options)?)
+}
+
+/// `src` in the canonical layout of `wenyan-to-rust fmt`; see `unparse::unparse`.
+/// Each comment is kept, on its own line before the statement that follows it.
+pub fn format(src: &str) -> Result<String, Diagnostic> {
+    let (cst, parsed) = cst::Cst::parse(src).map_err(|e| e.diagnostic)?;
+    let nodes = cst.preorder();
+    let mut comments = vec![vec![]; nodes.len()];
+    let mut trailing = vec![];
+    let mut node = 0;
+    for (i, token) in cst.tokens.tokens.iter().enumerate() {
+        // the first statement that begins at or after the token
+        while node < nodes.len() && nodes[node].tokens.start < i {
+            node += 1;
+        }
+        for trivia in &token.leading_trivia {
+            if trivia.kind == lex::TriviaKind::Comment {
+                let comment = src[trivia.span.clone()].to_string();
+                match comments.get_mut(node) {
+                    Some(comments) => comments.push(comment),
+                    None => trailing.push(comment),
+                }
+            }
+        }
+    }
+    for trivia in &cst.tokens.trailing_trivia {
+        if trivia.kind == lex::TriviaKind::Comment {
+            trailing.push(src[trivia.span.clone()].to_string());
+        }
+    }
+    Ok(unparse::unparse_with_comments(
+        &parsed, &comments, &trailing,
+    ))
+}
@@ -0,0 +1,412 @@
+use crate::compile;
+use crate::cst::{self, Cst};
+use crate::diagnostic::Diagnostic;
+use crate::identbimap::IdentBiMap;
+use crate::lex;
+use crate::parse;
+use crate::romanize::Romanizer;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::ops::Range;
+
+/// The command that gives the Rust code of a document; its argument is the URI of the document
+pub const SHOW_RUST: &str = "wenyan-to-rust.showRust";
+
+/// A Language Server Protocol server over JSON-RPC, for `wenyan-to-rust lsp`.
+/// Documents are synchronized in full and analysed anew on every request.
+pub struct Server<'a, W: Write> {
+    out: W,
+    romanizer: &'a dyn Romanizer,
+    pub options: compile::Options,
+    /// the text of each open document, by URI
+    documents: HashMap<String, String>,
+}
+
+/// Where an identifier is declared, by `名之曰`, `有 ... 名之曰` or `凡 ... 中之`
+struct Declaration {
+    /// the index of the token naming it
+    token: usize,
+    /// the tokens where it is visible, unless declared again
+    scope: Range<usize>,
+    /// the type given by `吾有` or `有`
+    type_: Option<lex::Type>,
+}
+
+/// A document that lexes and parses
+struct Analysis {
+    cst: Cst,
+    parsed: Vec<parse::Statement>,
+    declarations: Vec<Declaration>,
+}
+
+impl Analysis {
+    fn new(src: &str) -> Result<Self, cst::Error> {
+        let (cst, parsed) = Cst::parse(src)?;
+        let mut declarations = vec![];
+        collect_declarations(
+            &cst,
+            &cst.statements,
+            &parsed,
+            cst.tokens.tokens.len(),
+            &mut declarations,
+        );
+        Ok(Analysis {
+            cst,
+            parsed,
+            declarations,
+        })
+    }
+
+    fn identifier(&self, token: usize) -> Option<&str> {
+        match &self.cst.tokens.tokens.get(token)?.lex {
+            lex::Lex::Identifier(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The declaration that the identifier at `token` refers to: the latest one of the same name visible there
+    fn resolve(&self, token: usize) -> Option<&Declaration> {
+        let name = self.identifier(token)?;
+        self.declarations
+            .iter()
+            .filter(|d| {
+                d.token <= token
+                    && d.scope.contains(&token)
+                    && self.identifier(d.token) == Some(name)
+            })
+            .max_by_key(|d| d.token)
+    }
+
+    fn token_index_at(&self, offset: usize) -> Option<usize> {
+        self.cst
+            .tokens
+            .tokens
+            .iter()
+            .position(|token| token.span.contains(&offset))
+    }
+}
+
+/// Records the declarations of `stmts`, whose nodes are `nodes`, in a block whose tokens end at `block_end`
+fn collect_declarations(
+    cst: &Cst,
+    nodes: &[cst::Node],
+    stmts: &[parse::Statement],
+    block_end: usize,
+    r: &mut Vec<Declaration>,
+) {
+    for (node, st) in nodes.iter().zip(stmts) {
+        // the tokens of the statement itself, before those of its blocks
+        let own_end = node
+            .children
+            .first()
+            .map_or(node.tokens.end, |child| child.tokens.start);
+        let own = node.tokens.start..own_end;
+        let lex_at = |i: usize| &cst.tokens.tokens[i].lex;
+        match st {
+            parse::Statement::Define { .. }
+            | parse::Statement::InitDefine { .. }
+            | parse::Statement::NameMulti { .. } => {
+                let type_ = match st {
+                    parse::Statement::Define { decl, .. } => Some(decl.type_),
+                    parse::Statement::InitDefine { type_, .. } => Some(*type_),
+                    _ => None,
+                };
+                if let Some(ming2zhi1) = own.clone().find(|&i| *lex_at(i) == lex::Lex::Ming2Zhi1) {
+                    for i in ming2zhi1 + 1..own.end {
+                        if let lex::Lex::Identifier(_) = lex_at(i) {
+                            r.push(Declaration {
+                                token: i,
+                                scope: i..block_end,
+                                type_,
+                            });
+                        }
+                    }
+                }
+            }
+            parse::Statement::ForArr { .. } => {
+                if let Some(zhong1zhi1) = own.clone().find(|&i| *lex_at(i) == lex::Lex::Zhong1Zhi1)
+                {
+                    r.push(Declaration {
+                        token: zhong1zhi1 + 1,
+                        scope: node.tokens.clone(),
+                        type_: None,
+                    });
+                }
+            }
+            _ => {}
+        }
+
+        // `node.children` are the statements of the blocks one after another
+        let mut children = &node.children[..];
+        for block in st.blocks() {
+            let (these, rest) = children.split_at(block.len());
+            let end = these.last().map_or(own_end, |child| child.tokens.end);
+            collect_declarations(cst, these, block, end, r);
+            children = rest;
+        }
+    }
+}
+
+/// The LSP position of the byte `offset`: the line, and the column in UTF-16 code units
+fn position(src: &str, offset: usize) -> Value {
+    let before = &src[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    json!({
+        "line": before.matches('\n').count(),
+        "character": before[line_start..].encode_utf16().count(),
+    })
+}
+
+fn range(src: &str, span: &lex::Span) -> Value {
+    json!({ "start": position(src, span.start), "end": position(src, span.end) })
+}
+
+/// The byte offset of an LSP position; the end of the line or of `src` if it is beyond
+fn offset(src: &str, pos: &Value) -> usize {
+    let line = pos["line"].as_u64().unwrap_or(0);
+    let character = pos["character"].as_u64().unwrap_or(0);
+    let mut line_start = 0;
+    for _ in 0..line {
+        match src[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return src.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in src[line_start..].char_indices() {
+        if c == '\n' || units >= character {
+            return line_start + i;
+        }
+        units += c.len_utf16() as u64;
+    }
+    src.len()
+}
+
+fn read_message<R: BufRead>(input: &mut R) -> std::io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(n) = line.strip_prefix("Content-Length:") {
+            length = n.trim().parse().ok();
+        }
+    }
+    let length = length.ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, "missing Content-Length")
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+impl<'a, W: Write> Server<'a, W> {
+    pub fn new(out: W, romanizer: &'a dyn Romanizer) -> Self {
+        Server {
+            out,
+            romanizer,
+            options: compile::Options::default(),
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Serves the requests read from `input` until `exit` or the end of `input`
+    pub fn run<R: BufRead>(&mut self, mut input: R) -> std::io::Result<()> {
+        while let Some(message) = read_message(&mut input)? {
+            let id = message.get("id").cloned();
+            let params = &message["params"];
+            let result = match message["method"].as_str() {
+                Some("initialize") => Ok(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "executeCommandProvider": { "commands": [SHOW_RUST] },
+                    },
+                    "serverInfo": { "name": "wenyan-to-rust" },
+                })),
+                Some("shutdown") => Ok(Value::Null),
+                Some("exit") => return Ok(()),
+                Some("textDocument/didOpen") => {
+                    let document = &params["textDocument"];
+                    let text = document["text"].as_str().unwrap_or_default();
+                    self.update(document["uri"].as_str(), text.to_string())?;
+                    continue;
+                }
+                Some("textDocument/didChange") => {
+                    // the whole text, as the server asks for in `initialize`
+                    let text = params["contentChanges"]
+                        .as_array()
+                        .and_then(|changes| changes.last())
+                        .and_then(|change| change["text"].as_str());
+                    if let Some(text) = text {
+                        self.update(params["textDocument"]["uri"].as_str(), text.to_string())?;
+                    }
+                    continue;
+                }
+                Some("textDocument/didClose") => {
+                    if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                        self.documents.remove(uri);
+                        self.publish_diagnostics(uri, &[])?;
+                    }
+                    continue;
+                }
+                Some("textDocument/hover") => Ok(self.hover(params)),
+                Some("textDocument/definition") => Ok(self.definition(params)),
+                Some("workspace/executeCommand") => self.execute_command(params),
+                _ if id.is_none() => continue,
+                Some(method) => Err((-32601, format!("unknown method {}", method))),
+                None => Err((-32600, String::from("not a request"))),
+            };
+            let response = match result {
+                Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                Err((code, message)) => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": code, "message": message },
+                }),
+            };
+            self.send(&response)?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, message: &Value) -> std::io::Result<()> {
+        let body = message.to_string();
+        write!(self.out, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.out.flush()
+    }
+
+    fn update(&mut self, uri: Option<&str>, text: String) -> std::io::Result<()> {
+        if let Some(uri) = uri {
+            let diagnostics = self.diagnostics(&text);
+            self.documents.insert(uri.to_string(), text);
+            self.publish_diagnostics(uri, &diagnostics)?;
+        }
+        Ok(())
+    }
+
+    fn publish_diagnostics(&mut self, uri: &str, diagnostics: &[Value]) -> std::io::Result<()> {
+        self.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "diagnostics": diagnostics },
+        }))
+    }
+
+    /// The error that keeps `src` from being transpiled, if any
+    fn diagnostics(&self, src: &str) -> Vec<Value> {
+        let (span, diagnostic) = match Analysis::new(src) {
+            Err(e) => (e.span, e.diagnostic),
+            Ok(analysis) => match compile::compile(&analysis.parsed, self.romanizer, &self.options)
+            {
+                Ok(_) => return vec![],
+                Err(e) => {
+                    let span = match &e {
+                        // the first use that refers to no declaration
+                        compile::Error::UseBeforeDeclaration(parse::Identifier(name)) => (0
+                            ..analysis.cst.tokens.tokens.len())
+                            .find(|&i| {
+                                analysis.identifier(i) == Some(name)
+                                    && analysis.resolve(i).is_none()
+                            })
+                            .map(|i| analysis.cst.tokens.tokens[i].span.clone()),
+                        compile::Error::Unsupported(_) => None,
+                    };
+                    (span.unwrap_or(0..0), Diagnostic::from(e))
+                }
+            },
+        };
+        vec![json!({
+            "range": range(src, &span),
+            "severity": 1,
+            "source": "wenyan-to-rust",
+            "message": diagnostic.to_string(),
+        })]
+    }
+
+    /// The open document of `params["textDocument"]`, analysed, and the byte offset of `params["position"]`
+    fn analyse_at(&self, params: &Value) -> Option<(&str, Analysis, usize)> {
+        let src = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let analysis = Analysis::new(src).ok()?;
+        let offset = offset(src, &params["position"]);
+        Some((src, analysis, offset))
+    }
+
+    /// For an identifier: its type if declared with one, and its name in the Rust code
+    fn hover(&self, params: &Value) -> Value {
+        let (src, analysis, offset) = match self.analyse_at(params) {
+            Some(a) => a,
+            None => return Value::Null,
+        };
+        let token = match analysis.token_index_at(offset) {
+            Some(token) => token,
+            None => return Value::Null,
+        };
+        let name = match analysis.identifier(token) {
+            Some(name) => name,
+            None => return Value::Null,
+        };
+        let type_ = analysis
+            .resolve(token)
+            .and_then(|d| d.type_)
+            .map(|t| format!(" {}", crate::unparse::type_name(t)));
+        let rust = IdentBiMap::new(&analysis.parsed, self.romanizer)
+            .ok()
+            .and_then(|map| {
+                let (_, rust) = map.mapping().into_iter().find(|(id, _)| id.0 == name)?;
+                Some(format!(" → `{}`", rust))
+            });
+        let text = format!(
+            "「{}」{}{}",
+            name,
+            type_.unwrap_or_default(),
+            rust.unwrap_or_default()
+        );
+        json!({
+            "contents": { "kind": "markdown", "value": text },
+            "range": range(src, &analysis.cst.tokens.tokens[token].span),
+        })
+    }
+
+    /// For an identifier: where the variable it refers to is named
+    fn definition(&self, params: &Value) -> Value {
+        let (src, analysis, offset) = match self.analyse_at(params) {
+            Some(a) => a,
+            None => return Value::Null,
+        };
+        let declaration = match analysis
+            .token_index_at(offset)
+            .and_then(|token| analysis.resolve(token))
+        {
+            Some(d) => d,
+            None => return Value::Null,
+        };
+        json!({
+            "uri": params["textDocument"]["uri"],
+            "range": range(src, &analysis.cst.tokens.tokens[declaration.token].span),
+        })
+    }
+
+    fn execute_command(&self, params: &Value) -> Result<Value, (i64, String)> {
+        if params["command"] != SHOW_RUST {
+            return Err((-32602, format!("unknown command {}", params["command"])));
+        }
+        let uri = params["arguments"][0].as_str().unwrap_or_default();
+        let src = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| (-32602, format!("{} is not open", uri)))?;
+        crate::transpile(src, self.romanizer, &self.options)
+            .map(Value::String)
+            .map_err(|e| (-32603, e.to_string()))
+    }
+}
@@ -1,24 +1,288 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::non_ascii_literal)]
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 extern crate clap;
-use clap::{App, Arg};
-mod compile;
-mod identbimap;
-mod lex;
-mod parse;
+use clap::{App, AppSettings, Arg, SubCommand};
+use wenyan_to_rust::backend::{self, Backend};
+use wenyan_to_rust::romanize::{self, Romanizer};
+use wenyan_to_rust::{compile, config, cst, eval, json, lex, lsp, parse, repl, run, Diagnostic};
 
-#[cfg(test)]
-mod tests;
+/// `-` as an input path stands for the standard input
+fn read_input(path: &str) -> std::io::Result<String> {
+    let mut contents = String::new();
+    if path == "-" {
+        std::io::stdin().read_to_string(&mut contents)?;
+    } else {
+        let mut file = File::open(path)?;
+        file.read_to_string(&mut contents)?;
+    }
+    Ok(contents)
+}
+
+/// Everything other than the transpiled code goes to stderr, so that the stdout can be piped into `rustc`.
+fn lex_and_parse(contents: &str, verbose_level: u64) -> Result<Vec<parse::Statement>, Diagnostic> {
+    if verbose_level > 0 {
+        eprintln!("src: \n----------------------");
+        eprintln!("{}", contents);
+        eprintln!("----------------------");
+    }
+
+    let lex = lex::lex(&contents);
+    if verbose_level > 0 {
+        eprintln!("\nlexer output: \n----------------------");
+        eprintln!("{:?}", lex);
+        eprintln!("----------------------");
+    }
+    let lex = lex?;
+
+    let parsed = parse::parse(&lex);
+    if verbose_level > 0 {
+        eprintln!("\nparser output: \n----------------------");
+        eprintln!("{:?}", parsed);
+        eprintln!("----------------------");
+    }
+    Ok(parsed?)
+}
+
+fn transpile(
+    contents: &str,
+    backend: &dyn Backend,
+    romanizer: &dyn Romanizer,
+    options: &compile::Options,
+    verbose_level: u64,
+) -> Result<String, Diagnostic> {
+    let parsed = lex_and_parse(contents, verbose_level)?;
+    let compiled = backend.compile(&parsed, romanizer, options)?;
+    if verbose_level > 0 {
+        eprintln!("\ncompiler output: \n----------------------");
+        eprint!("{}", compiled);
+        eprintln!("----------------------");
+    }
+    Ok(compiled)
+}
+
+/// What `--emit` can ask for besides the names of the backends.
+/// With anything other than a single backend, the output is a JSON object with `version` and a key for each,
+/// the code of a backend being a string.
+const JSON_KINDS: [&str; 2] = ["tokens", "ast"];
+
+/// The output for `--emit=kinds`
+fn emit(
+    contents: &str,
+    kinds: &[&str],
+    romanizer: &dyn Romanizer,
+    options: &compile::Options,
+    verbose_level: u64,
+) -> Result<String, Diagnostic> {
+    if let [backend] = kinds {
+        if let Some(backend) = backend::by_name(backend) {
+            return transpile(contents, backend, romanizer, options, verbose_level);
+        }
+    }
+    let mut r = serde_json::Map::new();
+    r.insert(String::from("version"), json::VERSION.into());
+    for kind in kinds {
+        match *kind {
+            "tokens" => {
+                let tokens = lex::lex_with_trivia(contents).map_err(|(e, _)| e)?;
+                if let serde_json::Value::Object(tokens) = json::tokens(contents, &tokens) {
+                    r.extend(tokens);
+                }
+            }
+            "ast" => {
+                let (cst, parsed) = cst::Cst::parse(contents).map_err(|e| e.diagnostic)?;
+                r.insert(String::from("ast"), json::ast(&cst, &parsed));
+            }
+            name => {
+                let backend = backend::by_name(name).expect("clap checks the names");
+                let compiled = transpile(contents, backend, romanizer, options, verbose_level)?;
+                r.insert(name.to_string(), compiled.into());
+            }
+        }
+    }
+    let mut out = serde_json::to_string_pretty(&r).expect("a JSON value can always be written");
+    out.push('\n');
+    Ok(out)
+}
+
+/// `wenyan-to-rust run file.wy -- args...`
+fn run_subcommand(
+    matches: &clap::ArgMatches,
+    romanizer: &dyn Romanizer,
+    options: &compile::Options,
+    verbose_level: u64,
+) -> std::io::Result<i32> {
+    let input = matches.value_of("INPUT").unwrap();
+    let args = matches
+        .values_of("ARGS")
+        .map(Iterator::collect)
+        .unwrap_or_else(Vec::new);
+    let contents = read_input(input)?;
+    let parsed = match lex_and_parse(&contents, verbose_level) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}: {}", input, e);
+            return Ok(1);
+        }
+    };
+    let (compiled, origins) = match compile::compile_with_origins(&parsed, romanizer, options) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}: {}", input, Diagnostic::from(e));
+            return Ok(1);
+        }
+    };
+    // the spans of the statements, for rustc's errors
+    let cst = match cst::Cst::parse(&contents) {
+        Ok((cst, _)) => cst,
+        Err(e) => {
+            eprintln!("{}: {}", input, e.diagnostic);
+            return Ok(1);
+        }
+    };
+    let origins = run::Origins {
+        path: input,
+        cst: &cst,
+        lines: &origins,
+    };
+    match run::run(&compiled, Some(&origins), &args) {
+        Ok(code) => Ok(code),
+        Err(run::Error::RustcFailed) => {
+            eprintln!("{}: rustc failed to compile the transpiled code", input);
+            Ok(1)
+        }
+        Err(run::Error::Io(e)) => {
+            eprintln!("{}: failed to invoke rustc or the program: {}", input, e);
+            Ok(1)
+        }
+    }
+}
+
+/// `wenyan-to-rust eval file.wy`; needs no rustc
+fn eval_subcommand(matches: &clap::ArgMatches, verbose_level: u64) -> std::io::Result<i32> {
+    let input = matches.value_of("INPUT").unwrap();
+    let contents = read_input(input)?;
+    let parsed = match lex_and_parse(&contents, verbose_level) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}: {}", input, e);
+            return Ok(1);
+        }
+    };
+    let stdout = std::io::stdout();
+    let mut interpreter = eval::Interpreter::new(stdout.lock());
+    match interpreter.run(&parsed) {
+        Ok(()) => Ok(0),
+        Err(e) => {
+            eprintln!("{}: runtime error: {:?}", input, e);
+            Ok(1)
+        }
+    }
+}
+
+/// `wenyan-to-rust repl`; reads statements from stdin one by one, keeping the variables and the values not yet named
+fn repl_subcommand(
+    matches: &clap::ArgMatches,
+    romanizer: &dyn Romanizer,
+    options: &compile::Options,
+) -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut repl = repl::Repl::new(stdout.lock(), romanizer);
+    repl.options = *options;
+    repl.show_rust = matches.is_present("rust");
+    repl.run(stdin.lock())
+}
+
+/// `wenyan-to-rust lsp`; a language server on stdin and stdout
+fn lsp_subcommand(romanizer: &dyn Romanizer, options: &compile::Options) -> std::io::Result<()> {
+    let stdin = std::io::stdin();
+    let stdout = std::io::stdout();
+    let mut server = lsp::Server::new(stdout.lock(), romanizer);
+    server.options = *options;
+    server.run(stdin.lock())
+}
+
+/// The standard input can only be read once
+fn reads_stdin_twice(inputs: &[&str]) -> bool {
+    let twice = inputs.iter().filter(|input| **input == "-").count() > 1;
+    if twice {
+        eprintln!("`-` can only be given once");
+    }
+    twice
+}
+
+/// `wenyan-to-rust fmt files...`; rewrites each file in place, or with `--check` only reports where it differs
+fn fmt_subcommand(matches: &clap::ArgMatches) -> std::io::Result<i32> {
+    let check = matches.is_present("check");
+    let inputs = matches.values_of("INPUT").unwrap().collect::<Vec<_>>();
+    if reads_stdin_twice(&inputs) {
+        return Ok(2);
+    }
+    let mut code = 0;
+    for input in inputs {
+        let contents = match read_input(input) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("{}: {}", input, e);
+                code = 1;
+                continue;
+            }
+        };
+        let formatted = match wenyan_to_rust::format(&contents) {
+            Ok(f) => f,
+            Err(e) => {
+                eprintln!("{}: {}", input, e);
+                code = 1;
+                continue;
+            }
+        };
+        if check {
+            if formatted != contents {
+                // the first line that differs
+                let line = contents
+                    .lines()
+                    .zip(formatted.lines())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                eprintln!("{}:{}: not formatted", input, line + 1);
+                code = 1;
+            }
+        } else if input == "-" {
+            print!("{}", formatted);
+        } else if formatted != contents {
+            std::fs::write(input, formatted)?;
+        }
+    }
+    Ok(code)
+}
+
+/// In batch mode, `foo/bar.wy` is written to `<out-dir>/bar.rs`, or `<out-dir>/bar.json` with `--emit`
+fn output_path_in_dir(out_dir: &Path, input: &str, extension: &str) -> PathBuf {
+    let stem = if input == "-" {
+        std::ffi::OsStr::new("stdin")
+    } else {
+        Path::new(input)
+            .file_stem()
+            .unwrap_or_else(|| std::ffi::OsStr::new(input))
+    };
+    out_dir.join(stem).with_extension(extension)
+}
 
-use std::collections::HashMap;
-use std::include_str;
 fn main() -> std::io::Result<()> {
+    let emit_kinds = backend::BACKENDS
+        .iter()
+        .map(|backend| backend.name())
+        .chain(JSON_KINDS.iter().copied())
+        .collect::<Vec<_>>();
     let matches = App::new("wenyan-to-rust")
         .version("0.1.0")
         .author("jekto.vatimeliju <jekto.vatimeliju@gmail.com>")
         .about("Tries to convert wenyan to rust")
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::with_name("config")
                 .short("c")
@@ -27,60 +291,236 @@ fn main() -> std::io::Result<()> {
                 .help("Sets a custom config file")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("romanization")
+                .long("romanization")
+                .value_name("SCHEME")
+                .help("Sets how identifiers are turned into Rust; overrides the config")
+                .possible_values(&romanize::SCHEME_NAMES)
+                .global(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("optimize")
+                .short("O")
+                .help("Writes the values not yet named where they are used, instead of binding each to an `_ans`, and computes what is known when compiling")
+                .global(true),
+        )
         .arg(
             Arg::with_name("INPUT")
-                .help("Sets the input file to use")
+                .help("Sets the input file(s) to use; `-` reads from stdin")
                 .required(true)
+                .multiple(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("output")
+                .short("o")
+                .long("output")
+                .value_name("FILE")
+                .help("Writes the output to FILE instead of stdout")
+                .takes_value(true)
+                .conflicts_with("out-dir"),
+        )
+        .arg(
+            Arg::with_name("out-dir")
+                .long("out-dir")
+                .value_name("DIR")
+                .help("Writes the output for each input to DIR/<input stem>.rs, or with the extension of the `--emit` kind (.js, .ir, .json)")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("emit")
+                .long("emit")
+                .value_name("KINDS")
+                .help("Writes the code of another backend (`--emit=js`), the lowered program (`--emit=ir`), or the tokens with their spans, the AST and/or the code as JSON (`--emit=tokens,ast`)")
+                .possible_values(&emit_kinds)
+                .multiple(true)
+                .require_delimiter(true)
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("v")
                 .short("v")
                 .multiple(true)
                 .help("Sets the level of verbosity"),
         )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about("Transpiles the input, compiles it with the local rustc and runs it")
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Sets the input file to use; `-` reads from stdin")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("ARGS")
+                        .help("Arguments passed to the compiled program")
+                        .multiple(true)
+                        .last(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("eval")
+                .about("Interprets the input directly, without going through Rust")
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Sets the input file to use; `-` reads from stdin")
+                        .required(true)
+                        .index(1),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("fmt")
+                .about("Rewrites the input files in the canonical layout: one statement per line, blocks indented by `　`")
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("Sets the input file(s) to use; `-` reads from stdin and writes to stdout")
+                        .required(true)
+                        .multiple(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("Changes no file, but fails if some file is not formatted"),
+                ),
+        )
+        .subcommand(SubCommand::with_name("lsp").about(
+            "Runs a Language Server Protocol server on stdin and stdout: diagnostics, hover, go to definition, and the command `wenyan-to-rust.showRust`",
+        ))
+        .subcommand(
+            SubCommand::with_name("repl")
+                .about("Reads and evaluates statements interactively; `:help` lists the commands")
+                .arg(
+                    Arg::with_name("rust")
+                        .long("rust")
+                        .help("Shows the Rust code each input transpiles to"),
+                ),
+        )
         .get_matches();
 
-    // let config = matches.value_of("config").unwrap_or("default.conf");
-
-    let pinyin_json = include_str!("hanzi2roman-map-pinyin.json");
-    let conversion_table: HashMap<String, String> = serde_json::from_str(pinyin_json)?;
+    let mut config = match matches.value_of("config") {
+        None => config::Config::default(),
+        Some(path) => match config::Config::read(Path::new(path)) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("{}: invalid config: {:?}", path, e);
+                std::process::exit(2);
+            }
+        },
+    };
+    if let Some(scheme) = matches.value_of("romanization") {
+        config.romanization = scheme.parse().ok();
+    }
+    if matches.is_present("optimize") {
+        config.optimize = true;
+    }
+    let options = config.compile_options();
+    let romanizer = match config.romanizer() {
+        Ok(romanizer) => romanizer,
+        Err(e) => {
+            eprintln!("cannot set up the romanization: {:?}", e);
+            std::process::exit(2);
+        }
+    };
 
     let verbose_level = matches.occurrences_of("v");
 
-    let mut file = File::open(matches.value_of("INPUT").unwrap())?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    if verbose_level > 0 {
-        println!("src: \n----------------------");
-        println!("{}", contents);
-        println!("----------------------");
+    if let Some(sub) = matches.subcommand_matches("run") {
+        let code = run_subcommand(sub, &*romanizer, &options, verbose_level)?;
+        std::process::exit(code);
     }
 
-    let lex = lex::lex(&contents);
-    if verbose_level > 0 || lex.is_err() {
-        println!("\nlexer output: \n----------------------");
-        println!("{:?}", lex);
-        println!("----------------------");
-    }
-
-    if let Ok(lex) = lex {
-        let parsed = parse::parse(&lex);
-        if verbose_level > 0 || parsed.is_err() {
-            println!("\nparser output: \n----------------------");
-            println!("{:?}", parsed);
-            println!("----------------------");
+    if let Some(sub) = matches.subcommand_matches("eval") {
+        let code = eval_subcommand(sub, verbose_level)?;
+        std::process::exit(code);
+    }
+
+    if let Some(sub) = matches.subcommand_matches("fmt") {
+        let code = fmt_subcommand(sub)?;
+        std::process::exit(code);
+    }
+
+    if matches.subcommand_matches("lsp").is_some() {
+        return lsp_subcommand(&*romanizer, &options);
+    }
+
+    if let Some(sub) = matches.subcommand_matches("repl") {
+        return repl_subcommand(sub, &*romanizer, &options);
+    }
+
+    let inputs = matches.values_of("INPUT").unwrap().collect::<Vec<_>>();
+    let out_dir = matches.value_of("out-dir").map(Path::new);
+    let kinds = match matches.values_of("emit") {
+        Some(kinds) => kinds.collect(),
+        None => vec!["rust"],
+    };
+    let extension = match kinds.as_slice() {
+        [kind] => backend::by_name(kind).map_or("json", |backend| backend.extension()),
+        _ => "json",
+    };
+
+    if out_dir.is_none() && inputs.len() > 1 {
+        eprintln!("multiple inputs require --out-dir");
+        std::process::exit(2);
+    }
+    if reads_stdin_twice(&inputs) {
+        std::process::exit(2);
+    }
+    if let Some(dir) = out_dir {
+        // two inputs with the same stem would be written to the same file
+        let mut written_by = HashMap::new();
+        for input in &inputs {
+            let path = output_path_in_dir(dir, input, extension);
+            if let Some(other) = written_by.insert(path.clone(), *input) {
+                eprintln!(
+                    "{} and {} would both be written to {}",
+                    other,
+                    input,
+                    path.display()
+                );
+                std::process::exit(2);
+            }
         }
-        if let Ok(parsed) = parsed {
-            let compiled = compile::compile(&parsed, &conversion_table);
-            if verbose_level > 0 {
-                println!("\ncompiler output: \n----------------------");
+    }
+
+    if let Some(dir) = out_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut failed = false;
+    for input in inputs {
+        let contents = match read_input(input) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("{}: {}", input, e);
+                failed = true;
+                continue;
             }
-            print!("{}", compiled);
-            if verbose_level > 0 {
-                println!("----------------------");
+        };
+
+        let compiled = match emit(&contents, &kinds, &*romanizer, &options, verbose_level) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("{}: {}", input, e);
+                failed = true;
+                continue;
             }
+        };
+
+        if let Some(dir) = out_dir {
+            std::fs::write(output_path_in_dir(dir, input, extension), compiled)?;
+        } else if let Some(path) = matches.value_of("output") {
+            std::fs::write(path, compiled)?;
+        } else {
+            print!("{}", compiled);
         }
     }
+
+    if failed {
+        std::process::exit(1);
+    }
     Ok(())
 }
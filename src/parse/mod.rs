@@ -1,15 +1,45 @@
 use crate::lex;
-type LexIter<'a> = peek_nth::PeekableNth<std::slice::Iter<'a, lex::Lex>>;
+use std::ops::Range;
+
+/// The tokens, keeping track of how many have been read and of where each statement is
+struct LexIter<'a> {
+    iter: peek_nth::PeekableNth<std::slice::Iter<'a, lex::Lex>>,
+    consumed: usize,
+    /// how many loops the next token is in
+    loops: usize,
+    /// the token range of each statement begun, in the order they begin
+    ranges: Vec<Range<usize>>,
+}
+
+impl<'a> Iterator for LexIter<'a> {
+    type Item = &'a lex::Lex;
+
+    fn next(&mut self) -> Option<&'a lex::Lex> {
+        let token = self.iter.next()?;
+        self.consumed += 1;
+        Some(token)
+    }
+}
+
+impl<'a> LexIter<'a> {
+    fn peek(&mut self) -> Option<&&'a lex::Lex> {
+        self.iter.peek()
+    }
+
+    fn peek_nth(&mut self, n: usize) -> Option<&&'a lex::Lex> {
+        self.iter.peek_nth(n)
+    }
+}
 pub type CondPlusStatements = (IfCond, Vec<Statement>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Lvalue {
     Simple(Identifier),
     Index(Identifier, i64),
     IndexByIdent(Identifier, Identifier),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value<T> {
     Simple(T),
     Index(T, i64),
@@ -17,7 +47,7 @@ pub enum Value<T> {
     Length(T),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum Statement {
     Declare(DeclareStatement),
     Print,
@@ -89,14 +119,36 @@ pub enum Statement {
     },
 }
 
-#[derive(Debug, Clone)]
+impl Statement {
+    /// The blocks directly inside, e.g. the branches of `若`
+    pub fn blocks(&self) -> Vec<&[Statement]> {
+        match self {
+            Statement::If {
+                ifcase,
+                elseifcases,
+                elsecase,
+            } => std::iter::once(ifcase)
+                .chain(elseifcases)
+                .map(|(_, stmts)| &stmts[..])
+                .chain(std::iter::once(&elsecase[..]))
+                .collect(),
+            Statement::ForEnum { statements, .. }
+            | Statement::ForEnumIdent { statements, .. }
+            | Statement::Loop { statements } => vec![statements],
+            Statement::ForArr { stmts, .. } => vec![stmts],
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum IfCond {
     Unary(UnaryIfExpr),
     Binary(UnaryIfExpr, lex::IfLogicOp, UnaryIfExpr),
     NotQi2,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UnaryIfExpr {
     Simple(OrQi2<Data>),
     Complex(Value<Data>),
@@ -121,7 +173,7 @@ impl DivBinaryOp {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum MathKind {
     ArithBinaryMath(
         lex::ArithBinaryOp,
@@ -134,14 +186,14 @@ pub enum MathKind {
     ModMath(DivBinaryOp, OrQi2<Data>, lex::Preposition, OrQi2<Data>),
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub struct DeclareStatement {
     pub how_many_variables: usize,
     pub type_: lex::Type,
     pub data_arr: Vec<Data>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Data {
     StringLiteral(String),
     BoolValue(bool),
@@ -160,14 +212,20 @@ pub enum Error {
     SomethingWentWrong(Position),
     UnexpectedEOF,
     InvalidVariableCount,
+    /// valid wenyan that this compiler cannot parse yet
+    Unsupported(&'static str),
+    /// a keyword that cannot begin a statement, or begins one that is not supported yet
+    UnsupportedStatement(lex::Lex),
+    /// `乃止` or `乃止是遍` not inside a loop
+    JumpOutsideLoop,
 }
 
 #[allow(clippy::enum_glob_use)]
-fn interpret_intnum(num: &lex::IntNum) -> i64 {
+pub(crate) fn interpret_intnum(num: &lex::IntNum) -> Result<i64, Error> {
     use lex::IntMult::*;
     use lex::IntNumKeywords::*;
     let lex::IntNum(v) = num;
-    match *v.as_slice() {
+    Ok(match *v.as_slice() {
         [Ling2] => 0,
         [IntDigit(d)] => d.to_num(),
 
@@ -199,11 +257,11 @@ fn interpret_intnum(num: &lex::IntNum) -> i64 {
         }
 
         [IntMult(Qian1)] => 1000,
-        _ => unimplemented!("parsing integer"),
-    }
+        _ => return Err(Error::Unsupported("this form of integer")),
+    })
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum OrQi2<T> {
     NotQi2(T),
     Qi2,
@@ -232,7 +290,7 @@ fn parse_data_or_qi2(iter: &mut LexIter<'_>) -> Result<OrQi2<Data>, Error> {
         lex::Lex::Identifier(ident) => Ok(OrQi2::NotQi2(Data::Identifier(Identifier(
             ident.to_string(),
         )))),
-        lex::Lex::IntNum(intnum) => Ok(OrQi2::NotQi2(Data::IntNum(interpret_intnum(intnum)))), /* FIXME: must handle float */
+        lex::Lex::IntNum(intnum) => Ok(OrQi2::NotQi2(Data::IntNum(interpret_intnum(intnum)?))), /* FIXME: must handle float */
         lex::Lex::Qi2 => Ok(OrQi2::Qi2),
         _ => Err(Error::SomethingWentWrong(here!())),
     }
@@ -269,7 +327,7 @@ fn parse_data(iter: &mut LexIter<'_>) -> Result<Data, Error> {
         lex::Lex::StringLiteral(strlit) => Ok(Data::StringLiteral(strlit.to_string())),
         lex::Lex::BoolValue(bv) => Ok(Data::BoolValue(bv.interpret())),
         lex::Lex::Identifier(ident) => Ok(Data::Identifier(Identifier(ident.to_string()))),
-        lex::Lex::IntNum(intnum) => Ok(Data::IntNum(interpret_intnum(intnum))), /* FIXME: must handle float */
+        lex::Lex::IntNum(intnum) => Ok(Data::IntNum(interpret_intnum(intnum)?)), /* FIXME: must handle float */
         _ => Err(Error::SomethingWentWrong(here!())),
     }
 }
@@ -297,20 +355,19 @@ fn parse_init_define_statement_after_you3(mut iter: &mut LexIter<'_>) -> Result<
                     lex::Lex::Yue1 => {
                         let next = iter.next();
                         match next.ok_or(Error::UnexpectedEOF)? {
-                            lex::Lex::Identifier(ident) => {
-                                 Ok(Statement::InitDefine {
-                                    type_: *t,
-                                    name: Identifier(ident.to_string()),
-                                    data
-                                })
-                            }
-                            _ =>  Err(Error::SomethingWentWrong(here!())),
+                            lex::Lex::Identifier(ident) => Ok(Statement::InitDefine {
+                                type_: *t,
+                                name: Identifier(ident.to_string()),
+                                data,
+                            }),
+                            _ => Err(Error::SomethingWentWrong(here!())),
                         }
                     }
                     _ => Err(Error::SomethingWentWrong(here!())),
                 }
             }
-            None | Some(..) => panic!("If this message is obtained by a wenyan program that successfully compiles in the original edition, please submit an issue."),
+            None => Err(Error::UnexpectedEOF),
+            Some(..) => Err(Error::SomethingWentWrong(here!())),
         }
     } else {
         Err(Error::SomethingWentWrong(here!()))
@@ -321,17 +378,9 @@ fn parse_for_enum_statement_after_wei2shi4(mut iter: &mut LexIter<'_>) -> Result
     match iter.next().ok_or(Error::UnexpectedEOF)? {
         lex::Lex::IntNum(num) => match iter.next().ok_or(Error::UnexpectedEOF)? {
             lex::Lex::Bian4Loop => {
-                let mut inner = vec![];
-                loop {
-                    if let Some(&&lex::Lex::Yun2Yun2OrYe3(_)) = iter.peek() {
-                        iter.next();
-                        break;
-                    }
-
-                    inner.push(parse_statement(&mut iter)?);
-                }
+                let inner = parse_loop_body(&mut iter)?;
                 Ok(Statement::ForEnum {
-                    num: interpret_intnum(num),
+                    num: interpret_intnum(num)?,
                     statements: inner,
                 })
             }
@@ -339,15 +388,7 @@ fn parse_for_enum_statement_after_wei2shi4(mut iter: &mut LexIter<'_>) -> Result
         },
         lex::Lex::Identifier(ident) => match iter.next().ok_or(Error::UnexpectedEOF)? {
             lex::Lex::Bian4Loop => {
-                let mut inner = vec![];
-                loop {
-                    if let Some(&&lex::Lex::Yun2Yun2OrYe3(_)) = iter.peek() {
-                        iter.next();
-                        break;
-                    }
-
-                    inner.push(parse_statement(&mut iter)?);
-                }
+                let inner = parse_loop_body(&mut iter)?;
                 Ok(Statement::ForEnumIdent {
                     ident: OrQi2::NotQi2(Identifier(ident.to_string())),
                     statements: inner,
@@ -359,15 +400,7 @@ fn parse_for_enum_statement_after_wei2shi4(mut iter: &mut LexIter<'_>) -> Result
         // not found in spec.html
         lex::Lex::Qi2 => match iter.next().ok_or(Error::UnexpectedEOF)? {
             lex::Lex::Bian4Loop => {
-                let mut inner = vec![];
-                loop {
-                    if let Some(&&lex::Lex::Yun2Yun2OrYe3(_)) = iter.peek() {
-                        iter.next();
-                        break;
-                    }
-
-                    inner.push(parse_statement(&mut iter)?);
-                }
+                let inner = parse_loop_body(&mut iter)?;
                 Ok(Statement::ForEnumIdent {
                     ident: OrQi2::Qi2,
                     statements: inner,
@@ -392,8 +425,8 @@ fn parse_optional_indexer<T>(iter: &mut LexIter<'_>, data: T) -> Result<Value<T>
     if Some(&&lex::Lex::Zhi1) == next_token {
         iter.next();
         match iter.next().ok_or(Error::UnexpectedEOF)? {
-            lex::Lex::IntNum(int_num) => Ok(Value::Index(data, interpret_intnum(int_num))),
-            lex::Lex::StringLiteral(lit) => unimplemented!("data之STRING_LITERAL"),
+            lex::Lex::IntNum(int_num) => Ok(Value::Index(data, interpret_intnum(int_num)?)),
+            lex::Lex::StringLiteral(_) => Err(Error::Unsupported("data之STRING_LITERAL")),
             lex::Lex::Identifier(id) => Ok(Value::IndexByIdent(data, Identifier(id.to_string()))),
             lex::Lex::Chang2 => Ok(Value::Length(data)),
             _ => Err(Error::SomethingWentWrong(here!())),
@@ -405,7 +438,7 @@ fn parse_optional_indexer<T>(iter: &mut LexIter<'_>, data: T) -> Result<Value<T>
 
 fn parse_assign_after_zhe3(mut iter: &mut LexIter<'_>) -> Result<Value<OrQi2<Data>>, Error> {
     match iter.next().ok_or(Error::UnexpectedEOF)? {
-        lex::Lex::Jin1Bu4Fu4Cun2Yi3 => unimplemented!("昔之 ... 者今不復存矣"),
+        lex::Lex::Jin1Bu4Fu4Cun2Yi3 => Err(Error::Unsupported("昔之 ... 者今不復存矣")),
         lex::Lex::Jin1 => {
             let data = parse_data_or_qi2(&mut iter)?;
             let res = parse_optional_indexer(&mut iter, data)?;
@@ -440,14 +473,16 @@ fn parse_assign_after_xi1zhi1(mut iter: &mut LexIter<'_>) -> Result<Statement, E
                 if let lex::Lex::Zhe3 = iter.next().ok_or(Error::UnexpectedEOF)? {
                     let rvalue = parse_assign_after_zhe3(&mut iter)?;
                     Ok(Statement::Assignment {
-                        lvalue: Lvalue::Index(ident, interpret_intnum(&int_num)),
+                        lvalue: Lvalue::Index(ident, interpret_intnum(&int_num)?),
                         rvalue,
                     })
                 } else {
                     Err(Error::SomethingWentWrong(here!()))
                 }
             }
-            lex::Lex::StringLiteral(lit) => unimplemented!("昔之 IDENTIFIER 之 STRING"),
+            lex::Lex::StringLiteral(_) => {
+                Err(Error::Unsupported("昔之 IDENTIFIER 之 STRING_LITERAL"))
+            }
             lex::Lex::Identifier(id) => {
                 if let lex::Lex::Zhe3 = iter.next().ok_or(Error::UnexpectedEOF)? {
                     let rvalue = parse_assign_after_zhe3(&mut iter)?;
@@ -485,13 +520,10 @@ fn parse_reference_statement_after_fu2(mut iter: &mut LexIter<'_>) -> Result<Sta
         Some(lex::Lex::Zhi1) => {
             // ('之' (STRING_LITERAL|INT_NUM|'其餘'|IDENTIFIER|'長'))?
             iter.next();
-            match iter
-                .next()
-                .ok_or_else(|| Error::SomethingWentWrong(here!()))?
-            {
-                lex::Lex::StringLiteral(lit) => unimplemented!("夫 data 之 STRING_LITERAL"),
+            match iter.next().ok_or(Error::UnexpectedEOF)? {
+                lex::Lex::StringLiteral(_) => Err(Error::Unsupported("夫 data 之 STRING_LITERAL")),
                 lex::Lex::IntNum(index) => Ok(Statement::Reference {
-                    rvalue: Value::Index(data, interpret_intnum(&index)),
+                    rvalue: Value::Index(data, interpret_intnum(&index)?),
                 }),
                 lex::Lex::Qi2Yu2 => Ok(Statement::ReferenceWhatIsLeft { data }),
                 lex::Lex::Identifier(ident) => Ok(Statement::Reference {
@@ -593,7 +625,8 @@ fn parse_if_statement_after_zhe3(mut iter: &mut LexIter<'_>) -> Result<IfStmtAft
                                 elsecase: parse_after_ruo4fei1(&mut iter)?,
                             });
                         }
-                        _ => unreachable!(),
+                        None => return Err(Error::UnexpectedEOF),
+                        Some(_) => return Err(Error::SomethingWentWrong(here!())),
                     }
                     condstmt_vec.push(parse_elseif(&mut iter)?);
                 }
@@ -710,25 +743,42 @@ fn parse_arraycat_after_xian2(mut iter: &mut LexIter<'_>) -> Result<Statement, E
     }
 }
 
-fn parse_statement(mut iter: &mut LexIter<'_>) -> Result<Statement, Error> {
+/// The statements of a loop, up to its `云云`
+fn parse_loop_body(mut iter: &mut LexIter<'_>) -> Result<Vec<Statement>, Error> {
+    iter.loops += 1;
+    let mut stmts = vec![];
+    while !matches!(
+        iter.peek().ok_or(Error::UnexpectedEOF)?,
+        lex::Lex::Yun2Yun2OrYe3(_)
+    ) {
+        stmts.push(parse_statement(&mut iter)?);
+    }
+    iter.next();
+    iter.loops -= 1;
+    Ok(stmts)
+}
+
+fn parse_statement(iter: &mut LexIter<'_>) -> Result<Statement, Error> {
+    let index = iter.ranges.len();
+    iter.ranges.push(iter.consumed..iter.consumed);
+    let st = parse_statement_inner(iter)?;
+    iter.ranges[index].end = iter.consumed;
+    Ok(st)
+}
+
+fn parse_statement_inner(mut iter: &mut LexIter<'_>) -> Result<Statement, Error> {
     match iter.next().ok_or(Error::UnexpectedEOF)? {
+        lex::Lex::Nai3Zhi3Shi4Bian4 | lex::Lex::Nai3Zhi3 if iter.loops == 0 => {
+            Err(Error::JumpOutsideLoop)
+        }
         lex::Lex::Nai3Zhi3Shi4Bian4 => Ok(Statement::Continue),
         lex::Lex::Nai3Zhi3 => Ok(Statement::Break),
         lex::Lex::Fan2 => {
             let list = parse_identifier(&mut iter)?;
             if let lex::Lex::Zhong1Zhi1 = iter.next().ok_or(Error::UnexpectedEOF)? {
                 let elem = parse_identifier(&mut iter)?;
-                let mut stmts = vec![];
-                loop {
-                    if let lex::Lex::Yun2Yun2OrYe3(_) = iter
-                        .peek()
-                        .ok_or_else(|| Error::SomethingWentWrong(here!()))?
-                    {
-                        iter.next();
-                        return Ok(Statement::ForArr { list, elem, stmts });
-                    }
-                    stmts.push(parse_statement(&mut iter)?);
-                }
+                let stmts = parse_loop_body(&mut iter)?;
+                Ok(Statement::ForArr { list, elem, stmts })
             } else {
                 Err(Error::SomethingWentWrong(here!()))
             }
@@ -788,23 +838,14 @@ fn parse_statement(mut iter: &mut LexIter<'_>) -> Result<Statement, Error> {
             math: MathKind::ArithUnaryMath(OrQi2::from(&parse_ident_or_qi2(&mut iter)?)),
         }),
         lex::Lex::You3 => parse_init_define_statement_after_you3(&mut iter),
-        lex::Lex::Heng2Wei2Shi4 => {
-            let mut statements = vec![];
-            loop {
-                match **iter.peek().ok_or(Error::UnexpectedEOF)? {
-                    lex::Lex::Yun2Yun2OrYe3(_) => {
-                        iter.next();
-                        return Ok(Statement::Loop { statements });
-                    }
-                    _ => statements.push(parse_statement(&mut iter)?),
-                }
-            }
-        }
+        lex::Lex::Heng2Wei2Shi4 => Ok(Statement::Loop {
+            statements: parse_loop_body(&mut iter)?,
+        }),
         lex::Lex::Wei2Shi4 => parse_for_enum_statement_after_wei2shi4(&mut iter),
         lex::Lex::Shu1Zhi1 => Ok(Statement::Print),
         lex::Lex::Xi1Zhi1 => parse_assign_after_xi1zhi1(&mut iter),
         lex::Lex::Wu2You3 => parse_after_wu2you3(&mut iter),
-        a => unimplemented!("Parser encountered {:?}", a),
+        a => Err(Error::UnsupportedStatement(a.clone())),
     }
 }
 
@@ -839,50 +880,48 @@ fn parse_after_fu2(mut iter: &mut LexIter<'_>) -> Result<Statement, Error> {
 fn parse_after_wu2you3(mut iter: &mut LexIter<'_>) -> Result<Statement, Error> {
     let next = iter.next().ok_or(Error::UnexpectedEOF)?;
     match next {
-        lex::Lex::IntNum(num) => {
-            match iter.next().ok_or(Error::UnexpectedEOF)? {
-                lex::Lex::Type(t) => {
-                    use std::convert::TryFrom;
+        lex::Lex::IntNum(num) => match iter.next().ok_or(Error::UnexpectedEOF)? {
+            lex::Lex::Type(t) => {
+                use std::convert::TryFrom;
 
-                    let mut ans = vec![];
-                    let vec = loop {
-                        if iter.peek() != Some(&&lex::Lex::Yue1) {
-                            break ans;
-                        }
-                        iter.next();
-                        let data = parse_data(&mut iter)?;
-                        ans.push(data);
-                    };
-
-                    let variable_count = match usize::try_from(interpret_intnum(num)) {
-                        Err(_) => return Err(Error::InvalidVariableCount),
-                        Ok(a) => a,
-                    };
-
-                    if variable_count == 0 {
-                        return Err(Error::InvalidVariableCount);
+                let mut ans = vec![];
+                let vec = loop {
+                    if iter.peek() != Some(&&lex::Lex::Yue1) {
+                        break ans;
                     }
+                    iter.next();
+                    let data = parse_data(&mut iter)?;
+                    ans.push(data);
+                };
 
-                    let declare = DeclareStatement {
-                        how_many_variables: variable_count as usize,
-                        type_: *t,
-                        data_arr: vec,
-                    };
-
-                    if let Some(lex::Lex::Ming2Zhi1) = iter.peek() {
-                        iter.next();
-                        let idents = parse_name_multi_statement_after_ming2zhi1(&mut iter)?;
-                        Ok(Statement::Define {
-                            decl: declare,
-                            idents,
-                        })
-                    } else {
-                        Ok(Statement::Declare(declare))
-                    }
+                let variable_count = match usize::try_from(interpret_intnum(num)?) {
+                    Err(_) => return Err(Error::InvalidVariableCount),
+                    Ok(a) => a,
+                };
+
+                if variable_count == 0 {
+                    return Err(Error::InvalidVariableCount);
+                }
+
+                let declare = DeclareStatement {
+                    how_many_variables: variable_count as usize,
+                    type_: *t,
+                    data_arr: vec,
+                };
+
+                if let Some(lex::Lex::Ming2Zhi1) = iter.peek() {
+                    iter.next();
+                    let idents = parse_name_multi_statement_after_ming2zhi1(&mut iter)?;
+                    Ok(Statement::Define {
+                        decl: declare,
+                        idents,
+                    })
+                } else {
+                    Ok(Statement::Declare(declare))
                 }
-                _ => unimplemented!(), // 術, 物
             }
-        }
+            _ => Err(Error::Unsupported("吾有 ... 術 and 物")),
+        },
         _ => Err(Error::SomethingWentWrong(here!())),
     }
 }
@@ -907,15 +946,40 @@ fn parse_name_multi_statement_after_ming2zhi1(
 }
 
 use peek_nth::IteratorExt;
-pub fn parse(lex: &[lex::Lex]) -> Result<Vec<Statement>, Error> {
-    let mut iter = lex.iter().peekable_nth();
+
+/// Like `parse`, also giving the token range of each statement, including those in blocks, in the order they begin;
+/// that is, each is followed by those of its blocks (see `Statement::blocks`).
+/// An error comes with the index of the token at which it was found, which is `lex.len()` at the end.
+pub fn parse_with_ranges(
+    lex: &[lex::Lex],
+) -> Result<(Vec<Statement>, Vec<Range<usize>>), (Error, usize)> {
+    let mut iter = LexIter {
+        iter: lex.iter().peekable_nth(),
+        consumed: 0,
+        loops: 0,
+        ranges: vec![],
+    };
 
     let mut ans = vec![];
-    loop {
-        if iter.peek().is_none() {
-            return Ok(ans);
+    while iter.peek().is_some() {
+        match parse_statement(&mut iter) {
+            Ok(st) => ans.push(st),
+            Err(e) => {
+                // the token just read, unless the end was reached
+                let at = match e {
+                    Error::UnexpectedEOF => lex.len(),
+                    _ => iter.consumed.saturating_sub(1),
+                };
+                return Err((e, at));
+            }
         }
+    }
+    Ok((ans, iter.ranges))
+}
 
-        ans.push(parse_statement(&mut iter)?);
+pub fn parse(lex: &[lex::Lex]) -> Result<Vec<Statement>, Error> {
+    match parse_with_ranges(lex) {
+        Ok((stmts, _)) => Ok(stmts),
+        Err((e, _)) => Err(e),
     }
 }
@@ -0,0 +1,620 @@
+use crate::ir;
+use crate::lex;
+use crate::parse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// 2^53; up to this, every integer is exactly an `f64`, so `i64` arithmetic gives what `f64` arithmetic would
+const MAX_SAFE: f64 = 9_007_199_254_740_992.0;
+
+/// No list or string of a transpiled program comes near 2^48 elements, which would be 2 PiB of `f64`
+const MAX_LEN: f64 = 281_474_976_710_656.0;
+
+/// A counted loop up to this many times is followed iteration by iteration; others are widened
+const MAX_UNROLL: i64 = 256;
+
+/// How many iterations of a loop are joined before the bounds still moving are widened
+const WIDEN_AFTER: usize = 3;
+
+/// The integers from `lo` to `hi`, and `-0` if `neg_zero`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Range {
+    lo: f64,
+    hi: f64,
+    neg_zero: bool,
+}
+
+impl Range {
+    fn exactly(n: f64) -> Self {
+        Range {
+            lo: n,
+            hi: n,
+            neg_zero: false,
+        }
+    }
+
+    fn has_zero(self) -> bool {
+        self.lo <= 0.0 && 0.0 <= self.hi || self.neg_zero
+    }
+
+    /// Whether it may be negative or `-0`
+    fn has_negative(self) -> bool {
+        self.lo < 0.0 || self.neg_zero
+    }
+
+    fn join(self, other: Self) -> Self {
+        Range {
+            lo: self.lo.min(other.lo),
+            hi: self.hi.max(other.hi),
+            neg_zero: self.neg_zero || other.neg_zero,
+        }
+    }
+
+    fn widen(self, next: Self) -> Self {
+        Range {
+            lo: if next.lo < self.lo {
+                f64::NEG_INFINITY
+            } else {
+                self.lo
+            },
+            hi: if next.hi > self.hi {
+                f64::INFINITY
+            } else {
+                self.hi
+            },
+            neg_zero: self.neg_zero || next.neg_zero,
+        }
+    }
+
+    /// `None` if it may leave the integers exactly representable, or be `-0`, which prints differently from `0`
+    fn checked(self) -> Option<Self> {
+        if -MAX_SAFE <= self.lo && self.hi <= MAX_SAFE && !self.neg_zero {
+            Some(self)
+        } else {
+            None
+        }
+    }
+}
+
+/// What may be known of a value
+#[derive(Debug, Clone, PartialEq)]
+enum Abs {
+    /// no value at all, e.g. the elements of an empty list
+    Nothing,
+    Num(Range),
+    List(Box<Abs>),
+    /// a string, a boolean or `undefined`
+    Other,
+    /// values of different types, which would not compile anyway
+    Any,
+}
+
+impl Abs {
+    fn join(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Abs::Nothing, x) | (x, Abs::Nothing) => x.clone(),
+            (Abs::Num(a), Abs::Num(b)) => Abs::Num(a.join(*b)),
+            (Abs::List(a), Abs::List(b)) => Abs::List(Box::new(a.join(b))),
+            (Abs::Other, Abs::Other) => Abs::Other,
+            _ => Abs::Any,
+        }
+    }
+
+    fn widen(&self, next: &Self) -> Self {
+        match (self, next) {
+            (Abs::Num(a), Abs::Num(b)) => Abs::Num(a.widen(*b)),
+            (Abs::List(a), Abs::List(b)) => Abs::List(Box::new(a.widen(b))),
+            _ => self.join(next),
+        }
+    }
+
+    fn num(&self) -> Option<Range> {
+        match self {
+            Abs::Num(r) => Some(*r),
+            _ => None,
+        }
+    }
+
+    fn elems(&self) -> Option<&Abs> {
+        match self {
+            Abs::List(elems) => Some(elems),
+            _ => None,
+        }
+    }
+}
+
+type Vars<K> = HashMap<K, Abs>;
+
+type Scopes = Vec<Vars<parse::Identifier>>;
+
+/// A variable known on one side only keeps what is known of it
+fn join_vars<K: Clone + Eq + Hash>(a: &Vars<K>, b: &Vars<K>) -> Vars<K> {
+    let mut joined = a.clone();
+    for (k, v) in b {
+        let v = match a.get(k) {
+            Some(w) => w.join(v),
+            None => v.clone(),
+        };
+        joined.insert(k.clone(), v);
+    }
+    joined
+}
+
+fn widen_vars<K: Clone + Eq + Hash>(a: &Vars<K>, next: &Vars<K>) -> Vars<K> {
+    next.iter()
+        .map(|(k, v)| {
+            let v = match a.get(k) {
+                Some(w) => w.widen(v),
+                None => v.clone(),
+            };
+            (k.clone(), v)
+        })
+        .collect()
+}
+
+fn join_scopes(a: &Scopes, b: &Scopes) -> Scopes {
+    a.iter().zip(b).map(|(a, b)| join_vars(a, b)).collect()
+}
+
+fn widen_scopes(a: &Scopes, next: &Scopes) -> Scopes {
+    a.iter()
+        .zip(next)
+        .map(|(a, next)| widen_vars(a, next))
+        .collect()
+}
+
+/// The state at some point of the program; `None` where it is not reached, e.g. after `乃止`
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    scopes: Scopes,
+    /// the values of the temporaries pushed so far
+    temps: Vars<ir::Temp>,
+}
+
+impl State {
+    fn join(&self, other: &Self) -> Self {
+        State {
+            scopes: join_scopes(&self.scopes, &other.scopes),
+            temps: join_vars(&self.temps, &other.temps),
+        }
+    }
+
+    fn widen(&self, next: &Self) -> Self {
+        State {
+            scopes: widen_scopes(&self.scopes, &next.scopes),
+            temps: widen_vars(&self.temps, &next.temps),
+        }
+    }
+}
+
+fn join_reached(a: Option<State>, b: Option<State>) -> Option<State> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.join(&b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// How many times the body of a loop is run, if not left by `乃止`
+#[derive(Clone, Copy)]
+enum Count {
+    Exactly(i64),
+    /// as many times as the bound read on every iteration, which is at most the given one
+    AtMost(i64),
+    /// any number of times, including none
+    Unknown,
+    /// `恆為是`
+    Forever,
+}
+
+/// The states at `乃止` and `乃止是遍` in the innermost loop, cut to the scopes outside of it
+struct Jumps {
+    depth: usize,
+    breaks: Option<State>,
+    continues: Option<State>,
+}
+
+/// Follows the ops over ranges of values rather than values, as running the program would follow them.
+/// Every method returns `None` as soon as some number cannot be shown to be an integer in range.
+struct Analysis {
+    /// `None` where the program point is not reached
+    state: Option<State>,
+    jumps: Vec<Jumps>,
+}
+
+/// Whether every number that the program computes is provably an integer whose magnitude is at most 2^53 and which is not `-0`,
+/// so that the program gives the same output with `i64` as with `f64`.
+/// This is not the case as soon as there is `除`, a `NaN` from `其` on an empty stack, or a loop whose count is unknown doing arithmetic.
+pub fn all_integers(program: &ir::Program) -> bool {
+    let mut analysis = Analysis {
+        state: Some(State {
+            scopes: vec![HashMap::new()],
+            temps: HashMap::new(),
+        }),
+        jumps: vec![],
+    };
+    analysis.exec_ops(&program.ops).is_some()
+}
+
+impl Analysis {
+    fn state(&mut self) -> &mut State {
+        self.state
+            .as_mut()
+            .expect("only reached statements are analysed")
+    }
+
+    fn declare(&mut self, ident: &parse::Identifier, value: Abs) {
+        self.state()
+            .scopes
+            .last_mut()
+            .unwrap()
+            .insert(ident.clone(), value);
+    }
+
+    fn lookup_mut(&mut self, ident: &parse::Identifier) -> Option<&mut Abs> {
+        self.state()
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(ident))
+    }
+
+    fn lookup(&mut self, ident: &parse::Identifier) -> Option<Abs> {
+        self.lookup_mut(ident).map(|v| v.clone())
+    }
+
+    fn eval_data(&mut self, data: &parse::Data) -> Option<Abs> {
+        match data {
+            parse::Data::StringLiteral(_) | parse::Data::BoolValue(_) => Some(Abs::Other),
+            parse::Data::Identifier(ident) => self.lookup(ident),
+            #[allow(clippy::cast_precision_loss)]
+            parse::Data::IntNum(n) => Range::exactly(*n as f64).checked().map(Abs::Num),
+        }
+    }
+
+    /// `其` on an empty stack is `NaN`
+    fn eval_operand(&mut self, a: &ir::Operand) -> Option<Abs> {
+        match a {
+            ir::Operand::Data(data) => self.eval_data(data),
+            ir::Operand::Temp(temp) => self.state().temps.get(temp).cloned(),
+            ir::Operand::Undefined => None,
+        }
+    }
+
+    fn eval_value(&mut self, value: &parse::Value<ir::Operand>) -> Option<Abs> {
+        match value {
+            parse::Value::Simple(a) => self.eval_operand(a),
+            parse::Value::Length(a) => {
+                self.eval_operand(a)?;
+                Some(Abs::Num(Range {
+                    lo: 0.0,
+                    hi: MAX_LEN,
+                    neg_zero: false,
+                }))
+            }
+            parse::Value::Index(a, _) => Some(self.eval_operand(a)?.elems()?.clone()),
+            parse::Value::IndexByIdent(a, ident) => {
+                let list = self.eval_operand(a)?;
+                self.lookup(ident)?.num()?;
+                Some(list.elems()?.clone())
+            }
+        }
+    }
+
+    fn eval_cond(&mut self, cond: &ir::Cond) -> Option<()> {
+        match cond {
+            ir::Cond::Unary(a) => self.eval_value(a)?,
+            ir::Cond::Binary(left, _, right) => {
+                self.eval_value(left)?;
+                self.eval_value(right)?
+            }
+            ir::Cond::Not(a) => self.eval_operand(a)?,
+        };
+        Some(())
+    }
+
+    /// The list of `充` and `銜`, which must be a variable
+    fn list_ident(a: &ir::Operand) -> Option<&parse::Identifier> {
+        match a {
+            ir::Operand::Data(parse::Data::Identifier(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+
+    fn eval_expr(&mut self, expr: &ir::Expr) -> Option<Abs> {
+        match expr {
+            ir::Expr::Init(_, Some(data)) => self.eval_data(data),
+            ir::Expr::Init(type_, None) => Some(match type_ {
+                lex::Type::Shu4 => Abs::Num(Range::exactly(0.0)),
+                lex::Type::Lie4 => Abs::List(Box::new(Abs::Nothing)),
+                lex::Type::Yan2 | lex::Type::Yao2 => Abs::Other,
+            }),
+            ir::Expr::Value(v) => self.eval_value(v),
+            ir::Expr::Binary(op, a, b) => {
+                let (a, b) = (self.eval_operand(a)?, self.eval_operand(b)?);
+                match *op {
+                    "&&" | "||" => Some(Abs::Other),
+                    op => arith(op, a.num()?, b.num()?).map(Abs::Num),
+                }
+            }
+            ir::Expr::Not(a) => {
+                self.eval_operand(a)?;
+                Some(Abs::Other)
+            }
+            ir::Expr::Rest(a) => {
+                let v = self.eval_operand(a)?;
+                v.elems()?;
+                Some(v)
+            }
+            ir::Expr::Concat(a, elems) => {
+                let mut result = self.lookup(Self::list_ident(a)?)?;
+                for e in elems {
+                    result = result.join(&self.lookup(e)?);
+                }
+                result.elems()?;
+                Some(result)
+            }
+        }
+    }
+
+    /// An element of a list is joined to what the list may already hold
+    fn assign(&mut self, lvalue: &parse::Lvalue, value: Abs) -> Option<()> {
+        let ident = match lvalue {
+            parse::Lvalue::Simple(ident) => {
+                *self.lookup_mut(ident)? = value;
+                return Some(());
+            }
+            parse::Lvalue::Index(ident, _) => ident,
+            parse::Lvalue::IndexByIdent(ident, index) => {
+                self.lookup(index)?.num()?;
+                ident
+            }
+        };
+        let list = self.lookup_mut(ident)?;
+        *list = Abs::List(Box::new(list.elems()?.join(&value)));
+        Some(())
+    }
+
+    /// Runs `ops` in a new scope, after declaring `binding` (the element of `ForEach`) in it
+    fn exec_block(
+        &mut self,
+        binding: Option<(&parse::Identifier, Abs)>,
+        ops: &[ir::Op],
+    ) -> Option<()> {
+        self.state().scopes.push(HashMap::new());
+        if let Some((ident, value)) = binding {
+            self.declare(ident, value);
+        }
+        self.exec_ops(ops)?;
+        if let Some(state) = &mut self.state {
+            state.scopes.pop();
+        }
+        Some(())
+    }
+
+    fn exec_ops(&mut self, ops: &[ir::Op]) -> Option<()> {
+        for op in ops {
+            if self.state.is_none() {
+                // after `乃止` or `乃止是遍`
+                break;
+            }
+            self.exec(op)?;
+        }
+        Some(())
+    }
+
+    /// Leaves the innermost loop, for its end if `to_end`, or for its next iteration
+    fn jump(&mut self, to_end: bool) {
+        let mut state = self.state.take().unwrap();
+        let jumps = self
+            .jumps
+            .last_mut()
+            .expect("the parser only allows 乃止 in a loop");
+        state.scopes.truncate(jumps.depth);
+        let target = if to_end {
+            &mut jumps.breaks
+        } else {
+            &mut jumps.continues
+        };
+        *target = join_reached(target.take(), Some(state));
+    }
+
+    /// Runs the body of a loop once from `head`, giving the state at the start of the next iteration and that of `乃止`
+    fn iterate(
+        &mut self,
+        head: &State,
+        binding: Option<(&parse::Identifier, Abs)>,
+        ops: &[ir::Op],
+    ) -> Option<(Option<State>, Option<State>)> {
+        self.state = Some(head.clone());
+        self.jumps.push(Jumps {
+            depth: head.scopes.len(),
+            breaks: None,
+            continues: None,
+        });
+        self.exec_block(binding, ops)?;
+        let jumps = self.jumps.pop().unwrap();
+        let next = join_reached(self.state.take(), jumps.continues);
+        Some((next, jumps.breaks))
+    }
+
+    fn exec_loop(
+        &mut self,
+        count: Count,
+        binding: Option<(&parse::Identifier, Abs)>,
+        ops: &[ir::Op],
+    ) -> Option<()> {
+        let entry = self.state.take().unwrap();
+        let mut exit = None;
+        match count {
+            Count::Exactly(n) | Count::AtMost(n) if n <= MAX_UNROLL => {
+                let mut head = Some(entry);
+                for _ in 0..n {
+                    let current = match head {
+                        Some(current) => current,
+                        None => break,
+                    };
+                    if let Count::AtMost(_) = count {
+                        exit = join_reached(exit, Some(current.clone()));
+                    }
+                    let (next, breaks) = self.iterate(&current, binding.clone(), ops)?;
+                    exit = join_reached(exit, breaks);
+                    if next.as_ref() == Some(&current) {
+                        head = next;
+                        break;
+                    }
+                    head = next;
+                }
+                exit = join_reached(exit, head);
+            }
+            _ => {
+                // `head` grows from `entry` to what holds at the start of every iteration
+                let mut head = entry;
+                for i in 0.. {
+                    let (next, breaks) = self.iterate(&head, binding.clone(), ops)?;
+                    exit = join_reached(exit, breaks);
+                    let joined = match next {
+                        Some(next) => next.join(&head),
+                        None => break,
+                    };
+                    if joined == head {
+                        break;
+                    }
+                    head = if i < WIDEN_AFTER {
+                        joined
+                    } else {
+                        head.widen(&joined)
+                    };
+                }
+                if let Count::Exactly(_) | Count::AtMost(_) | Count::Unknown = count {
+                    exit = join_reached(exit, Some(head));
+                }
+            }
+        }
+        self.state = exit;
+        Some(())
+    }
+
+    fn exec(&mut self, op: &ir::Op) -> Option<()> {
+        match op {
+            ir::Op::Push(temp, expr) => {
+                let v = self.eval_expr(expr)?;
+                self.state().temps.insert(*temp, v);
+            }
+            ir::Op::Pop { .. }
+            | ir::Op::Print(_)
+            | ir::Op::Flush(_)
+            | ir::Op::Spill(_)
+            | ir::Op::PrintSpilled(_)
+            | ir::Op::DropSpilled
+            | ir::Op::Enter(_)
+            | ir::Op::Restore(_)
+            | ir::Op::Leave
+            | ir::Op::Unmark(_)
+            | ir::Op::Origin(_) => {}
+            // what was on a runtime stack is not followed
+            ir::Op::Unspill(_) => return None,
+            ir::Op::Name(named) => {
+                for (ident, temp) in named {
+                    let v = match temp {
+                        Some(temp) => self.eval_operand(&ir::Operand::Temp(*temp))?,
+                        // `undefined`
+                        None => Abs::Other,
+                    };
+                    self.declare(ident, v);
+                }
+            }
+            ir::Op::Declare(ident, expr) => {
+                let v = self.eval_expr(expr)?;
+                self.declare(ident, v);
+            }
+            ir::Op::Assign(lvalue, rvalue) => {
+                let v = self.eval_value(rvalue)?;
+                self.assign(lvalue, v)?;
+            }
+            ir::Op::Fill(list, elems) => {
+                let mut joined = Abs::Nothing;
+                for e in elems {
+                    joined = joined.join(&self.eval_data(e)?);
+                }
+                let list = self.lookup_mut(Self::list_ident(list)?)?;
+                *list = Abs::List(Box::new(list.elems()?.join(&joined)));
+            }
+            ir::Op::If { branches, else_ } => {
+                // each branch starts from the state after the conditions, and the others fall through to the next condition
+                let mut joined = None;
+                for branch in branches {
+                    self.exec_ops(&branch.pops)?;
+                    self.eval_cond(&branch.cond)?;
+                    let fallthrough = self.state.clone();
+                    self.exec_block(None, &branch.body)?;
+                    joined = join_reached(joined, self.state.take());
+                    self.state = fallthrough;
+                }
+                self.exec_block(None, else_)?;
+                self.state = join_reached(joined, self.state.take());
+            }
+            ir::Op::Repeat(num, body) => self.exec_loop(Count::Exactly(*num), None, body)?,
+            ir::Op::RepeatBy(bound, body) => {
+                let range = self.eval_operand(bound)?.num()?;
+                let count = match bound {
+                    // the bound is read again on every iteration
+                    ir::Operand::Data(parse::Data::Identifier(i)) if ir::assigns(body, i) => {
+                        Count::Unknown
+                    }
+                    // the bounds are integers
+                    #[allow(clippy::float_cmp, clippy::cast_possible_truncation)]
+                    _ if range.lo == range.hi => Count::Exactly(range.hi as i64),
+                    #[allow(clippy::cast_possible_truncation)]
+                    _ => Count::AtMost(range.hi as i64),
+                };
+                self.exec_loop(count, None, body)?;
+            }
+            ir::Op::ForEach { list, elem, body } => {
+                let elems = self.lookup(list)?.elems()?.clone();
+                self.exec_loop(Count::Unknown, Some((elem, elems)), body)?;
+            }
+            ir::Op::Loop(body) => self.exec_loop(Count::Forever, None, body)?,
+            ir::Op::Break => self.jump(true),
+            ir::Op::Continue => self.jump(false),
+        }
+        Some(())
+    }
+}
+
+/// `op` of integers in the ranges, as in `ir::Expr::Binary`; `/` may not give an integer
+fn arith(op: &str, a: Range, b: Range) -> Option<Range> {
+    let r = match op {
+        "+" => Range {
+            lo: a.lo + b.lo,
+            hi: a.hi + b.hi,
+            neg_zero: a.neg_zero && b.neg_zero,
+        },
+        "-" => Range {
+            lo: a.lo - b.hi,
+            hi: a.hi - b.lo,
+            neg_zero: a.neg_zero && b.has_zero(),
+        },
+        "*" => {
+            let corners = [a.lo * b.lo, a.lo * b.hi, a.hi * b.lo, a.hi * b.hi];
+            Range {
+                lo: corners.iter().copied().fold(f64::INFINITY, f64::min),
+                hi: corners.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+                neg_zero: a.has_zero() && b.has_negative() || a.has_negative() && b.has_zero(),
+            }
+        }
+        // `% 0` is `NaN`, and `%` keeps the sign of the dividend, even when the result is `0`
+        "%" => {
+            if b.has_zero() {
+                return None;
+            }
+            let m = b.lo.abs().max(b.hi.abs()) - 1.0;
+            Range {
+                lo: a.lo.max(-m).min(0.0),
+                hi: a.hi.min(m).max(0.0),
+                neg_zero: a.has_negative(),
+            }
+        }
+        _ => return None,
+    };
+    r.checked()
+}
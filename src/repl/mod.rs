@@ -0,0 +1,206 @@
+use crate::compile;
+use crate::cst;
+use crate::diagnostic::Diagnostic;
+use crate::eval;
+use crate::lex;
+use crate::parse;
+use crate::romanize::Romanizer;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = ". ";
+
+const HELP: &str = "\
+:rust   toggles showing the Rust code each input transpiles to,
+        after the earlier lines that it changes
+:stack  shows the values not yet named
+:vars   shows the variables
+:help   shows this message
+:quit   exits (so does EOF)";
+
+/// The input read so far may still be completed by the following lines
+fn is_incomplete_lex(e: &lex::Error) -> bool {
+    match e {
+        lex::Error::UnexpectedEOFAfter(_)
+        | lex::Error::NonterminatedIdentifier
+        | lex::Error::NonterminatedStringLiteral => true,
+        lex::Error::UnexpectedCharAfter(..)
+        | lex::Error::EmptyIdentifier
+        | lex::Error::UnrecognizedCharacter(_) => false,
+    }
+}
+
+pub struct Repl<'a, W: Write> {
+    interpreter: eval::Interpreter<W>,
+    /// every statement accepted so far; transpiled as a whole, so that the names and the `_ans`s stay consistent
+    history: Vec<parse::Statement>,
+    romanizer: &'a dyn Romanizer,
+    pub options: compile::Options,
+    pub show_rust: bool,
+    /// the Rust lines shown for each statement, by its index in `cst::preorder`
+    shown: HashMap<usize, Vec<String>>,
+}
+
+impl<'a, W: Write> Repl<'a, W> {
+    pub fn new(out: W, romanizer: &'a dyn Romanizer) -> Self {
+        Repl {
+            interpreter: eval::Interpreter::new(out),
+            history: vec![],
+            romanizer,
+            options: compile::Options::default(),
+            show_rust: false,
+            shown: HashMap::new(),
+        }
+    }
+
+    fn out(&mut self) -> &mut W {
+        self.interpreter.output_mut()
+    }
+
+    fn command(&mut self, cmd: &str) -> std::io::Result<bool> {
+        match cmd {
+            ":quit" | ":q" => return Ok(false),
+            ":rust" => {
+                self.show_rust = !self.show_rust;
+                let state = if self.show_rust { "on" } else { "off" };
+                writeln!(self.out(), "showing Rust: {}", state)?;
+            }
+            ":stack" => {
+                let stack = self
+                    .interpreter
+                    .stack()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                writeln!(self.out(), "[{}]", stack)?;
+            }
+            ":vars" => {
+                let mut vars = self
+                    .interpreter
+                    .variables()
+                    .iter()
+                    .map(|(parse::Identifier(name), v)| format!("「{}」 = {}", name, v))
+                    .collect::<Vec<_>>();
+                vars.sort();
+                for v in vars {
+                    writeln!(self.out(), "{}", v)?;
+                }
+            }
+            _ => writeln!(self.out(), "{}", HELP)?,
+        }
+        Ok(true)
+    }
+
+    /// The Rust code for `self.history[from..]`.
+    /// A later statement may change how the earlier ones are written, such as `jia3` for `_jia3` once it is read,
+    /// or `let mut` once it is assigned to; the lines shown before that now differ come first.
+    fn transpiled_since(&mut self, from: usize) -> Result<String, compile::Error> {
+        let (src, origins) =
+            compile::compile_with_origins(&self.history, self.romanizer, &self.options)?;
+        let lines = src
+            .lines()
+            .zip(origins)
+            .filter_map(|(line, origin)| Some((origin?, format!("{}\n", line))))
+            .collect::<Vec<_>>();
+        let mut by_origin = HashMap::<usize, Vec<String>>::new();
+        for (origin, line) in &lines {
+            by_origin.entry(*origin).or_default().push(line.clone());
+        }
+
+        // the origins count the statements inside blocks as well
+        let from = cst::preorder(&self.history[..from]).len();
+        let changed = |i: &usize| {
+            *i < from && matches!(self.shown.get(i), Some(shown) if shown != &by_origin[i])
+        };
+        let mut ans = String::new();
+        if lines.iter().any(|(i, _)| changed(i)) {
+            ans.push_str("// changed:\n");
+            for (_, line) in lines.iter().filter(|(i, _)| changed(i)) {
+                ans.push_str(line);
+            }
+            ans.push_str("// new:\n");
+        }
+        for (_, line) in lines.iter().filter(|(i, _)| *i >= from) {
+            ans.push_str(line);
+        }
+
+        for (i, lines) in by_origin {
+            if i >= from || self.shown.contains_key(&i) {
+                self.shown.insert(i, lines);
+            }
+        }
+        Ok(ans)
+    }
+
+    /// Returns `false` if `buf` is not yet a complete sequence of statements
+    fn submit(&mut self, buf: &str) -> std::io::Result<bool> {
+        let lex = match lex::lex(buf) {
+            Ok(lex) => lex,
+            Err(e) if is_incomplete_lex(&e) => return Ok(false),
+            Err(e) => {
+                writeln!(self.out(), "{}", Diagnostic::from(e))?;
+                return Ok(true);
+            }
+        };
+        let parsed = match parse::parse(&lex) {
+            Ok(parsed) => parsed,
+            Err(parse::Error::UnexpectedEOF) => return Ok(false),
+            Err(e) => {
+                writeln!(self.out(), "{}", Diagnostic::from(e))?;
+                return Ok(true);
+            }
+        };
+
+        let from = self.history.len();
+        for st in parsed {
+            if let Err(e) = self.interpreter.run(std::slice::from_ref(&st)) {
+                writeln!(self.out(), "runtime error: {:?}", e)?;
+                break;
+            }
+            self.history.push(st);
+        }
+        if self.show_rust && self.history.len() > from {
+            match self.transpiled_since(from) {
+                Ok(rust) => write!(self.out(), "{}", rust)?,
+                Err(e) => writeln!(self.out(), "{}", Diagnostic::from(e))?,
+            }
+        }
+        Ok(true)
+    }
+
+    /// Reads statements until EOF or `:quit`. A statement may span several lines, e.g. a `為是` block up to its `云云`.
+    pub fn run<R: BufRead>(&mut self, input: R) -> std::io::Result<()> {
+        let mut buf = String::new();
+        let mut lines = input.lines();
+        loop {
+            let prompt = if buf.is_empty() {
+                PROMPT
+            } else {
+                CONTINUATION_PROMPT
+            };
+            write!(self.out(), "{}", prompt)?;
+            self.out().flush()?;
+
+            let line = match lines.next() {
+                None => break,
+                Some(line) => line?,
+            };
+            if buf.is_empty() && line.trim().starts_with(':') {
+                if self.command(line.trim())? {
+                    continue;
+                }
+                break;
+            }
+
+            buf.push_str(&line);
+            buf.push('\n');
+            if self.submit(&buf)? {
+                buf.clear();
+            }
+        }
+        writeln!(self.out())?;
+        Ok(())
+    }
+}
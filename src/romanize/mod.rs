@@ -0,0 +1,297 @@
+use crate::parse;
+use std::collections::HashMap;
+use std::path::Path;
+use unicode_xid::UnicodeXID;
+
+#[derive(Debug)]
+pub enum Error {
+    UnknownScheme(String),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+}
+
+/// Turns a wenyan identifier into a Rust one.
+/// Unless `crate_attributes` says otherwise, the result is a valid snake_case identifier:
+/// the readings of the characters joined by `_`, such as `ding1_zheng1`.
+pub trait Romanizer {
+    /// The reading of a single character in lowercase ASCII letters and digits, such as `jia3`; `None` if unknown
+    fn syllable(&self, c: char) -> Option<String>;
+
+    /// Runs of ASCII letters and digits are kept (lowercased) as a single word;
+    /// characters without a reading are escaped by their codepoint, such as `u7532`.
+    fn romanize(&self, ident: &parse::Identifier) -> String {
+        let parse::Identifier(i) = ident;
+        let mut words = vec![];
+        let mut ascii = String::new();
+        for c in i.chars() {
+            if c.is_ascii_alphanumeric() {
+                ascii.push(c.to_ascii_lowercase());
+                continue;
+            }
+            if !ascii.is_empty() {
+                words.push(std::mem::take(&mut ascii));
+            }
+            words.push(
+                self.syllable(c)
+                    .unwrap_or_else(|| format!("u{:x}", c as u32)),
+            );
+        }
+        if !ascii.is_empty() {
+            words.push(ascii);
+        }
+        let ans = words.join("_");
+        if ans.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("_{}", ans)
+        } else {
+            ans
+        }
+    }
+
+    /// Put at the top of the output, for names that are not snake_case
+    fn crate_attributes(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// `jia3`
+    Pinyin,
+    /// `jia`
+    PinyinToneless,
+    /// `chia3`; derived from pinyin, with the apostrophes and the umlauts dropped
+    WadeGiles,
+    /// `gaap3`; the bundled table only has the common characters, the others are escaped
+    Jyutping,
+    /// `u7532`
+    Hex,
+    /// `甲`, as long as Rust accepts the characters; pinyin otherwise
+    Hanzi,
+}
+
+impl std::str::FromStr for Scheme {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "pinyin" => Ok(Scheme::Pinyin),
+            "pinyin-toneless" => Ok(Scheme::PinyinToneless),
+            "wade-giles" => Ok(Scheme::WadeGiles),
+            "jyutping" => Ok(Scheme::Jyutping),
+            "hex" => Ok(Scheme::Hex),
+            "hanzi" => Ok(Scheme::Hanzi),
+            _ => Err(Error::UnknownScheme(s.to_string())),
+        }
+    }
+}
+
+/// The names accepted by `Scheme::from_str`
+pub const SCHEME_NAMES: [&str; 6] = [
+    "pinyin",
+    "pinyin-toneless",
+    "wade-giles",
+    "jyutping",
+    "hex",
+    "hanzi",
+];
+
+/// Reads a table in the format of the bundled `hanzi2roman-map-pinyin.json`:
+/// from the hex codepoint (e.g. `"7532"`) to the space-separated readings (e.g. `"JIA3"`)
+pub fn read_table(path: &Path) -> Result<HashMap<String, String>, Error> {
+    let json = std::fs::read_to_string(path).map_err(Error::Io)?;
+    serde_json::from_str(&json).map_err(Error::Json)
+}
+
+/// `table` replaces the bundled table of the scheme if given, such as with a fuller one of Jyutping readings
+pub fn romanizer(
+    scheme: Scheme,
+    table: Option<HashMap<String, String>>,
+) -> Result<Box<dyn Romanizer + Sync>, Error> {
+    let table = match (scheme, table) {
+        (Scheme::Hex, _) => return Ok(Box::new(Hex)),
+        (_, Some(table)) => table,
+        (Scheme::Jyutping, None) => crate::jyutping_table(),
+        (_, None) => crate::pinyin_table(),
+    };
+    Ok(match scheme {
+        Scheme::Pinyin | Scheme::Jyutping => Box::new(Table::new(table, true)),
+        Scheme::PinyinToneless => Box::new(Table::new(table, false)),
+        Scheme::WadeGiles => Box::new(WadeGiles(Table::new(table, true))),
+        Scheme::Hanzi => Box::new(Hanzi(Table::new(table, true))),
+        Scheme::Hex => unreachable!(),
+    })
+}
+
+/// Every character is escaped by its codepoint
+pub struct Hex;
+
+impl Romanizer for Hex {
+    fn syllable(&self, _: char) -> Option<String> {
+        None
+    }
+}
+
+/// Takes the first reading in the table
+pub struct Table {
+    table: HashMap<String, String>,
+    keep_tones: bool,
+}
+
+impl Table {
+    pub fn new(table: HashMap<String, String>, keep_tones: bool) -> Self {
+        Table { table, keep_tones }
+    }
+
+    /// The default: the bundled pinyin table, with tones
+    pub fn pinyin() -> Self {
+        Table::new(crate::pinyin_table(), true)
+    }
+}
+
+impl Romanizer for Table {
+    fn syllable(&self, c: char) -> Option<String> {
+        let readings = self.table.get(&format!("{:X}", c as u32))?;
+        let first = readings.split_whitespace().next()?.to_ascii_lowercase();
+        if first.is_empty() || !first.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        if self.keep_tones {
+            Some(first)
+        } else {
+            Some(split_tone(&first).0.to_string())
+        }
+    }
+}
+
+/// Keeps the identifier as is, since Rust accepts XID_Start/XID_Continue characters in identifiers;
+/// the other characters are romanized by the inner table.
+pub struct Hanzi(Table);
+
+impl Romanizer for Hanzi {
+    fn syllable(&self, c: char) -> Option<String> {
+        self.0.syllable(c)
+    }
+
+    fn romanize(&self, ident: &parse::Identifier) -> String {
+        let parse::Identifier(i) = ident;
+        let mut ans = String::new();
+        // whether `ans` ends with a romanized character, which must be separated from what follows
+        let mut after_romanized = false;
+        for c in i.chars() {
+            if UnicodeXID::is_xid_continue(c) && c != '_' {
+                if after_romanized {
+                    ans.push('_');
+                }
+                ans.push(c);
+                after_romanized = false;
+            } else {
+                if !ans.is_empty() {
+                    ans.push('_');
+                }
+                ans.push_str(
+                    &self
+                        .syllable(c)
+                        .unwrap_or_else(|| format!("u{:x}", c as u32)),
+                );
+                after_romanized = true;
+            }
+        }
+        match ans.chars().next() {
+            Some(c) if UnicodeXID::is_xid_start(c) => ans,
+            _ => format!("_{}", ans),
+        }
+    }
+
+    fn crate_attributes(&self) -> Option<&'static str> {
+        Some("#![allow(non_snake_case, uncommon_codepoints, mixed_script_confusables, confusable_idents)]")
+    }
+}
+
+/// `jia3` into `("jia", "3")`
+fn split_tone(syllable: &str) -> (&str, &str) {
+    let body = syllable.trim_end_matches(|c: char| c.is_ascii_digit());
+    (body, &syllable[body.len()..])
+}
+
+/// Converts the readings of a pinyin table
+pub struct WadeGiles(Table);
+
+impl Romanizer for WadeGiles {
+    fn syllable(&self, c: char) -> Option<String> {
+        self.0.syllable(c).map(|s| pinyin_to_wade_giles(&s))
+    }
+}
+
+/// Works on a single lowercase syllable with an optional tone number, `ü` written as `v`.
+/// Since the result must be ASCII, `p'` becomes `p` and `ü` becomes `u`.
+fn pinyin_to_wade_giles(syllable: &str) -> String {
+    let (body, tone) = split_tone(syllable);
+
+    // syllables that do not follow the rules below
+    let whole = match body {
+        "zhi" | "chi" => Some("chih"),
+        "shi" => Some("shih"),
+        "ri" => Some("jih"),
+        "zi" | "ci" => Some("tzu"),
+        "si" => Some("ssu"),
+        "er" => Some("erh"),
+        "e" => Some("o"),
+        "yi" => Some("i"),
+        "you" => Some("yu"),
+        "yan" => Some("yen"),
+        "ye" => Some("yeh"),
+        "yue" => Some("yueh"),
+        "yong" => Some("yung"),
+        _ => None,
+    };
+    if let Some(w) = whole {
+        return format!("{}{}", w, tone);
+    }
+
+    const INITIALS: [(&str, &str); 23] = [
+        ("zh", "ch"),
+        ("ch", "ch"),
+        ("sh", "sh"),
+        ("b", "p"),
+        ("p", "p"),
+        ("m", "m"),
+        ("f", "f"),
+        ("d", "t"),
+        ("t", "t"),
+        ("n", "n"),
+        ("l", "l"),
+        ("g", "k"),
+        ("k", "k"),
+        ("h", "h"),
+        ("j", "ch"),
+        ("q", "ch"),
+        ("x", "hs"),
+        ("r", "j"),
+        ("z", "ts"),
+        ("c", "ts"),
+        ("s", "s"),
+        ("y", "y"),
+        ("w", "w"),
+    ];
+    let (initial, wg_initial) = INITIALS
+        .iter()
+        .find(|(p, _)| body.starts_with(p))
+        .copied()
+        .unwrap_or(("", ""));
+    let final_ = &body[initial.len()..];
+    let velar = matches!(initial, "g" | "k" | "h");
+    let retroflex = matches!(initial, "zh" | "ch" | "sh");
+
+    let wg_final = match final_ {
+        "ong" => "ung",
+        "iong" => "iung",
+        "ian" => "ien",
+        "ie" => "ieh",
+        "ue" | "ve" => "ueh",
+        "ui" => "uei",
+        "e" if velar => "o",
+        "uo" if !velar && !retroflex => "o",
+        "v" => "u",
+        f => f,
+    };
+    format!("{}{}{}", wg_initial, wg_final, tone)
+}
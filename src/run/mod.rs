@@ -0,0 +1,147 @@
+use crate::cst::Cst;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    /// `rustc` rejected the generated code; the messages have already been reported on stderr
+    RustcFailed,
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// A fresh directory under the system temp dir; removed on drop
+pub struct TempDir(PathBuf);
+
+static TEMP_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+impl TempDir {
+    pub fn new() -> std::io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "wenyan-to-rust-{}-{}",
+            std::process::id(),
+            TEMP_DIR_COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        std::fs::create_dir_all(&path)?;
+        Ok(TempDir(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The wenyan program that the Rust code was transpiled from, for rustc's errors to point back at it
+pub struct Origins<'a> {
+    /// the name of the `.wy` file, `-` for stdin
+    pub path: &'a str,
+    pub cst: &'a Cst,
+    /// for each line of the Rust code, the statement it comes from (see `compile::compile_with_origins`)
+    pub lines: &'a [Option<usize>],
+}
+
+impl Origins<'_> {
+    /// `file:line:col` of the statement that line `line` (1-indexed) of the Rust code comes from,
+    /// and its text; that of a block stops where the block does
+    fn locate(&self, line: usize) -> Option<(String, &str)> {
+        let i = (*self.lines.get(line.checked_sub(1)?)?)?;
+        let node = self.cst.preorder()[i];
+        let span = self.cst.span(node);
+        let end = match node.children.first() {
+            Some(child) => self.cst.span(child).start,
+            None => span.end,
+        };
+        let before = &self.cst.src[..span.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let location = format!(
+            "{}:{}:{}",
+            self.path,
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1
+        );
+        Some((location, self.cst.src[span.start..end].trim_end()))
+    }
+}
+
+/// Uses `$RUSTC` if set, so that the same toolchain as cargo's is picked up
+fn rustc_command() -> Command {
+    Command::new(std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc")))
+}
+
+/// Compiles `src` with the local `rustc` into `dir`, returning the path to the binary.
+/// The errors are reported on stderr; each one is followed by where in `origins` the offending line comes from.
+pub fn rustc(src: &str, origins: Option<&Origins>, dir: &Path) -> Result<PathBuf, Error> {
+    let src_path = dir.join("main.rs");
+    let bin_path = dir.join("main");
+    std::fs::write(&src_path, src)?;
+
+    let output = rustc_command()
+        .arg("--edition=2018")
+        .arg("--error-format=json")
+        .arg("-A")
+        .arg("warnings")
+        .arg("-o")
+        .arg(&bin_path)
+        .arg(&src_path)
+        .output()?;
+
+    if output.status.success() {
+        return Ok(bin_path);
+    }
+
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        let diagnostic: serde_json::Value = match serde_json::from_str(line) {
+            Ok(d) => d,
+            Err(_) => {
+                eprintln!("{}", line);
+                continue;
+            }
+        };
+        if let Some(rendered) = diagnostic["rendered"].as_str() {
+            eprint!("{}", rendered);
+        }
+        if let Some(origins) = origins {
+            report_origin(&diagnostic, origins);
+        }
+    }
+    Err(Error::RustcFailed)
+}
+
+fn report_origin(diagnostic: &serde_json::Value, origins: &Origins) {
+    let spans = match diagnostic["spans"].as_array() {
+        Some(spans) => spans,
+        None => return,
+    };
+    for span in spans {
+        if span["is_primary"].as_bool() != Some(true) {
+            continue;
+        }
+        let located = span["line_start"]
+            .as_u64()
+            .and_then(|line| origins.locate(line as usize));
+        if let Some((location, text)) = located {
+            eprintln!("note: generated from {}:\n    {}\n", location, text);
+        }
+    }
+}
+
+/// Transpiled code goes through `rustc` in a temporary directory, and the resulting binary is run with `args`.
+/// Returns the exit code of the program.
+pub fn run(src: &str, origins: Option<&Origins>, args: &[&str]) -> Result<i32, Error> {
+    let dir = TempDir::new()?;
+    let bin = rustc(src, origins, dir.path())?;
+    let status = Command::new(bin).args(args).status()?;
+    Ok(status.code().unwrap_or(1))
+}
@@ -0,0 +1,377 @@
+use crate::lex;
+use crate::parse::{self, Data, IfCond, Lvalue, MathKind, OrQi2, Statement, UnaryIfExpr, Value};
+
+/// Writes `stmts` back as wenyan, one statement per line and each block indented by a full-width space `　`,
+/// so that `parse` gives `stmts` again. A number that `parse` cannot read (e.g. `一百零五`) is still written as is usual.
+pub fn unparse(stmts: &[Statement]) -> String {
+    unparse_with_comments(stmts, &[], &[])
+}
+
+/// Like `unparse`, also writing the comments (e.g. `批曰「「...」」`), each on its own line and ended by `。` as the statements are:
+/// `comments[i]` before the `i`th statement in the order of `cst::preorder`, and `trailing` at the end
+pub fn unparse_with_comments(
+    stmts: &[Statement],
+    comments: &[Vec<String>],
+    trailing: &[String],
+) -> String {
+    let mut out = Writer {
+        text: String::new(),
+        comments,
+        next_statement: 0,
+    };
+    write_block(&mut out, stmts, 0);
+    for comment in trailing {
+        write_comment(&mut out, 0, comment);
+    }
+    out.text
+}
+
+struct Writer<'a> {
+    text: String,
+    comments: &'a [Vec<String>],
+    /// the index in preorder of the next statement to write
+    next_statement: usize,
+}
+
+fn write_block(out: &mut Writer<'_>, stmts: &[Statement], indent: usize) {
+    for st in stmts {
+        if let Some(comments) = out.comments.get(out.next_statement) {
+            for comment in comments {
+                write_comment(out, indent, comment);
+            }
+        }
+        out.next_statement += 1;
+        write_statement(out, st, indent);
+    }
+}
+
+fn write_comment(out: &mut Writer<'_>, indent: usize, comment: &str) {
+    line(out, indent, &format!("{}。", comment));
+}
+
+fn line(out: &mut Writer<'_>, indent: usize, text: &str) {
+    out.text.push_str(&"　".repeat(indent));
+    out.text.push_str(text);
+    out.text.push('\n');
+}
+
+/// `text`, then the block, then `云云。`
+fn block(out: &mut Writer<'_>, indent: usize, text: &str, stmts: &[Statement]) {
+    line(out, indent, text);
+    write_block(out, stmts, indent + 1);
+    line(out, indent, "云云。");
+}
+
+#[allow(clippy::too_many_lines)]
+fn write_statement(out: &mut Writer<'_>, st: &Statement, indent: usize) {
+    match st {
+        Statement::Declare(decl) => line(out, indent, &declare(decl)),
+        Statement::Define { decl, idents } => {
+            line(
+                out,
+                indent,
+                &format!("{}{}", declare(decl), name_multi(idents)),
+            );
+        }
+        Statement::InitDefine { type_, data, name } => line(
+            out,
+            indent,
+            &format!(
+                "有{}{}。名之曰{}。",
+                type_name(*type_),
+                self::data(data),
+                ident(name)
+            ),
+        ),
+        Statement::NameMulti { idents } => line(out, indent, &name_multi(idents)),
+        Statement::Print => line(out, indent, "書之。"),
+        Statement::Flush => line(out, indent, "噫。"),
+        Statement::Break => line(out, indent, "乃止。"),
+        Statement::Continue => line(out, indent, "乃止是遍。"),
+        Statement::ForEnum { num, statements } => {
+            block(
+                out,
+                indent,
+                &format!("為是{}遍。", number(*num)),
+                statements,
+            );
+        }
+        Statement::ForEnumIdent { ident, statements } => block(
+            out,
+            indent,
+            &format!("為是{}遍。", ident_or_qi2(ident)),
+            statements,
+        ),
+        Statement::ForArr { list, elem, stmts } => block(
+            out,
+            indent,
+            &format!("凡{}中之{}。", self::ident(list), self::ident(elem)),
+            stmts,
+        ),
+        Statement::Loop { statements } => block(out, indent, "恆為是。", statements),
+        Statement::If {
+            ifcase: (cond, stmts),
+            elseifcases,
+            elsecase,
+        } => {
+            line(out, indent, &if_cond(cond, "若"));
+            write_block(out, stmts, indent + 1);
+            for (cond, stmts) in elseifcases {
+                line(out, indent, &if_cond(cond, "或若"));
+                write_block(out, stmts, indent + 1);
+            }
+            if !elsecase.is_empty() {
+                line(out, indent, "若非。");
+                write_block(out, elsecase, indent + 1);
+            }
+            line(out, indent, "云云。");
+        }
+        Statement::Math { math } => line(out, indent, &self::math(math)),
+        Statement::Assignment { lvalue, rvalue } => {
+            let lvalue = match lvalue {
+                Lvalue::Simple(i) => ident(i),
+                Lvalue::Index(i, index) => format!("{}之{}", ident(i), number(*index)),
+                Lvalue::IndexByIdent(i, index) => format!("{}之{}", ident(i), ident(index)),
+            };
+            line(
+                out,
+                indent,
+                &format!("昔之{}者。今{}是矣。", lvalue, value(rvalue, data_or_qi2)),
+            );
+        }
+        Statement::Reference { rvalue } => {
+            line(out, indent, &format!("夫{}。", value(rvalue, data)));
+        }
+        Statement::ReferenceWhatIsLeft { data } => {
+            line(out, indent, &format!("夫{}之其餘。", self::data(data)));
+        }
+        Statement::ArrayFill {
+            what_to_fill,
+            elems,
+        } => line(
+            out,
+            indent,
+            &format!(
+                "充{}{}。",
+                ident_or_qi2(what_to_fill),
+                elems
+                    .iter()
+                    .map(|e| format!("以{}", data(e)))
+                    .collect::<String>()
+            ),
+        ),
+        Statement::ArrayCat { append_to, elems } => line(
+            out,
+            indent,
+            &format!(
+                "銜{}{}。",
+                ident_or_qi2(append_to),
+                elems
+                    .iter()
+                    .map(|e| format!("以{}", ident(e)))
+                    .collect::<String>()
+            ),
+        ),
+    }
+}
+
+fn declare(decl: &parse::DeclareStatement) -> String {
+    #[allow(clippy::cast_possible_wrap)]
+    let count = number(decl.how_many_variables as i64);
+    format!(
+        "吾有{}{}。{}",
+        count,
+        type_name(decl.type_),
+        decl.data_arr
+            .iter()
+            .map(|d| format!("曰{}。", data(d)))
+            .collect::<String>()
+    )
+}
+
+fn name_multi(idents: &[parse::Identifier]) -> String {
+    format!(
+        "名之{}。",
+        idents
+            .iter()
+            .map(|i| format!("曰{}", ident(i)))
+            .collect::<String>()
+    )
+}
+
+fn math(math: &MathKind) -> String {
+    match math {
+        MathKind::ArithBinaryMath(op, a, prep, b) => format!(
+            "{}{}{}{}。",
+            match op {
+                lex::ArithBinaryOp::Jia1 => "加",
+                lex::ArithBinaryOp::Jian3 => "減",
+                lex::ArithBinaryOp::Cheng2 => "乘",
+            },
+            data_or_qi2(a),
+            preposition(*prep),
+            data_or_qi2(b)
+        ),
+        MathKind::ModMath(op, a, prep, b) => format!(
+            "除{}{}{}。{}",
+            data_or_qi2(a),
+            preposition(*prep),
+            data_or_qi2(b),
+            match op {
+                parse::DivBinaryOp::Div => "",
+                parse::DivBinaryOp::Mod => "所餘幾何。",
+            }
+        ),
+        MathKind::ArithUnaryMath(a) => format!("變{}。", data_or_qi2(a)),
+        MathKind::BooleanAlgebra(a, b, op) => format!(
+            "夫{}{}{}。",
+            ident(a),
+            ident(b),
+            match op {
+                lex::LogicBinaryOp::Zhong1You3Yang2Hu1 => "中有陽乎",
+                lex::LogicBinaryOp::Zhong1Wu2Yin1Hu1 => "中無陰乎",
+            }
+        ),
+    }
+}
+
+/// `若 ... 者。` or `或若 ... 者。`; only `若` has the forms `若其然者` and `若其不然者`,
+/// so that `parse` never gives `IfCond::NotQi2` after `或若`
+fn if_cond(cond: &IfCond, keyword: &str) -> String {
+    match cond {
+        IfCond::NotQi2 => format!("{}其不然者。", keyword),
+        IfCond::Unary(UnaryIfExpr::Simple(OrQi2::Qi2)) if keyword == "若" => {
+            String::from("若其然者。")
+        }
+        IfCond::Unary(a) => format!("{}{}者。", keyword, unary_if_expr(a)),
+        IfCond::Binary(a, op, b) => format!(
+            "{}{}{}{}者。",
+            keyword,
+            unary_if_expr(a),
+            match op {
+                lex::IfLogicOp::Deng3Yu2 => "等於",
+                lex::IfLogicOp::Bu4Deng3Yu2 => "不等於",
+                lex::IfLogicOp::Bu4Da4Yu2 => "不大於",
+                lex::IfLogicOp::Bu4Xiao3Yu2 => "不小於",
+                lex::IfLogicOp::Da4Yu2 => "大於",
+                lex::IfLogicOp::Xiao3Yu2 => "小於",
+            },
+            unary_if_expr(b)
+        ),
+    }
+}
+
+fn unary_if_expr(a: &UnaryIfExpr) -> String {
+    match a {
+        UnaryIfExpr::Simple(a) => data_or_qi2(a),
+        UnaryIfExpr::Complex(v) => value(v, data),
+    }
+}
+
+fn value<T>(v: &Value<T>, write: fn(&T) -> String) -> String {
+    match v {
+        Value::Simple(a) => write(a),
+        Value::Index(a, index) => format!("{}之{}", write(a), number(*index)),
+        Value::IndexByIdent(a, index) => format!("{}之{}", write(a), ident(index)),
+        Value::Length(a) => format!("{}之長", write(a)),
+    }
+}
+
+fn preposition(prep: lex::Preposition) -> &'static str {
+    match prep {
+        lex::Preposition::Yi3 => "以",
+        lex::Preposition::Yu2 => "於",
+    }
+}
+
+/// `數`, `列`, `言` or `爻`
+pub fn type_name(t: lex::Type) -> &'static str {
+    match t {
+        lex::Type::Shu4 => "數",
+        lex::Type::Lie4 => "列",
+        lex::Type::Yan2 => "言",
+        lex::Type::Yao2 => "爻",
+    }
+}
+
+fn ident(parse::Identifier(name): &parse::Identifier) -> String {
+    format!("「{}」", name)
+}
+
+fn ident_or_qi2(a: &OrQi2<parse::Identifier>) -> String {
+    match a {
+        OrQi2::NotQi2(i) => ident(i),
+        OrQi2::Qi2 => String::from("其"),
+    }
+}
+
+fn data_or_qi2(a: &OrQi2<Data>) -> String {
+    match a {
+        OrQi2::NotQi2(d) => data(d),
+        OrQi2::Qi2 => String::from("其"),
+    }
+}
+
+fn data(d: &Data) -> String {
+    match d {
+        Data::StringLiteral(s) => format!("「「{}」」", s),
+        Data::BoolValue(true) => String::from("陽"),
+        Data::BoolValue(false) => String::from("陰"),
+        Data::Identifier(i) => ident(i),
+        Data::IntNum(n) => number(*n),
+    }
+}
+
+const DIGITS: [char; 10] = ['零', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+
+/// The usual way of writing `n`: `十五`, `一百二十`, `一萬零三`
+pub fn number(n: i64) -> String {
+    if n < 0 {
+        return format!("負{}", number_abs(n.unsigned_abs()));
+    }
+    #[allow(clippy::cast_sign_loss)]
+    number_abs(n as u64)
+}
+
+fn number_abs(n: u64) -> String {
+    match n {
+        0 => return String::from("零"),
+        // `parse` reads `千` but not `一千`
+        1000 => return String::from("千"),
+        _ => {}
+    }
+    // groups of four digits, the lowest first
+    let mut groups = vec![];
+    let mut rest = n;
+    while rest > 0 {
+        groups.push(rest % 10000);
+        rest /= 10000;
+    }
+    let mut out = String::new();
+    // a zero has been skipped after some digit, and is to be written as `零` before the next one
+    let mut zero = false;
+    for (i, &group) in groups.iter().enumerate().rev() {
+        for (place, unit) in ["千", "百", "十", ""].iter().enumerate() {
+            let d = group / 10_u64.pow(3 - place as u32) % 10;
+            if d == 0 {
+                zero = !out.is_empty();
+                continue;
+            }
+            if zero {
+                out.push('零');
+                zero = false;
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            out.push(DIGITS[d as usize]);
+            out.push_str(unit);
+        }
+        if group != 0 {
+            out.push_str(["", "萬", "億", "兆", "京"][i]);
+        }
+    }
+    // `十五` rather than `一十五`
+    match out.strip_prefix("一十") {
+        Some(rest) => format!("十{}", rest),
+        None => out,
+    }
+}
@@ -1,7 +0,0 @@
-fn main() {
-    let JIA3 = 3.0;
-    println!("");
-    let YI3 = 5.0;
-    let _ans1 = YI3;
-    println!("{}", _ans1);
-}
@@ -1,18 +0,0 @@
-fn main() {
-    let JIA3 = 3.0;
-    let _ans1 = 9.0;
-    let _ans2 = 7.0;
-    println!("{} {}", _ans1, _ans2);
-    let YI3 = 3.0;
-    let BING3 = 9.0;
-    let _ans3 = 7.0;
-    println!("{}", _ans3);
-    let DING1 = 3.0;
-    let WU4 = 9.0;
-    let JI3 = 7.0;
-    println!("");
-    let GENG1 = 3.0;
-    let XIN1 = 9.0;
-    let REN2 = 0.0;
-    println!("");
-}
@@ -1,9 +0,0 @@
-fn main() {
-    let JIA3 = 3.0;
-    let mut _rand1 = 0.0;
-    while _rand1 < JIA3 {
-        let _ans1 = "問天地好在";
-        println!("{}", _ans1);
-        _rand1 += 1.0;
-    }
-}
@@ -1,5 +0,0 @@
-fn main() {
-    let JIA3 = 3.0;
-    let YI3 = false;
-    let BING3 = "噫吁戲";
-}
@@ -1,6 +0,0 @@
-fn main() {
-    let ZUO4_ = 3.0;
-    println!("");
-    let ZUO4__ = 5.0;
-    println!("");
-}
@@ -1,5 +0,0 @@
-fn main() {
-    let JIA3 = 3.0;
-    let YI3 = 9.0;
-    let BING3 = 27.0;
-}
@@ -1,4 +0,0 @@
-fn main() {
-    let JIA3 = 3.0;
-    let JIA3 = 3.0;
-}
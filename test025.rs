@@ -1,5 +0,0 @@
-fn main() {
-    let JIA3 = 0.0;
-    let JIA3 = 0.0;
-    let JIA3 = 0.0;
-}
@@ -1,7 +0,0 @@
-fn main() {
-    let mut JIA3 = 0.0;
-    let mut JIA3 = 0.0;
-    let mut JIA3 = 0.0;
-    JIA3 = 4.0;
-    YI3 = JIA3;
-}
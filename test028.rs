@@ -1,4 +0,0 @@
-fn main() {
-    let _ans1 = 1.0 + 3.0;
-    let BING3 = _ans1;
-}
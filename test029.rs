@@ -1,6 +0,0 @@
-fn main() {
-    let _ans1 = 1.0 + 3.0;
-    let _ans2 = 6.0 + 9.0;
-    let JIA3 = _ans1;
-    let YI3 = _ans2;
-}
@@ -1,15 +0,0 @@
-fn main() {
-    let _ans1 = 2.0 + 3.0;
-    let _ans2 = 1.0 + 3.0;
-    let _ans3 = 3.0 + 3.0;
-    let BING3 = _ans2;
-    let DING1 = _ans3;
-    println!("{}", _ans1);
-    let _ans4 = 2.0 + 3.0;
-    let _ans5 = 1.0 + 3.0;
-    let _ans6 = 3.0 + 3.0;
-    let WU4 = _ans4;
-    let JI3 = _ans5;
-    let GENG1 = _ans6;
-    println!("");
-}
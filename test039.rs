@@ -1,12 +0,0 @@
-fn main() {
-    let _ans1 = 0.0 - 4.0;
-    let FU4SI4 = _ans1;
-    let _ans2 = 0.0 - 9.0;
-    let FU4JIU3 = _ans2;
-    let _ans3 = 9.0 % FU4SI4;
-    println!("{}", _ans3);
-    let _ans4 = FU4JIU3 % 4.0;
-    println!("{}", _ans4);
-    let _ans5 = FU4JIU3 % FU4SI4;
-    println!("{}", _ans5);
-}
@@ -1,6 +0,0 @@
-fn main() {
-    let JIA3 = false;
-    let YI3 = true;
-    let _ans1 = JIA3 || YI3;
-    println!("{}", _ans1);
-}
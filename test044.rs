@@ -1,12 +0,0 @@
-fn main() {
-    let TOU2 = 35.0;
-    let ZU2 = 94.0;
-    let _ans1 = ZU2 / 2.0;
-    let _ans2 = _ans1 - TOU2;
-    let TU4 = _ans2;
-    let _ans3 = TOU2 - TU4;
-    let ZHI4 = _ans3;
-    let _ans4 = ZHI4;
-    let _ans5 = TU4;
-    println!("{} {}", _ans4, _ans5);
-}
@@ -1,29 +0,0 @@
-fn main() {
-    let TOU2 = 340.0;
-    let BI4 = 32.0;
-    let ZU2 = 216.0;
-    let WEI3 = 417.0;
-    let _ans1 = BI4 / 2.0;
-    let XING2TIAN1 = _ans1;
-    let _ans2 = XING2TIAN1 * 2.0;
-    let _ans3 = ZU2 - _ans2;
-    let _ans4 = _ans3 / 4.0;
-    let XU3HU2HE2 = _ans4;
-    let _ans5 = XU3HU2HE2 * 10.0;
-    let SHI2BEI4XU3HU2HE2 = _ans5;
-    let _ans6 = TOU2 + WEI3;
-    let _ans7 = _ans6 - SHI2BEI4XU3HU2HE2;
-    let _ans8 = _ans7 / 3.0;
-    let LIANG3TOU2SHE2 = _ans8;
-    let _ans9 = WEI3 - LIANG3TOU2SHE2;
-    let _ans10 = _ans9 - XU3HU2HE2;
-    let _ans11 = _ans10 / 8.0;
-    let JIU3WEI3HU2 = _ans11;
-    let _ans12 = XU3HU2HE2 - JIU3WEI3HU2;
-    let XU3DE2LA1 = _ans12;
-    let _ans13 = XU3DE2LA1;
-    let _ans14 = JIU3WEI3HU2;
-    let _ans15 = XING2TIAN1;
-    let _ans16 = LIANG3TOU2SHE2;
-    println!("{} {} {} {}", _ans13, _ans14, _ans15, _ans16);
-}
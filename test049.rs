@@ -1,9 +0,0 @@
-fn main() {
-    let QI4LIANG4 = 10.0;
-    let mut JIU3LIANG4 = 9.0;
-    if JIU3LIANG4 > QI4LIANG4 {
-        JIU3LIANG4 = QI4LIANG4;
-    }
-    let _ans1 = JIU3LIANG4;
-    println!("{}", _ans1);
-}
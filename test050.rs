@@ -1,10 +0,0 @@
-fn main() {
-    let mut ZHU3 = 49.0;
-    if ZHU3 > 50.0 {
-        let _ans1 = ZHU3 + 1.0;
-        ZHU3 = _ans1;
-    } else {
-        let _ans2 = ZHU3 - 1.0;
-        ZHU3 = _ans2;
-    }
-}
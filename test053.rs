@@ -1,10 +0,0 @@
-fn main() {
-    let JIA3 = false;
-    let YI3 = true;
-    let _ans1 = JIA3 && YI3;
-    let BING3 = _ans1;
-    let _ans2 = JIA3 || YI3;
-    let BING3 = _ans2;
-    let _ans3 = !JIA3;
-    let BING3 = _ans3;
-}
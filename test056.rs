@@ -1,19 +0,0 @@
-fn main() {
-    let JIA3 = false;
-    let YI3 = false;
-    let _ans1 = 1.0 + 3.0;
-    let _ans2 = JIA3 || YI3;
-    let BING3 = _ans2;
-    let _ans3 = !BING3;
-    let DING1 = _ans3;
-    if DING1 {
-        let _ans4 = "古之人誠不我欺。";
-    }
-    println!("{} {}", _ans1, _ans4);
-    let _ans5 = 1.0 + 3.0;
-    let _ans6 = JIA3 || YI3;
-    if !_ans6 {
-        let _ans7 = "古之人誠不我欺。";
-    }
-    println!("{}", _ans7);
-}
@@ -1,5 +0,0 @@
-fn main() {
-    let JIA3 = vec![];
-    let _ans1 = vec![];
-    let YI3 = _ans1;
-}
@@ -1,4 +0,0 @@
-fn main() {
-    let mut LIE4 = vec![];
-    LIE4.append(&mut vec![1.0, 2.0, 3.0]);
-}
@@ -1,9 +0,0 @@
-fn main() {
-    let mut LIE4 = vec![];
-    LIE4.append(&mut vec![1.0, 2.0, 3.0]);
-    for YUAN2 in LIE4 {
-        let _ans1 = YUAN2;
-        let _ans2 = "者。亦列中之物也。";
-        println!("{} {}", _ans1, _ans2);
-    }
-}
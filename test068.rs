@@ -1,4 +0,0 @@
-fn main() {
-    let mut YI3 = vec![];
-    YI3.append(&mut vec![2.0, 9.0, 4.0, 22.0]);
-}
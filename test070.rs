@@ -1,11 +0,0 @@
-fn main() {
-    let mut JIA3 = vec![];
-    JIA3.push(3.0);
-    JIA3.push(5.0);
-    let mut YI3 = vec![];
-    YI3.append(&mut vec![2.0, 9.0, 4.0, 22.0]);
-    let _ans1 = 1.0 + 3.0;
-    let _ans2 = [&JIA3[..], &YI3[..]].concat();
-    let BING3 = _ans1;
-    let CHOU3 = _ans2;
-}
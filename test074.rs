@@ -1,12 +0,0 @@
-fn main() {
-    let _ans1 = JIA3;
-    JIA3.append(&mut vec![1.0, 2.0]);
-    let _ans2 = 1.0 + 3.0;
-    let _ans3 = JIA3[1 - 1];
-    let YI3 = _ans2;
-    let BING3 = _ans3;
-    let _ans4 = 2.0 + 4.0;
-    let _ans5 = BING3;
-    let WU4 = _ans4;
-    let JI3 = _ans5;
-}
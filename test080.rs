@@ -1,7 +0,0 @@
-fn main() {
-    let mut JIA3 = vec![];
-    JIA3.push(3.0);
-    JIA3.push(5.0);
-    let  _ans1 = &JIA3[1..].to_vec();
-    let YI3 = _ans1;
-}
@@ -1,9 +0,0 @@
-fn main() {
-    let mut JIA3 = vec![];
-    JIA3.push(3.0);
-    JIA3.push(5.0);
-    let mut YI3 = vec![];
-    for YUAN2 in JIA3 {
-        YI3.push(YUAN2);
-    }
-}
@@ -1,30 +0,0 @@
-fn main() {
-    let mut QI2SUO3 = vec![];
-    let mut SUO3NAN2 = vec![];
-    let mut QI2FANG1 = vec![];
-    let mut SUO3ZHAN1 = vec![];
-    let mut SUO3ZENG4 = vec![];
-    let mut SUO3BAO4 = vec![];
-    let mut SUO3GAN3 = vec![];
-    let mut SUO3SHANG1 = vec![];
-    QI2SUO3.append(&mut vec!["太山", "桂林", "漢陽", "雁門"]);
-    SUO3NAN2.append(&mut vec!["樑父艱", "湘水深", "隴阪長", "雪雰雰"]);
-    QI2FANG1.append(&mut vec!["東", "南", "西", "北"]);
-    SUO3ZHAN1.append(&mut vec!["翰", "襟", "裳", "巾"]);
-    SUO3ZENG4.append(&mut vec!["金錯刀", "琴琅玕", "貂襜褕", "錦繡段"]);
-    SUO3BAO4.append(&mut vec!["英瓊瑤", "雙玉盤", "明月珠", "青玉案"]);
-    SUO3GAN3.append(&mut vec!["逍遙", "惆悵", "踟躕", "增嘆"]);
-    SUO3SHANG1.append(&mut vec!["勞", "傷", "紆", "惋"]);
-    for DI4 in QI2SUO3 {
-        let _ans1 = DI4;
-        println!("{}", _ans1);
-    }
-    let _ans2 = "此皆有所思之地也";
-    println!("{}", _ans2);
-    for BAO3 in SUO3ZENG4 {
-        let _ans3 = BAO3;
-        println!("{}", _ans3);
-    }
-    let _ans4 = "此皆美人之所贈也";
-    println!("{}", _ans4);
-}
@@ -1,7 +0,0 @@
-fn main() {
-    let mut JIA3 = vec![];
-    JIA3.push(3.0);
-    JIA3.push(5.0);
-    let mut YI3 = 1.0;
-    YI3 = JIA3[(YI3 as usize) - 1];
-}
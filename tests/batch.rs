@@ -0,0 +1,50 @@
+//! Runs `wenyan-to-rust` on several inputs with `--out-dir`.
+
+use std::fs;
+use std::process::{Command, Output};
+use wenyan_to_rust::run::TempDir;
+
+fn batch(dir: &TempDir, inputs: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wenyan-to-rust"))
+        .args(inputs)
+        .arg("--out-dir")
+        .arg(dir.path().join("out"))
+        .current_dir(dir.path())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn each_input_is_written_to_its_stem() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("x.wy"), "吾有一數。曰三。書之。").unwrap();
+    fs::write(
+        dir.path().join("y.wy"),
+        "吾有一言。曰「「問天地好在。」」。書之。",
+    )
+    .unwrap();
+    let output = batch(&dir, &["x.wy", "y.wy"]);
+    assert!(output.status.success());
+    assert!(dir.path().join("out/x.rs").exists());
+    assert!(dir.path().join("out/y.rs").exists());
+}
+
+#[test]
+fn inputs_with_the_same_stem_are_rejected() {
+    let dir = TempDir::new().unwrap();
+    for sub in &["a", "b"] {
+        fs::create_dir(dir.path().join(sub)).unwrap();
+        fs::write(dir.path().join(sub).join("x.wy"), "吾有一數。曰三。書之。").unwrap();
+    }
+    let output = batch(&dir, &["a/x.wy", "b/x.wy"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("would both be written to"));
+    assert!(!dir.path().join("out/x.rs").exists());
+}
+
+#[test]
+fn stdin_is_read_once() {
+    let dir = TempDir::new().unwrap();
+    let output = batch(&dir, &["-", "-"]);
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,136 @@
+//! Runs `wenyan-to-rust --emit=...` and checks the JSON it writes.
+
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+const PROGRAM: &str = "\
+有數三。名之曰「甲」。
+為是「甲」遍。
+　批曰「「註」」。夫「甲」之長。書之。
+云云。
+";
+
+fn emit(kinds: &str, src: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wenyan-to-rust"))
+        .arg(format!("--emit={}", kinds))
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(src.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn emit_json(kinds: &str, src: &str) -> Value {
+    let output = emit(kinds, src);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn span(v: &Value) -> std::ops::Range<usize> {
+    let bound = |i: usize| v[i].as_u64().unwrap() as usize;
+    bound(0)..bound(1)
+}
+
+#[test]
+fn tokens() {
+    let out = emit_json("tokens", PROGRAM);
+    assert_eq!(out["version"], 1);
+    assert!(out.get("ast").is_none());
+
+    // the tokens and trivia cover the source exactly, in order
+    let mut covered = 0;
+    let mut check = |v: &Value| {
+        let span = span(v);
+        assert_eq!(span.start, covered);
+        covered = span.end;
+    };
+    for token in out["tokens"].as_array().unwrap() {
+        token["leading_trivia"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .for_each(|t| check(&t["span"]));
+        check(&token["span"]);
+        assert_eq!(token["text"], PROGRAM[span(&token["span"])]);
+    }
+    out["trailing_trivia"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .for_each(|t| check(&t["span"]));
+    assert_eq!(covered, PROGRAM.len());
+
+    let tokens = out["tokens"].as_array().unwrap();
+    assert_eq!(tokens[0]["kind"], "You3");
+    assert_eq!(tokens[2]["kind"], "IntNum");
+    assert_eq!(tokens[2]["value"], 3);
+    assert_eq!(tokens[5]["kind"], "Identifier");
+    assert_eq!(tokens[5]["text"], "「甲」");
+    assert_eq!(tokens[5]["value"], "甲");
+    let comment = tokens
+        .iter()
+        .flat_map(|t| t["leading_trivia"].as_array().unwrap())
+        .find(|t| t["kind"] == "comment")
+        .unwrap();
+    assert_eq!(&PROGRAM[span(&comment["span"])], "批曰「「註」」");
+}
+
+#[test]
+fn ast() {
+    let out = emit_json("ast,rust", PROGRAM);
+    assert!(out["rust"].as_str().unwrap().contains("fn main() {"));
+    let ast = &out["ast"];
+    assert_eq!(
+        ast[0],
+        json!({
+            "kind": "InitDefine",
+            "span": [0, 30],
+            "type": "數",
+            "value": { "kind": "Int", "value": 3 },
+            "name": "甲",
+        })
+    );
+    assert_eq!(ast[1]["kind"], "ForEnumIdent");
+    assert_eq!(
+        ast[1]["count"],
+        json!({ "kind": "Identifier", "name": "甲" })
+    );
+    let body = ast[1]["body"].as_array().unwrap();
+    assert_eq!(
+        body[0]["value"],
+        json!({ "kind": "Length", "of": { "kind": "Identifier", "name": "甲" } })
+    );
+    assert_eq!(&PROGRAM[span(&body[0]["span"])], "夫「甲」之長");
+    assert_eq!(body[1]["kind"], "Print");
+    assert_eq!(&PROGRAM[span(&body[1]["span"])], "書之");
+    let text = &PROGRAM[span(&ast[1]["span"])];
+    assert!(
+        text.starts_with("為是") && text.ends_with("云云"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn tokens_of_a_program_that_does_not_parse() {
+    let out = emit_json("tokens", "書之。若");
+    assert_eq!(out["tokens"][0]["kind"], "Shu1Zhi1");
+    assert_eq!(out["tokens"][1]["kind"], "Ruo4");
+
+    let output = emit("tokens,ast", "書之。若");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
@@ -0,0 +1,289 @@
+//! Lowers small programs and checks what each statement does to the stack, as `ir::listing` writes it.
+
+use wenyan_to_rust::{ir, lex, parse};
+
+fn listing(src: &str) -> String {
+    let lex = lex::lex(src).unwrap();
+    let parsed = parse::parse(&lex).unwrap();
+    ir::listing(&ir::lower(&parsed))
+}
+
+fn check(src: &str, expected: &str) {
+    assert_eq!(listing(src), expected, "{}", src);
+}
+
+#[test]
+fn qi2_takes_the_top_and_drops_the_rest() {
+    check(
+        "加一以三。加二以三。減其以其。書之。",
+        "\
+push t1 = 1 + 3
+push t2 = 2 + 3
+pop t2, drop t1
+push t3 = t2 - undefined
+print t3
+",
+    );
+}
+
+#[test]
+fn yu2_swaps_the_operands_but_qi2_is_taken_first() {
+    check(
+        "吾有一數。曰五。減其於十。",
+        "\
+push t1 = 數 5
+pop t1
+push t2 = 10 - t1
+flush t2
+",
+    );
+}
+
+#[test]
+fn name_matches_the_stack_from_the_end() {
+    check(
+        "加二以三。加一以三。加三以三。名之曰「甲」。名之曰「乙」。書之。",
+        "\
+push t1 = 2 + 3
+push t2 = 1 + 3
+push t3 = 3 + 3
+name 「甲」 = t3
+name 「乙」 = t2
+print t1
+",
+    );
+    check(
+        "加二以三。加一以三。名之曰「甲」。曰「乙」。曰「丙」。書之。",
+        "\
+push t1 = 2 + 3
+push t2 = 1 + 3
+name 「甲」 = undefined, 「乙」 = t1, 「丙」 = t2
+print
+",
+    );
+}
+
+#[test]
+fn define_names_the_first_values_and_pushes_the_others() {
+    check(
+        "吾有三數。曰三。曰九。名之曰「甲」。書之。",
+        "\
+declare 「甲」 = 數 3
+push t1 = 數 9
+push t2 = 數 default
+print t1 t2
+",
+    );
+}
+
+#[test]
+fn flush_empties_the_stack() {
+    check(
+        "吾有二言。曰「「甲」」。曰「「乙」」。噫。書之。",
+        "\
+push t1 = 言 \"甲\"
+push t2 = 言 \"乙\"
+flush t1 t2
+print
+",
+    );
+}
+
+#[test]
+fn conditions_pop_before_their_branch() {
+    check(
+        "加一以二。若其等於三者。書之。或若其者。書之。若非。書之。云云。",
+        "\
+push t1 = 1 + 2
+pop t1
+if t1 == 3
+    print
+else if undefined
+    print
+else
+    print
+end
+",
+    );
+}
+
+#[test]
+fn loops() {
+    check(
+        "吾有一數。曰三。名之曰「甲」。為是「甲」遍。夫「甲」。書之。云云。為是其遍。乃止。云云。",
+        "\
+declare 「甲」 = 數 3
+repeat 「甲」
+    push t1 = 「甲」
+    print t1
+end
+repeat undefined
+    break
+end
+",
+    );
+}
+
+#[test]
+fn a_loop_sees_the_values_before_it_on_every_iteration() {
+    check(
+        "加一以二。為是三遍。書之。云云。書之。",
+        "\
+push t1 = 1 + 2
+repeat 3
+    print t1
+end
+flush t1
+print
+",
+    );
+}
+
+#[test]
+fn where_the_ways_through_a_block_leave_different_values_they_go_on_a_runtime_stack() {
+    check(
+        "加一以二。加三以四。若陽者。名之曰「甲」。云云。書之。加五以六。為是三遍。加一以二。乃止。云云。加其以一。書之。",
+        "\
+push t1 = 1 + 2
+push t2 = 3 + 4
+spill t1 t2
+enter
+if true
+    unspill t3
+    name 「甲」 = t3
+end
+leave
+print spilled
+push t4 = 5 + 6
+repeat 3
+    push t5 = 1 + 2
+    break
+    flush t5
+end
+pop t4
+push t6 = t4 + 1
+print t6
+",
+    );
+}
+
+#[test]
+fn in_a_loop_every_iteration_starts_from_the_values_before_it() {
+    check(
+        "加一以二。為是三遍。若陽者。書之。云云。書之。云云。",
+        "\
+push t1 = 1 + 2
+spill t1
+enter b1
+repeat 3
+    restore b1
+    enter
+    if true
+        print spilled
+    end
+    leave
+    print spilled
+end
+leave
+",
+    );
+}
+
+#[test]
+fn a_runtime_stack_is_under_the_values_pushed_after_the_block() {
+    check(
+        "加一以二。若陽者。書之。云云。加三以四。若陽者。書之。云云。加其以一。書之。",
+        "\
+push t1 = 1 + 2
+spill t1
+enter
+if true
+    print spilled
+end
+leave
+push t2 = 3 + 4
+spill t2
+enter
+if true
+    print spilled
+end
+leave
+unspill t3
+drop spilled
+push t4 = t3 + 1
+print t4
+",
+    );
+}
+
+#[test]
+fn in_a_block_the_same_rule_holds_after_a_block() {
+    check(
+        "加五以五。為是一遍。吾有一爻。曰陰。名之曰「甲」。加一以二。若「甲」者。書之。云云。書之。云云。",
+        "\
+push t1 = 5 + 5
+spill t1
+enter b1
+repeat 1
+    restore b1
+    declare 「甲」 = 爻 false
+    push t2 = 1 + 2
+    spill t2
+    enter
+    if 「甲」
+        print spilled
+    end
+    leave
+    print spilled
+end
+leave
+",
+    );
+}
+
+#[test]
+fn a_jump_leaves_the_blocks_in_the_loop() {
+    check(
+        "加二以二。為是二遍。為是一遍。加一以二。若陽者。噫。乃止。云云。云云。書之。云云。",
+        "\
+push t1 = 2 + 2
+spill t1
+enter b1
+repeat 2
+    restore b1
+    enter b2
+    repeat 1
+        restore b2
+        push t2 = 1 + 2
+        spill t2
+        enter
+        if true
+            flush
+            drop spilled
+            unmark 1
+            break
+        end
+        leave
+    end
+    leave
+    print spilled
+end
+leave
+",
+    );
+}
+
+#[test]
+fn what_a_loop_pushes_is_not_named_after_it() {
+    check(
+        "為是「甲」遍。加一以二。云云。名之曰「乙」。書之。",
+        "\
+repeat 「甲」
+    push t1 = 1 + 2
+    flush t1
+end
+name 「乙」 = undefined
+print
+",
+    );
+}
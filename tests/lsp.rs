@@ -0,0 +1,169 @@
+//! Runs `wenyan-to-rust lsp` with a scripted session and checks what it answers.
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+
+const URI: &str = "file:///test.wy";
+
+/// `「甲」` is declared on line 0, and again inside the `若` on line 2
+const PROGRAM: &str = "\
+吾有一數。曰三。名之曰「甲」。
+若「甲」等於三者。
+　吾有一言。曰「「好」」。名之曰「甲」。
+　夫「甲」。書之。
+云云。
+夫「甲」。書之。
+";
+
+fn message(body: &Value) -> String {
+    let body = body.to_string();
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+fn request(id: u64, method: &str, params: Value) -> String {
+    message(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+}
+
+fn notification(method: &str, params: Value) -> String {
+    message(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+}
+
+fn at(line: u64, character: u64) -> Value {
+    json!({
+        "textDocument": { "uri": URI },
+        "position": { "line": line, "character": character },
+    })
+}
+
+fn change(text: &str) -> String {
+    notification(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": URI, "version": 2 },
+            "contentChanges": [{ "text": text }],
+        }),
+    )
+}
+
+/// Sends `input` to the server and gives everything it sends back
+fn session(input: &str) -> Vec<Value> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wenyan-to-rust"))
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let mut out = BufReader::new(child.stdout.take().unwrap());
+    let mut messages = vec![];
+    loop {
+        let mut header = String::new();
+        if out.read_line(&mut header).unwrap() == 0 {
+            break;
+        }
+        let length = header
+            .trim()
+            .strip_prefix("Content-Length: ")
+            .unwrap()
+            .parse()
+            .unwrap();
+        out.read_line(&mut String::new()).unwrap();
+        let mut body = vec![0; length];
+        out.read_exact(&mut body).unwrap();
+        messages.push(serde_json::from_slice(&body).unwrap());
+    }
+    assert!(child.wait().unwrap().success());
+    messages
+}
+
+fn response(messages: &[Value], id: u64) -> &Value {
+    &messages
+        .iter()
+        .find(|m| m["id"] == id)
+        .unwrap_or_else(|| panic!("no response to {}", id))["result"]
+}
+
+fn diagnostics(messages: &[Value]) -> Vec<&Value> {
+    messages
+        .iter()
+        .filter(|m| m["method"] == "textDocument/publishDiagnostics")
+        .map(|m| &m["params"]["diagnostics"])
+        .collect()
+}
+
+fn range(start: (u64, u64), end: (u64, u64)) -> Value {
+    json!({
+        "start": { "line": start.0, "character": start.1 },
+        "end": { "line": end.0, "character": end.1 },
+    })
+}
+
+#[test]
+fn session_over_stdio() {
+    let input = [
+        request(1, "initialize", json!({ "capabilities": {} })),
+        notification("initialized", json!({})),
+        notification(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": { "uri": URI, "languageId": "wenyan", "version": 1, "text": "吾有一數。曰三。名之曰「甲」x" },
+            }),
+        ),
+        change("夫「乙」。書之。\n"),
+        change(PROGRAM),
+        request(2, "textDocument/hover", at(5, 2)),
+        request(3, "textDocument/hover", at(3, 3)),
+        request(4, "textDocument/definition", at(3, 3)),
+        request(5, "textDocument/definition", at(5, 2)),
+        request(6, "textDocument/definition", at(1, 0)),
+        request(
+            7,
+            "workspace/executeCommand",
+            json!({ "command": "wenyan-to-rust.showRust", "arguments": [URI] }),
+        ),
+        request(8, "textDocument/unknown", json!({})),
+        request(9, "shutdown", Value::Null),
+        notification("exit", Value::Null),
+    ]
+    .concat();
+    let messages = session(&input);
+
+    let capabilities = &response(&messages, 1)["capabilities"];
+    assert_eq!(capabilities["hoverProvider"], true);
+    assert_eq!(capabilities["definitionProvider"], true);
+
+    let diagnostics = diagnostics(&messages);
+    assert_eq!(diagnostics.len(), 3);
+    // the unrecognized `x`
+    assert_eq!(diagnostics[0][0]["range"], range((0, 14), (0, 15)));
+    assert_eq!(diagnostics[0][0]["message"], "unrecognized character 'x'");
+    // `「乙」` is used before it is declared
+    assert_eq!(diagnostics[1][0]["range"], range((0, 1), (0, 4)));
+    assert_eq!(diagnostics[2], &json!([]));
+
+    let hover = &response(&messages, 2)["contents"]["value"];
+    assert_eq!(hover, "「甲」 數 → `jia3`");
+    assert_eq!(response(&messages, 2)["range"], range((5, 1), (5, 4)));
+    let hover = &response(&messages, 3)["contents"]["value"];
+    assert_eq!(hover, "「甲」 言 → `jia3`");
+
+    // the `「甲」` inside the `若` is the one declared there, the one after it that of line 0
+    assert_eq!(response(&messages, 4)["range"], range((2, 16), (2, 19)));
+    assert_eq!(response(&messages, 4)["uri"], URI);
+    assert_eq!(response(&messages, 5)["range"], range((0, 11), (0, 14)));
+    // `若` is not an identifier
+    assert_eq!(response(&messages, 6), &Value::Null);
+
+    let rust = response(&messages, 7).as_str().unwrap();
+    assert!(rust.contains("fn main() {"), "{}", rust);
+
+    let unknown = messages.iter().find(|m| m["id"] == 8).unwrap();
+    assert_eq!(unknown["error"]["code"], -32601);
+    assert_eq!(response(&messages, 9), &Value::Null);
+}
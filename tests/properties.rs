@@ -0,0 +1,526 @@
+//! Properties checked on random well-formed programs:
+//!
+//! * `unparse` writes a program so that `parse` reads back the same program; this is also checked on every sample that parses.
+//! * Every backend gives its code or an error, with or without `-O`, and never panics.
+//! * With whitespace, punctuation and comments added between the tokens, the tokens and the trivia still cover the source,
+//!   and each node of the CST is the corresponding statement; this is also checked on every sample that parses.
+//!   `format` keeps the comments.
+//!
+//! A failing random case is reported with its seed; `PROPERTIES_SEED=n` runs only that one.
+
+use std::path::{Path, PathBuf};
+use wenyan_to_rust::parse::{
+    self, Data, DeclareStatement, DivBinaryOp, IfCond, Lvalue, MathKind, OrQi2, Statement,
+    UnaryIfExpr, Value,
+};
+use wenyan_to_rust::romanize;
+use wenyan_to_rust::unparse::unparse;
+use wenyan_to_rust::{backend, compile, cst, lex};
+
+const CASES: u64 = 2000;
+const MAX_DEPTH: usize = 3;
+const MAX_BLOCK_LEN: usize = 4;
+
+/// A tiny xorshift generator, so that a case is reproduced from its seed alone
+struct Gen(u64);
+
+impl Gen {
+    fn new(seed: u64) -> Self {
+        Gen(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len())]
+    }
+
+    fn ident(&mut self) -> parse::Identifier {
+        parse::Identifier(self.pick(&["甲", "乙", "丙", "天地", "a"]).to_string())
+    }
+
+    /// Only the forms that `parse` reads, e.g. not `一百零五`
+    fn number(&mut self) -> i64 {
+        loop {
+            let n = if self.below(2) == 0 {
+                self.below(100)
+            } else {
+                self.below(1000)
+            } as i64;
+            if n < 100 || n / 10 % 10 != 0 || n % 10 == 0 {
+                return n;
+            }
+        }
+    }
+
+    fn data(&mut self) -> Data {
+        match self.below(4) {
+            0 => Data::StringLiteral(self.pick(&["問天地好在。", "", "吾", "a b"]).to_string()),
+            1 => Data::BoolValue(self.below(2) == 0),
+            2 => Data::Identifier(self.ident()),
+            _ => Data::IntNum(self.number()),
+        }
+    }
+
+    fn data_or_qi2(&mut self) -> OrQi2<Data> {
+        if self.below(4) == 0 {
+            OrQi2::Qi2
+        } else {
+            OrQi2::NotQi2(self.data())
+        }
+    }
+
+    fn ident_or_qi2(&mut self) -> OrQi2<parse::Identifier> {
+        if self.below(4) == 0 {
+            OrQi2::Qi2
+        } else {
+            OrQi2::NotQi2(self.ident())
+        }
+    }
+
+    fn value<T>(&mut self, of: T) -> Value<T> {
+        match self.below(4) {
+            0 => Value::Simple(of),
+            1 => Value::Index(of, self.number()),
+            2 => Value::IndexByIdent(of, self.ident()),
+            _ => Value::Length(of),
+        }
+    }
+
+    fn unary_if_expr(&mut self) -> UnaryIfExpr {
+        if self.below(3) == 0 {
+            // `parse` only reads an indexed identifier here
+            let of = Data::Identifier(self.ident());
+            match self.value(of) {
+                Value::Simple(of) => UnaryIfExpr::Complex(Value::Length(of)),
+                v => UnaryIfExpr::Complex(v),
+            }
+        } else {
+            UnaryIfExpr::Simple(self.data_or_qi2())
+        }
+    }
+
+    /// `或若` has no `其不然者`
+    fn if_cond(&mut self, elseif: bool) -> IfCond {
+        match self.below(3) {
+            0 if !elseif => IfCond::NotQi2,
+            0 => IfCond::Unary(UnaryIfExpr::Simple(OrQi2::Qi2)),
+            1 => IfCond::Unary(self.unary_if_expr()),
+            _ => IfCond::Binary(
+                self.unary_if_expr(),
+                self.pick(&[
+                    lex::IfLogicOp::Deng3Yu2,
+                    lex::IfLogicOp::Bu4Deng3Yu2,
+                    lex::IfLogicOp::Bu4Da4Yu2,
+                    lex::IfLogicOp::Bu4Xiao3Yu2,
+                    lex::IfLogicOp::Da4Yu2,
+                    lex::IfLogicOp::Xiao3Yu2,
+                ]),
+                self.unary_if_expr(),
+            ),
+        }
+    }
+
+    fn type_(&mut self) -> lex::Type {
+        self.pick(&[
+            lex::Type::Shu4,
+            lex::Type::Lie4,
+            lex::Type::Yan2,
+            lex::Type::Yao2,
+        ])
+    }
+
+    fn preposition(&mut self) -> lex::Preposition {
+        self.pick(&[lex::Preposition::Yi3, lex::Preposition::Yu2])
+    }
+
+    fn idents(&mut self) -> Vec<parse::Identifier> {
+        (0..=self.below(3)).map(|_| self.ident()).collect()
+    }
+
+    fn declare(&mut self) -> DeclareStatement {
+        DeclareStatement {
+            how_many_variables: 1 + self.below(9),
+            type_: self.type_(),
+            data_arr: (0..self.below(4)).map(|_| self.data()).collect(),
+        }
+    }
+
+    fn math(&mut self) -> MathKind {
+        match self.below(4) {
+            0 => MathKind::ArithBinaryMath(
+                self.pick(&[
+                    lex::ArithBinaryOp::Jia1,
+                    lex::ArithBinaryOp::Jian3,
+                    lex::ArithBinaryOp::Cheng2,
+                ]),
+                self.data_or_qi2(),
+                self.preposition(),
+                self.data_or_qi2(),
+            ),
+            1 => MathKind::ModMath(
+                self.pick(&[DivBinaryOp::Div, DivBinaryOp::Mod]),
+                self.data_or_qi2(),
+                self.preposition(),
+                self.data_or_qi2(),
+            ),
+            // `變` takes an identifier
+            2 => MathKind::ArithUnaryMath(OrQi2::from(&self.ident_or_qi2())),
+            _ => MathKind::BooleanAlgebra(
+                self.ident(),
+                self.ident(),
+                self.pick(&[
+                    lex::LogicBinaryOp::Zhong1You3Yang2Hu1,
+                    lex::LogicBinaryOp::Zhong1Wu2Yin1Hu1,
+                ]),
+            ),
+        }
+    }
+
+    fn statement(&mut self, depth: usize, in_loop: bool) -> Statement {
+        // no blocks below MAX_DEPTH
+        let kinds = if depth < MAX_DEPTH { 19 } else { 14 };
+        match self.below(kinds) {
+            0 => Statement::Declare(self.declare()),
+            1 => Statement::Define {
+                decl: self.declare(),
+                idents: self.idents(),
+            },
+            2 => Statement::InitDefine {
+                type_: self.type_(),
+                data: self.data(),
+                name: self.ident(),
+            },
+            3 => Statement::NameMulti {
+                idents: self.idents(),
+            },
+            4 => match self.below(4) {
+                0 => Statement::Print,
+                1 => Statement::Flush,
+                2 => Statement::Break,
+                _ => Statement::Continue,
+            },
+            5 => Statement::Math { math: self.math() },
+            6 => Statement::Assignment {
+                lvalue: match self.below(3) {
+                    0 => Lvalue::Simple(self.ident()),
+                    1 => Lvalue::Index(self.ident(), self.number()),
+                    _ => Lvalue::IndexByIdent(self.ident(), self.ident()),
+                },
+                rvalue: {
+                    let of = self.data_or_qi2();
+                    self.value(of)
+                },
+            },
+            7 => Statement::Reference {
+                rvalue: {
+                    let of = self.data();
+                    self.value(of)
+                },
+            },
+            8 => Statement::ReferenceWhatIsLeft { data: self.data() },
+            9 => Statement::ArrayFill {
+                what_to_fill: self.ident_or_qi2(),
+                elems: (0..=self.below(3)).map(|_| self.data()).collect(),
+            },
+            10 => Statement::ArrayCat {
+                append_to: self.ident_or_qi2(),
+                elems: self.idents(),
+            },
+            11..=13 => Statement::Math { math: self.math() },
+            14 => Statement::ForEnum {
+                num: self.number(),
+                statements: self.block(depth + 1, true, false),
+            },
+            15 => Statement::ForEnumIdent {
+                ident: self.ident_or_qi2(),
+                statements: self.block(depth + 1, true, false),
+            },
+            16 => Statement::ForArr {
+                list: self.ident(),
+                elem: self.ident(),
+                stmts: self.block(depth + 1, true, false),
+            },
+            17 => Statement::Loop {
+                statements: self.block(depth + 1, true, false),
+            },
+            _ => Statement::If {
+                ifcase: (self.if_cond(false), self.block(depth + 1, in_loop, true)),
+                elseifcases: (0..self.below(3))
+                    .map(|_| (self.if_cond(true), self.block(depth + 1, in_loop, true)))
+                    .collect(),
+                elsecase: if self.below(2) == 0 {
+                    vec![]
+                } else {
+                    self.block(depth + 1, in_loop, true)
+                },
+            },
+        }
+    }
+
+    /// `parse` reads `吾有 ...` followed by `名之曰 ...` as one `Define`, so a `NameMulti` never follows a `Declare`;
+    /// `乃止` and `乃止是遍` are only in a loop
+    fn block(&mut self, depth: usize, in_loop: bool, nonempty: bool) -> Vec<Statement> {
+        let len = self.below(MAX_BLOCK_LEN) + usize::from(nonempty);
+        let mut stmts: Vec<Statement> = vec![];
+        while stmts.len() < len {
+            let st = self.statement(depth, in_loop);
+            if let (Some(Statement::Declare(_)), Statement::NameMulti { .. }) = (stmts.last(), &st)
+            {
+                continue;
+            }
+            if !in_loop && matches!(st, Statement::Break | Statement::Continue) {
+                continue;
+            }
+            stmts.push(st);
+        }
+        stmts
+    }
+}
+
+fn reparse(src: &str) -> Result<Vec<Statement>, String> {
+    let lex = lex::lex(src).map_err(|e| format!("lex error {:?}", e))?;
+    parse::parse(&lex).map_err(|e| format!("parse error {:?}", e))
+}
+
+fn check(stmts: &[Statement]) -> Result<(), String> {
+    let src = unparse(stmts);
+    match reparse(&src) {
+        Ok(parsed) if parsed == stmts => Ok(()),
+        Ok(parsed) => Err(format!(
+            "{}\nwas read back as\n{:#?}\ninstead of\n{:#?}",
+            src, parsed, stmts
+        )),
+        Err(e) => Err(format!("{}\nwas not read back: {}", src, e)),
+    }
+}
+
+/// `src` with some whitespace, line breaks, punctuation and comments added before the tokens
+fn relayout(src: &str, gen: &mut Gen) -> String {
+    let tokens = lex::lex_with_trivia(src).unwrap();
+    let mut r = String::new();
+    let mut pos = 0;
+    for token in &tokens.tokens {
+        r.push_str(&src[pos..token.span.start]);
+        for _ in 0..gen.below(3) {
+            r.push_str(gen.pick(&[
+                " ",
+                "　",
+                "\t",
+                "\n",
+                "\r\n",
+                "。",
+                "、",
+                "批曰「「也」」",
+                "注曰「甲」",
+            ]));
+        }
+        pos = token.span.start;
+    }
+    r.push_str(&src[pos..]);
+    r
+}
+
+fn check_cst(src: &str, stmts: &[Statement]) -> Result<(), String> {
+    let tokens = lex::lex_with_trivia(src).map_err(|e| format!("lex error {:?}", e))?;
+    let mut pos = 0;
+    for token in &tokens.tokens {
+        for span in token
+            .leading_trivia
+            .iter()
+            .map(|t| &t.span)
+            .chain(std::iter::once(&token.span))
+        {
+            if span.start != pos {
+                return Err(format!("{:?} does not begin at {}", span, pos));
+            }
+            pos = span.end;
+        }
+    }
+    for trivia in &tokens.trailing_trivia {
+        if trivia.span.start != pos {
+            return Err(format!("{:?} does not begin at {}", trivia.span, pos));
+        }
+        pos = trivia.span.end;
+    }
+    if pos != src.len() {
+        return Err(format!("the tokens and trivia end at {}", pos));
+    }
+
+    let (cst, parsed) =
+        cst::Cst::parse(src).map_err(|e| format!("parse error {:?}", e.diagnostic))?;
+    if parsed != stmts {
+        return Err(String::from("the AST from the CST differs"));
+    }
+    let nodes = cst.preorder();
+    let preorder = cst::preorder(stmts);
+    if nodes.len() != preorder.len() {
+        return Err(format!(
+            "{} nodes for {} statements",
+            nodes.len(),
+            preorder.len()
+        ));
+    }
+    for (node, st) in nodes.iter().zip(preorder) {
+        let inside = st.blocks().iter().map(|b| b.len()).sum::<usize>();
+        if node.children.len() != inside {
+            return Err(format!(
+                "{:?} has {} children for {} statements inside",
+                cst.text(node),
+                node.children.len(),
+                inside
+            ));
+        }
+    }
+    for (node, st) in cst.statements.iter().zip(stmts) {
+        if reparse(cst.text(node))?.as_slice() != std::slice::from_ref(st) {
+            return Err(format!("{:?} is not {:?}", cst.text(node), st));
+        }
+    }
+    Ok(())
+}
+
+fn seeds() -> std::ops::Range<u64> {
+    match std::env::var("PROPERTIES_SEED") {
+        Ok(seed) => {
+            let seed = seed.parse().expect("PROPERTIES_SEED must be a number");
+            seed..seed + 1
+        }
+        Err(_) => 0..CASES,
+    }
+}
+
+#[test]
+fn unparse_then_parse() {
+    for seed in seeds() {
+        let stmts = Gen::new(seed).block(0, false, true);
+        if let Err(msg) = check(&stmts) {
+            panic!("seed {}:\n{}", seed, msg);
+        }
+    }
+}
+
+#[test]
+fn compile_does_not_panic() {
+    let romanizer = romanize::Table::pinyin();
+    for seed in seeds() {
+        let stmts = Gen::new(seed).block(0, false, true);
+        for backend in &backend::BACKENDS {
+            for optimize in &[false, true] {
+                let options = compile::Options {
+                    optimize: *optimize,
+                };
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    let _ = backend.compile(&stmts, &romanizer, &options);
+                }));
+                if result.is_err() {
+                    panic!(
+                        "seed {}: compiling to {} with optimize = {} panicked on\n{}",
+                        seed,
+                        backend.name(),
+                        optimize,
+                        unparse(&stmts)
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cst() {
+    for seed in seeds() {
+        let mut gen = Gen::new(seed);
+        let stmts = gen.block(0, false, true);
+        let src = relayout(&unparse(&stmts), &mut gen);
+        if let Err(msg) = check_cst(&src, &stmts) {
+            panic!("seed {}: {}\n{}", seed, msg, src);
+        }
+        let formatted = wenyan_to_rust::format(&src).unwrap();
+        assert_eq!(reparse(&formatted).as_ref(), Ok(&stmts), "seed {}", seed);
+        assert_eq!(comments(&formatted), comments(&src), "seed {}", seed);
+        // as a statement, each comment ends with `。`
+        for line in formatted.lines() {
+            let line = line.trim_start_matches('　');
+            if line.starts_with(&['批', '注', '疏'][..]) {
+                assert!(line.ends_with('。'), "seed {}: {}", seed, line);
+            }
+        }
+    }
+}
+
+/// The comments of `src`, in order
+fn comments(src: &str) -> Vec<String> {
+    let tokens = lex::lex_with_trivia(src).unwrap();
+    tokens
+        .tokens
+        .iter()
+        .flat_map(|token| &token.leading_trivia)
+        .chain(&tokens.trailing_trivia)
+        .filter(|trivia| trivia.kind == lex::TriviaKind::Comment)
+        .map(|trivia| src[trivia.span.clone()].to_string())
+        .collect()
+}
+
+/// Every `.wy` under `tests/samples`, with its path
+fn samples() -> Vec<(PathBuf, String)> {
+    let mut r = vec![];
+    let mut dirs = vec![Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/samples")];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().map_or(false, |ext| ext == "wy") {
+                let src = std::fs::read_to_string(&path).unwrap();
+                r.push((path, src));
+            }
+        }
+    }
+    r
+}
+
+#[test]
+fn unparse_then_parse_samples() {
+    for (path, src) in samples() {
+        if let Ok(stmts) = reparse(&src) {
+            if let Err(msg) = check(&stmts) {
+                panic!("{}:\n{}", path.display(), msg);
+            }
+        }
+    }
+}
+
+#[test]
+fn format_is_idempotent_on_samples() {
+    for (path, src) in samples() {
+        if let Ok(formatted) = wenyan_to_rust::format(&src) {
+            assert_eq!(
+                wenyan_to_rust::format(&formatted).ok().as_ref(),
+                Some(&formatted),
+                "{}",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test]
+fn cst_of_samples() {
+    for (path, src) in samples() {
+        if let Ok(stmts) = reparse(&src) {
+            if let Err(msg) = check_cst(&src, &stmts) {
+                panic!("{}: {}", path.display(), msg);
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Feeds lines to the REPL and checks what it writes.
+
+use wenyan_to_rust::repl::Repl;
+use wenyan_to_rust::romanize;
+
+fn session(input: &str) -> String {
+    let romanizer = romanize::Table::pinyin();
+    let mut out = vec![];
+    let mut repl = Repl::new(&mut out, &romanizer);
+    repl.show_rust = true;
+    repl.run(input.as_bytes()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn the_rust_shown_before_is_shown_again_where_a_later_line_changes_it() {
+    let out = session("吾有一數。曰三。名之曰「甲」。\n加「甲」以一。書之。\n");
+    assert_eq!(
+        out,
+        "> \
+         \x20   let _jia3 = 3.0;\n\
+         > 4\n\
+         // changed:\n\
+         \x20   let jia3 = 3.0;\n\
+         // new:\n\
+         \x20   let _ans1 = jia3 + 1.0;\n\
+         \x20   println!(\"{}\", _ans1);\n\
+         > \n"
+    );
+}
+
+#[test]
+fn the_variables_and_the_stack_are_kept_between_lines() {
+    let out = session("吾有一數。曰三。名之曰「甲」。\n加「甲」以一。\n:stack\n書之。\n");
+    assert!(out.contains("[4]\n"), "{}", out);
+    assert!(out.contains("> 4\n"), "{}", out);
+}
@@ -0,0 +1,46 @@
+//! Runs `wenyan-to-rust run` on programs that rustc rejects, and checks where the errors are said to come from.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run_stderr(src: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_wenyan-to-rust"))
+        .args(["run", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(src.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn errors_point_at_the_innermost_statement() {
+    let stderr = run_stderr(
+        "吾有一列。名之曰「甲」。充「甲」以一。\n吾有一列。名之曰「乙」。充「乙」以「甲」。\n凡「乙」中之「丙」。\n　夫「丙」。書之。\n云云。\n",
+    );
+    assert!(
+        stderr.contains("note: generated from -:4:7:\n    書之\n"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn errors_in_a_block_head_show_the_head_only() {
+    let stderr =
+        run_stderr("吾有一列。名之曰「甲」。\n為是「甲」遍。\n　吾有一數。曰一。書之。\n云云。\n");
+    assert!(
+        stderr.contains("note: generated from -:2:1:\n    為是「甲」遍。\n"),
+        "{}",
+        stderr
+    );
+}
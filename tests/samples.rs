@@ -0,0 +1,355 @@
+//! Walks `tests/samples` and checks every `.wy` found there according to the directory it is in:
+//!
+//! * `should-compile/NAME.wy`: the output must be exactly `NAME.rs`, which must compile without warnings.
+//!   If `NAME.out` exists, `NAME.rs` is also compiled with the local rustc and its stdout must be exactly `NAME.out`;
+//!   so must be the output of the interpreter (`eval`).
+//!   If `NAME.js` exists, the output of the JavaScript backend must be exactly `NAME.js`,
+//!   and if `node` is found, running it must print `NAME.out` as well.
+//! * `should-fail/X/NAME.wy`: transpiling must fail with an error whose variant is named `X`, such as `SomethingWentWrong`.
+//! * `known-divergence/NAME.wy`: we know that the output is wrong (see the README.md there).
+//!   If `NAME.rs` exists, the output must still be exactly `NAME.rs`, so that any change is noticed;
+//!   otherwise, transpiling must fail.
+//!
+//! A sample may come with `NAME.conf`, a config file (such as `romanization = hex` or `optimize = true`) to transpile it with.
+//!
+//! Adding a sample is thus a matter of adding files.
+//! Pass substrings of the case names (e.g. `cargo test --test samples -- test006`) to run only some of them.
+
+use std::io::Read as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use wenyan_to_rust::romanize::{self, Romanizer};
+use wenyan_to_rust::{backend, compile, config, eval, lex, parse, run};
+
+/// Some samples, such as test058, never terminate
+const RUN_TIMEOUT: Duration = Duration::from_secs(10);
+
+const WORKERS: usize = 4;
+
+enum Category {
+    ShouldCompile,
+    ShouldFail(String),
+    KnownDivergence,
+}
+
+struct Case {
+    name: String,
+    /// path without the `.wy` extension
+    stem: PathBuf,
+    category: Category,
+}
+
+fn read(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|s| str::replace(&s, "\r", ""))
+}
+
+fn wy_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "wy"))
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+    dirs
+}
+
+fn case(path: &Path, name: String, category: Category) -> Case {
+    Case {
+        name,
+        stem: path.with_extension(""),
+        category,
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().unwrap().to_string_lossy().into_owned()
+}
+
+fn discover(root: &Path) -> Vec<Case> {
+    let mut cases = vec![];
+    for path in wy_files(&root.join("should-compile")) {
+        let name = format!("should-compile/{}", file_stem(&path));
+        cases.push(case(&path, name, Category::ShouldCompile));
+    }
+    for dir in subdirs(&root.join("should-fail")) {
+        let error = file_stem(&dir);
+        for path in wy_files(&dir) {
+            let name = format!("should-fail/{}/{}", error, file_stem(&path));
+            cases.push(case(&path, name, Category::ShouldFail(error.clone())));
+        }
+    }
+    for path in wy_files(&root.join("known-divergence")) {
+        let name = format!("known-divergence/{}", file_stem(&path));
+        cases.push(case(&path, name, Category::KnownDivergence));
+    }
+    cases
+}
+
+fn stdout_with_timeout(command: &mut Command) -> Result<String, String> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut buf = String::new();
+        stdout.read_to_string(&mut buf).map(|_| buf)
+    });
+    let deadline = Instant::now() + RUN_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            break status;
+        }
+        if Instant::now() > deadline {
+            let _ = child.kill();
+            return Err(format!("did not terminate within {:?}", RUN_TIMEOUT));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    if !status.success() {
+        return Err(format!("exited with {}", status));
+    }
+    reader
+        .join()
+        .unwrap()
+        .map(|s| str::replace(&s, "\r", ""))
+        .map_err(|e| e.to_string())
+}
+
+/// Type-checks `src` with `-D warnings`, so that e.g. an unneeded `mut` fails
+fn check_warnings(src: &str) -> Result<(), String> {
+    let dir = run::TempDir::new().map_err(|e| e.to_string())?;
+    let src_path = dir.path().join("main.rs");
+    std::fs::write(&src_path, src).map_err(|e| e.to_string())?;
+    let output = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| String::from("rustc")))
+        .args(&[
+            "--edition=2018",
+            "-D",
+            "warnings",
+            "--emit=metadata",
+            "--out-dir",
+        ])
+        .arg(dir.path())
+        .arg(&src_path)
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "the .rs has warnings:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
+
+fn compare(what: &str, actual: &str, expected: &str) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} differs\n--- expected\n{}\n--- actual\n{}",
+            what, expected, actual
+        ))
+    }
+}
+
+/// Errors are compared by the name of the innermost variant, e.g. `SomethingWentWrong` for
+/// `Parse(SomethingWentWrong(Position { .. }))`
+fn variant_name(error: &wenyan_to_rust::Diagnostic) -> String {
+    let debug = format!("{:?}", error);
+    let inner = match debug.find('(') {
+        Some(i) => &debug[i + 1..],
+        None => &debug,
+    };
+    inner
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
+fn transpile(
+    backend: &dyn backend::Backend,
+    src: &str,
+    romanizer: &dyn Romanizer,
+    options: &compile::Options,
+) -> Result<Result<String, wenyan_to_rust::Diagnostic>, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        wenyan_to_rust::transpile_with(backend, src, romanizer, options)
+    }))
+    .map_err(|payload| {
+        let msg = payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| (*s).to_string()))
+            .unwrap_or_default();
+        format!("panicked: {}", msg)
+    })
+}
+
+fn interpret(src: &str) -> Result<String, String> {
+    let lex = lex::lex(src).map_err(|e| format!("{:?}", e))?;
+    let parsed = parse::parse(&lex).map_err(|e| format!("{:?}", e))?;
+    let mut interpreter = eval::Interpreter::new(vec![]);
+    interpreter
+        .run(&parsed)
+        .map_err(|e| format!("eval failed: {:?}", e))?;
+    String::from_utf8(interpreter.into_output()).map_err(|e| e.to_string())
+}
+
+fn node_found() -> bool {
+    Command::new("node")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .status()
+        .is_ok()
+}
+
+/// The JavaScript in `expected_js` must print `expected_out`
+fn check_js(expected_js: &str, expected_out: &str) -> Result<(), String> {
+    let dir = run::TempDir::new().map_err(|e| e.to_string())?;
+    let path = dir.path().join("main.js");
+    std::fs::write(&path, expected_js).map_err(|e| e.to_string())?;
+    let stdout = stdout_with_timeout(Command::new("node").arg(&path))?;
+    compare("node stdout", &stdout, expected_out)
+}
+
+fn check(case: &Case, default_romanizer: &dyn Romanizer) -> Result<(), String> {
+    let src = read(&case.stem.with_extension("wy")).ok_or("cannot read the .wy")?;
+    let expected_rs = read(&case.stem.with_extension("rs"));
+    let conf = case.stem.with_extension("conf");
+    let config = if conf.exists() {
+        Some(config::Config::read(&conf).map_err(|e| format!("invalid .conf: {:?}", e))?)
+    } else {
+        None
+    };
+    let conf_romanizer = match &config {
+        Some(config) => Some(
+            config
+                .romanizer()
+                .map_err(|e| format!("invalid .conf: {:?}", e))?,
+        ),
+        None => None,
+    };
+    let romanizer: &dyn Romanizer = match &conf_romanizer {
+        Some(romanizer) => &**romanizer,
+        None => default_romanizer,
+    };
+    let options = config
+        .as_ref()
+        .map_or_else(compile::Options::default, config::Config::compile_options);
+    let result = transpile(&backend::Rust, &src, romanizer, &options);
+
+    match &case.category {
+        Category::ShouldCompile => {
+            let compiled = result?.map_err(|e| format!("failed to transpile: {:?}", e))?;
+            let expected_rs = expected_rs.ok_or("missing .rs")?;
+            compare(".rs", &compiled, &expected_rs)?;
+            check_warnings(&expected_rs)?;
+            if let Some(expected_out) = read(&case.stem.with_extension("out")) {
+                let dir = run::TempDir::new().map_err(|e| e.to_string())?;
+                let bin = run::rustc(&expected_rs, None, dir.path())
+                    .map_err(|e| format!("the .rs does not compile: {:?}", e))?;
+                compare(
+                    "stdout",
+                    &stdout_with_timeout(&mut Command::new(bin))?,
+                    &expected_out,
+                )?;
+                compare("eval output", &interpret(&src)?, &expected_out)?;
+            }
+            if let Some(expected_js) = read(&case.stem.with_extension("js")) {
+                let compiled = transpile(&backend::Js, &src, romanizer, &options)?
+                    .map_err(|e| format!("failed to compile to JavaScript: {:?}", e))?;
+                compare(".js", &compiled, &expected_js)?;
+                if let Some(expected_out) = read(&case.stem.with_extension("out")) {
+                    if node_found() {
+                        check_js(&expected_js, &expected_out)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        Category::ShouldFail(expected) => match result? {
+            Ok(_) => Err(format!("transpiled, although {} was expected", expected)),
+            Err(e) if &variant_name(&e) == expected => Ok(()),
+            Err(e) => Err(format!("expected {}, got {:?}", expected, e)),
+        },
+        Category::KnownDivergence => match expected_rs {
+            Some(expected_rs) => {
+                let compiled = result?.map_err(|e| format!("failed to transpile: {:?}", e))?;
+                compare(".rs", &compiled, &expected_rs)
+            }
+            None => match result? {
+                Ok(_) => Err(String::from("transpiled; add a .rs or move it out")),
+                Err(_) => Ok(()),
+            },
+        },
+    }
+}
+
+fn main() {
+    let filters = std::env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect::<Vec<_>>();
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/samples");
+    let cases = discover(&root)
+        .into_iter()
+        .filter(|case| filters.is_empty() || filters.iter().any(|f| case.name.contains(f)))
+        .collect::<Vec<_>>();
+
+    // panics are caught and reported per case
+    std::panic::set_hook(Box::new(|_| {}));
+
+    println!("\nrunning {} samples", cases.len());
+    let romanizer = romanize::Table::pinyin();
+    let queue = Mutex::new(cases.iter());
+    let failures = Mutex::new(vec![]);
+    std::thread::scope(|s| {
+        for _ in 0..WORKERS {
+            s.spawn(|| loop {
+                let case = match queue.lock().unwrap().next() {
+                    Some(case) => case,
+                    None => break,
+                };
+                match check(case, &romanizer) {
+                    Ok(()) => println!("test {} ... ok", case.name),
+                    Err(msg) => {
+                        println!("test {} ... FAILED", case.name);
+                        failures.lock().unwrap().push((case.name.clone(), msg));
+                    }
+                }
+            });
+        }
+    });
+
+    let mut failures = failures.into_inner().unwrap();
+    failures.sort();
+    for (name, msg) in &failures {
+        println!("\n---- {} ----\n{}", name, msg);
+    }
+    println!(
+        "\ntest result: {}. {} passed; {} failed\n",
+        if failures.is_empty() { "ok" } else { "FAILED" },
+        cases.len() - failures.len(),
+        failures.len()
+    );
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,11 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.append(&mut vec![1.0, 2.0, 3.0]);
+    let mut yi3 = jia3;
+    yi3[1 - 1] = 4.0;
+    let _ans1 = jia3;
+    println!("{}", _ans1);
+}
@@ -0,0 +1,31 @@
+// identifiers:
+//   「行一」 xing2_yi1
+//   「行二」 xing2_er4
+//   「行三」 xing2_san1
+//   「九宮」 jiu3_gong1
+//   「行」 xing2
+//   「廣」 guang3
+//   「縱橫圖」 zong4_heng2_tu2
+fn main() {
+    let mut xing2_yi1 = vec![];
+    xing2_yi1.append(&mut vec![4.0, 9.0, 2.0]);
+    let mut xing2_er4 = vec![];
+    xing2_er4.append(&mut vec![3.0, 5.0, 7.0]);
+    let mut xing2_san1 = vec![];
+    xing2_san1.append(&mut vec![8.0, 1.0, 6.0]);
+    let mut jiu3_gong1 = vec![];
+    jiu3_gong1.append(&mut vec![xing2_yi1, xing2_er4, xing2_san1]);
+    for xing2 in jiu3_gong1.iter().cloned() {
+        let _ans1 = xing2;
+        println!("{}", _ans1);
+    }
+    let guang3 = 9.0;
+    let mut zong4_heng2_tu2 = vec![];
+    for _ in 0..(f64::ceil(guang3) as usize) {
+        let mut xing2 = vec![];
+        for _ in 0..(f64::ceil(guang3) as usize) {
+            xing2.push(0.0);
+        }
+        zong4_heng2_tu2.push(xing2);
+    }
+}
@@ -0,0 +1,76 @@
+// identifiers:
+//   「行一」 xing2_yi1
+//   「行二」 xing2_er4
+//   「行三」 xing2_san1
+//   「九宮」 jiu3_gong1
+//   「行」 xing2
+//   「廣」 guang3
+//   「縱橫圖」 zong4_heng2_tu2
+//   「半」 ban4
+//   「數」 shu4
+//   「磔」 zhe2
+//   「掠」 lve4
+//   「勒」 le4
+//   「努」 nu3
+//   「橫」 heng2
+//   「縱」 zong4
+fn main() {
+    let mut xing2_yi1 = vec![];
+    xing2_yi1.append(&mut vec![4.0, 9.0, 2.0]);
+    let mut xing2_er4 = vec![];
+    xing2_er4.append(&mut vec![3.0, 5.0, 7.0]);
+    let mut xing2_san1 = vec![];
+    xing2_san1.append(&mut vec![8.0, 1.0, 6.0]);
+    let mut jiu3_gong1 = vec![];
+    jiu3_gong1.append(&mut vec![xing2_yi1, xing2_er4, xing2_san1]);
+    for xing2 in jiu3_gong1.iter().cloned() {
+        let _ans1 = xing2;
+        println!("{}", _ans1);
+    }
+    let guang3 = 9.0;
+    let mut zong4_heng2_tu2 = vec![];
+    for _ in 0..(f64::ceil(guang3) as usize) {
+        let mut xing2 = vec![];
+        for _ in 0..(f64::ceil(guang3) as usize) {
+            xing2.push(0.0);
+        }
+        zong4_heng2_tu2.push(xing2);
+    }
+    let _ans2 = guang3 - 1.0;
+    let _ans3 = _ans2 / 2.0;
+    let ban4 = _ans3;
+    let mut shu4 = 1.0;
+    let _ans4 = guang3 * guang3;
+    for _ in 0..(f64::ceil(_ans4) as usize) {
+        let _ans5 = shu4 - 1.0;
+        let _ans6 = _ans5 % guang3;
+        let zhe2 = _ans6;
+        let _ans7 = shu4 - zhe2;
+        let _ans8 = _ans7 - 1.0;
+        let _ans9 = _ans8 / guang3;
+        let lve4 = _ans9;
+        let _ans10 = zhe2 + ban4;
+        let _ans11 = _ans10 - lve4;
+        let le4 = _ans11;
+        let _ans12 = zhe2 - ban4;
+        let _ans13 = _ans12 + lve4;
+        let nu3 = _ans13;
+        let _ans14 = le4 + guang3;
+        let _ans15 = _ans14 % guang3;
+        let _ans16 = _ans15 + 1.0;
+        let heng2 = _ans16;
+        let _ans17 = nu3 + guang3;
+        let _ans18 = _ans17 % guang3;
+        let _ans19 = _ans18 + 1.0;
+        let zong4 = _ans19;
+        let _ans20 = zong4_heng2_tu2[(zong4 as usize) - 1];
+        let mut xing2 = _ans20;
+        xing2[(heng2 as usize) - 1] = shu4;
+        let _ans21 = shu4 + 1.0;
+        shu4 = _ans21;
+    }
+    for xing2 in zong4_heng2_tu2.iter().cloned() {
+        let _ans22 = xing2;
+        println!("{}", _ans22);
+    }
+}
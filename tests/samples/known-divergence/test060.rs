@@ -0,0 +1,8 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let _jia3 = vec![];
+    let _ans1 = vec![];
+    let _yi3 = _ans1;
+}
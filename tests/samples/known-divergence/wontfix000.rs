@@ -0,0 +1,31 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+//   「戊」 wu4
+//   「己」 ji3
+//   「庚」 geng1
+//   「辛」 xin1
+fn main() {
+    let _ans1 = 2.0 + 3.0;
+    let _ans2 = 1.0 + 3.0;
+    let _ans3 = 3.0 + 3.0;
+    let _jia3 = _ans3;
+    let _yi3 = _ans2;
+    println!("{}", _ans1);
+    let _ans4 = 2.0 + 3.0;
+    let _ans5 = 1.0 + 3.0;
+    let _ans6 = 3.0 + 3.0;
+    let _bing3 = _ans5;
+    let _ding1 = _ans6;
+    println!("{}", _ans4);
+    let _ans7 = 2.0 + 3.0;
+    let _ans8 = 1.0 + 3.0;
+    let _ans9 = 3.0 + 3.0;
+    let _wu4 : (); // undefined
+    let _ji3 = _ans7;
+    let _geng1 = _ans8;
+    let _xin1 = _ans9;
+    println!("");
+}
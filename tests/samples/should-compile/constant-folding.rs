@@ -0,0 +1,18 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+fn main() {
+    println!("{}", 40.0);
+    println!("{}", 3.5);
+    println!("{}", f64::INFINITY);
+    println!("{}", f64::NAN);
+    println!("{}", -1.0);
+    println!("{}", false);
+    let mut ding1 = 0.0;
+    if true {
+        ding1 = 7.0;
+    }
+    println!("{}", ding1 * 7.0);
+}
@@ -0,0 +1,33 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+//   「戊」 wu4
+fn main() {
+    let jia3 = 1.0;
+    let yi3;
+    if jia3 == 1.0 {
+        yi3 = 2.0;
+    } else {
+        yi3 = 3.0;
+    }
+    let _ans1 = yi3;
+    println!("{}", _ans1);
+    let mut bing3;
+    bing3 = 4.0;
+    let _ans2 = bing3;
+    println!("{}", _ans2);
+    bing3 = 5.0;
+    let _ans3 = bing3;
+    println!("{}", _ans3);
+    let _ = 6.0;
+    let mut ding1 = vec![];
+    ding1.append(&mut vec![1.0, 2.0]);
+    for _ in ding1.iter().cloned() {
+        let wu4;
+        wu4 = 7.0;
+        let _ans4 = wu4;
+        println!("{}", _ans4);
+    }
+}
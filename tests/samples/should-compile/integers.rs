@@ -0,0 +1,23 @@
+// identifiers:
+//   「和」 he2
+//   「列」 lie4
+//   「次」 ci4
+//   「長」 chang2
+fn main() {
+    let mut he2 = 0;
+    for _ in 0..10 {
+        he2 = he2 + 3;
+    }
+    println!("{}", he2);
+    let mut lie4 = vec![];
+    lie4.append(&mut vec![4, 5, 6]);
+    let _ans1 = lie4[2 - 1];
+    println!("{} {}", _ans1, lie4.len() as i64);
+    let chang2 = lie4.len() as i64;
+    for _ in 0..chang2 {
+        println!("{}", he2 % 4);
+    }
+    if he2 > 29 {
+        println!("{}", "大");
+    }
+}
@@ -0,0 +1,15 @@
+// identifiers:
+//   「戊」 wu4
+//   「己」 ji3
+//   「庚」 geng1
+//   「辛」 xin1
+fn main() {
+    let _ans1 = 2.0 + 3.0;
+    let _ans2 = 1.0 + 3.0;
+    let _ans3 = 3.0 + 3.0;
+    let _wu4 : (); // undefined
+    let _ji3 = _ans1;
+    let _geng1 = _ans2;
+    let _xin1 = _ans3;
+    println!("");
+}
@@ -0,0 +1,5 @@
+// identifiers:
+//   「戊」 wu4
+fn main() {
+    let _wu4 : (); // undefined
+}
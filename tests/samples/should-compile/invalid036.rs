@@ -0,0 +1,23 @@
+// identifiers:
+//   「丙」 bing3
+//   「丁」 ding1
+//   「戊」 wu4
+//   「己」 ji3
+//   「庚」 geng1
+fn main() {
+    let _ans1 = 2.0 + 3.0;
+    let _ans2 = 2.0 + 3.0;
+    let _ans3 = 1.0 + 3.0;
+    let _ans4 = 3.0 + 3.0;
+    let _bing3 = _ans3;
+    let _ding1 = _ans4;
+    println!("{} {}", _ans1, _ans2);
+    let _wu4 : (); // undefined
+    let _ans5 = 2.0 + 3.0;
+    let _ans6 = 1.0 + 3.0;
+    let _ans7 = 3.0 + 3.0;
+    let _wu4 = _ans5;
+    let _ji3 = _ans6;
+    let _geng1 = _ans7;
+    println!("");
+}
@@ -0,0 +1,7 @@
+// identifiers:
+//   「戊」 wu4
+//   「甲」 jia3
+fn main() {
+    let _wu4 : (); // undefined
+    let _jia3 : (); // undefined
+}
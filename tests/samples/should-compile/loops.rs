@@ -0,0 +1,43 @@
+// identifiers:
+//   「甲」 jia3
+//   「半」 ban4
+//   「負」 fu4
+//   「列」 lie4
+//   「元」 yuan2
+fn main() {
+    let mut jia3 = 5.0;
+    let _ans1 = jia3 / 2.0;
+    let ban4 = _ans1;
+    for _ in 0..(f64::ceil(ban4) as usize) {
+        let _ans2 = "一";
+        println!("{}", _ans2);
+    }
+    let _ans3 = 0.0 - jia3;
+    let fu4 = _ans3;
+    for _ in 0..(f64::ceil(fu4) as usize) {
+        let _ans4 = "二";
+        println!("{}", _ans4);
+    }
+    let mut _rand1 = 0.0;
+    while _rand1 < jia3 {
+        _rand1 += 1.0;
+        let _ans5 = jia3 - 1.0;
+        jia3 = _ans5;
+        if jia3 == 3.0 {
+            continue;
+        }
+        let _ans6 = jia3;
+        println!("{}", _ans6);
+    }
+    let mut lie4 = vec![];
+    lie4.append(&mut vec![1.0, 2.0]);
+    for yuan2 in lie4.iter().cloned() {
+        let _ans7 = yuan2;
+        println!("{}", _ans7);
+    }
+    for yuan2 in lie4.clone() {
+        lie4.push(yuan2);
+    }
+    let _ans8 = lie4.len() as f64;
+    println!("{}", _ans8);
+}
@@ -0,0 +1,30 @@
+// identifiers:
+//   「甲」 jia3
+//   「和」 he2
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+//   「己」 ji3
+//   「戊」 wu4
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let mut he2 = 0.0;
+    for yi3 in jia3.iter().cloned() {
+        he2 = yi3;
+    }
+    let _ans1 = he2;
+    println!("{}", _ans1);
+    let mut _bing3 = 1.0;
+    _bing3 = 2.0;
+    let ding1 = 3.0;
+    let _ans2 = ding1;
+    println!("{}", _ans2);
+    let mut ji3 = vec![];
+    ji3.push(1.0);
+    for _wu4 in ji3.iter().cloned() {
+        let _ans3 = "嗯";
+        println!("{}", _ans3);
+    }
+}
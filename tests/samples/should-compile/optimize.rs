@@ -0,0 +1,20 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+//   「戊」 wu4
+fn main() {
+    println!("{} {}", 4, 5);
+    println!("{}", -15);
+    let mut bing3 = vec![];
+    bing3.push(7);
+    bing3.push(8);
+    let _ans1 = bing3[1 - 1];
+    println!("{} {}", _ans1, bing3.len() as i64);
+    let _ = 0;
+    println!("{}", 9);
+    if true {
+        println!("{}", "大");
+    }
+}
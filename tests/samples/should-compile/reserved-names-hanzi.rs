@@ -0,0 +1,23 @@
+// identifiers:
+//   「loop」 r#loop
+//   「self」 self_
+//   「main」 main_
+//   「None」 None_
+//   「Self」 Self_
+//   「self_」 self_u5f
+#![allow(non_snake_case, uncommon_codepoints, mixed_script_confusables, confusable_idents)]
+fn main() {
+    let r#loop = 1.0;
+    let self_ = 2.0;
+    let main_ = 3.0;
+    let None_ = 4.0;
+    let Self_ = 5.0;
+    let self_u5f = 6.0;
+    let _ans1 = r#loop;
+    let _ans2 = self_;
+    let _ans3 = main_;
+    let _ans4 = None_;
+    let _ans5 = Self_;
+    let _ans6 = self_u5f;
+    println!("{} {} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5, _ans6);
+}
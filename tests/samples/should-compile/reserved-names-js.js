@@ -0,0 +1,7 @@
+var var_ = 1;
+var undefined_ = 2;
+var console_ = 3;
+const _ans1 = var_;
+const _ans2 = undefined_;
+const _ans3 = console_;
+console.log(_ans1, _ans2, _ans3);
@@ -0,0 +1,13 @@
+// identifiers:
+//   「var」 var
+//   「undefined」 undefined
+//   「console」 console
+fn main() {
+    let var = 1.0;
+    let undefined = 2.0;
+    let console = 3.0;
+    let _ans1 = var;
+    let _ans2 = undefined;
+    let _ans3 = console;
+    println!("{} {} {}", _ans1, _ans2, _ans3);
+}
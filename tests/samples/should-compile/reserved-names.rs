@@ -0,0 +1,22 @@
+// identifiers:
+//   「loop」 r#loop
+//   「self」 self_
+//   「main」 main_
+//   「None」 none
+//   「Self」 self__
+//   「self_」 self_u5f
+fn main() {
+    let r#loop = 1.0;
+    let self_ = 2.0;
+    let main_ = 3.0;
+    let none = 4.0;
+    let self__ = 5.0;
+    let self_u5f = 6.0;
+    let _ans1 = r#loop;
+    let _ans2 = self_;
+    let _ans3 = main_;
+    let _ans4 = none;
+    let _ans5 = self__;
+    let _ans6 = self_u5f;
+    println!("{} {} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5, _ans6);
+}
@@ -0,0 +1,30 @@
+// identifiers:
+//   「甲乙」 甲乙
+//   「綠」 綠
+//   「時」 時
+//   「是」 是
+//   「a天」 a天
+//   「甲＋乙」 甲_uff0b_乙
+//   「fn」 r#fn
+//   「3丙」 _3丙
+#![allow(non_snake_case, uncommon_codepoints, mixed_script_confusables, confusable_idents)]
+fn main() {
+    let 甲乙 = 3.0;
+    let 綠 = 5.0;
+    let 時 = 7.0;
+    let 是 = 9.0;
+    let a天 = "問天地好在。";
+    let _ans1 = 甲乙;
+    let _ans2 = 綠;
+    let _ans3 = 時;
+    let _ans4 = 是;
+    let _ans5 = a天;
+    println!("{} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5);
+    let 甲_uff0b_乙 = 1.0;
+    let r#fn = 2.0;
+    let _3丙 = 4.0;
+    let _ans6 = 甲_uff0b_乙;
+    let _ans7 = r#fn;
+    let _ans8 = _3丙;
+    println!("{} {} {}", _ans6, _ans7, _ans8);
+}
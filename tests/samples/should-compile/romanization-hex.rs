@@ -0,0 +1,19 @@
+// identifiers:
+//   「甲乙」 u7532_u4e59
+//   「綠」 u7da0
+//   「時」 u6642
+//   「是」 u662f
+//   「a天」 a_u5929
+fn main() {
+    let u7532_u4e59 = 3.0;
+    let u7da0 = 5.0;
+    let u6642 = 7.0;
+    let u662f = 9.0;
+    let a_u5929 = "問天地好在。";
+    let _ans1 = u7532_u4e59;
+    let _ans2 = u7da0;
+    let _ans3 = u6642;
+    let _ans4 = u662f;
+    let _ans5 = a_u5929;
+    println!("{} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5);
+}
@@ -0,0 +1,19 @@
+// identifiers:
+//   「甲乙」 jia_yi
+//   「綠」 lv
+//   「時」 shi
+//   「是」 shi_
+//   「a天」 a_tian
+fn main() {
+    let jia_yi = 3.0;
+    let lv = 5.0;
+    let shi = 7.0;
+    let shi_ = 9.0;
+    let a_tian = "問天地好在。";
+    let _ans1 = jia_yi;
+    let _ans2 = lv;
+    let _ans3 = shi;
+    let _ans4 = shi_;
+    let _ans5 = a_tian;
+    println!("{} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5);
+}
@@ -0,0 +1,19 @@
+// identifiers:
+//   「甲乙」 jia3_yi3
+//   「綠」 lv4
+//   「時」 shi2
+//   「是」 shi4
+//   「a天」 a_tian1
+fn main() {
+    let jia3_yi3 = 3.0;
+    let lv4 = 5.0;
+    let shi2 = 7.0;
+    let shi4 = 9.0;
+    let a_tian1 = "問天地好在。";
+    let _ans1 = jia3_yi3;
+    let _ans2 = lv4;
+    let _ans3 = shi2;
+    let _ans4 = shi4;
+    let _ans5 = a_tian1;
+    println!("{} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5);
+}
@@ -0,0 +1,19 @@
+// identifiers:
+//   「甲乙」 gaap3_jyut6
+//   「綠」 luk6
+//   「時」 si4
+//   「是」 si6
+//   「a天」 a_tin1
+fn main() {
+    let gaap3_jyut6 = 3.0;
+    let luk6 = 5.0;
+    let si4 = 7.0;
+    let si6 = 9.0;
+    let a_tin1 = "問天地好在。";
+    let _ans1 = gaap3_jyut6;
+    let _ans2 = luk6;
+    let _ans3 = si4;
+    let _ans4 = si6;
+    let _ans5 = a_tin1;
+    println!("{} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5);
+}
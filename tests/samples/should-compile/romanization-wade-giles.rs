@@ -0,0 +1,19 @@
+// identifiers:
+//   「甲乙」 chia3_i3
+//   「綠」 lu4
+//   「時」 shih2
+//   「是」 shih4
+//   「a天」 a_tien1
+fn main() {
+    let chia3_i3 = 3.0;
+    let lu4 = 5.0;
+    let shih2 = 7.0;
+    let shih4 = 9.0;
+    let a_tien1 = "問天地好在。";
+    let _ans1 = chia3_i3;
+    let _ans2 = lu4;
+    let _ans3 = shih2;
+    let _ans4 = shih4;
+    let _ans5 = a_tien1;
+    println!("{} {} {} {} {}", _ans1, _ans2, _ans3, _ans4, _ans5);
+}
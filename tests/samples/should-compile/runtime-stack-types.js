@@ -0,0 +1,88 @@
+const _stack = {
+    values: [],
+    lows: [],
+    shrunk() {
+        this.lows = this.lows.map((low) => Math.min(low, this.values.length));
+    },
+    pop() {
+        const value = this.values.pop();
+        this.shrunk();
+        return value;
+    },
+    take() {
+        const values = this.values.splice(0);
+        this.shrunk();
+        return values;
+    },
+    clear() {
+        this.values.length = 0;
+        this.shrunk();
+    },
+    enter() {
+        this.lows.push(this.values.length);
+    },
+    restore(entry) {
+        this.values = [...entry];
+        this.lows[this.lows.length - 1] = entry.length;
+    },
+    leave() {
+        this.values.length = this.lows.pop();
+        this.shrunk();
+    },
+};
+var jia3 = 0;
+var _ans1 = "天";
+_stack.values.push(_ans1);
+_stack.enter();
+if (jia3 == 0) {
+    console.log(..._stack.take());
+}
+_stack.leave();
+const _ans2 = _stack.pop();
+var yi3 = _ans2;
+console.log(..._stack.take());
+var _ans3 = "天";
+const _ans4 = 1 + 2;
+_stack.values.push(_ans3, _ans4);
+_stack.enter();
+if (true) {
+    console.log(..._stack.take());
+}
+_stack.leave();
+console.log(..._stack.take());
+var _ans5 = "地";
+const _ans6 = 1 + 2;
+_stack.values.push(_ans5, _ans6);
+_stack.enter();
+if (false) {
+    console.log(..._stack.take());
+}
+_stack.leave();
+const _ans8 = _stack.pop();
+const _ans7 = _stack.pop();
+var bing3 = _ans7;
+var ding1 = _ans8;
+const _ans9 = bing3;
+const _ans10 = ding1;
+console.log(..._stack.take(), _ans9, _ans10);
+const _ans11 = ding1 + 1;
+console.log(_ans11);
+if (bing3) {
+    var _ans12 = "真";
+    console.log(_ans12);
+}
+if (ding1 == 3) {
+    var _ans13 = "三";
+    console.log(_ans13);
+}
+var _ans14 = true;
+_stack.values.push(_ans14);
+_stack.enter();
+if (true) {
+    console.log(..._stack.take());
+}
+_stack.leave();
+const _ans15 = _stack.pop();
+_stack.clear();
+const _ans16 = _ans15 + 1;
+console.log(_ans16);
@@ -0,0 +1,190 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+/// a value of the runtime stack
+#[derive(Clone, Default, PartialEq, PartialOrd)]
+enum Value {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    List(Vec<Value>),
+    /// what `其` takes off the stack when it is empty
+    #[default]
+    Undefined,
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Num(x) => write!(f, "{}", x),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::List(elems) => {
+                let elems = elems.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "[ {} ]", elems.join(", "))
+            }
+            Value::Undefined => write!(f, "undefined"),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Num(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(elems: Vec<T>) -> Self {
+        Value::List(elems.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<Value> for f64 {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Num(x) => x,
+            Value::Bool(b) => f64::from(u8::from(b)),
+            Value::Str(s) if s.trim().is_empty() => 0.0,
+            Value::Str(s) => s.trim().parse().unwrap_or(f64::NAN),
+            Value::List(_) | Value::Undefined => f64::NAN,
+        }
+    }
+}
+
+impl From<Value> for bool {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Num(x) => x != 0.0 && !x.is_nan(),
+            Value::Str(s) => !s.is_empty(),
+            Value::Bool(b) => b,
+            Value::List(_) => true,
+            Value::Undefined => false,
+        }
+    }
+}
+
+/// the values before the blocks whose ways through leave different numbers of them
+struct Stack {
+    values: Vec<Value>,
+    /// for each of these blocks being run, how many of the values before it are left
+    lows: Vec<usize>,
+}
+
+impl Stack {
+    fn shrunk(&mut self) {
+        let len = self.values.len();
+        for low in &mut self.lows {
+            *low = (*low).min(len);
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        let value = self.values.pop().unwrap_or_default();
+        self.shrunk();
+        value
+    }
+
+    fn take(&mut self) -> Vec<Value> {
+        let values = std::mem::take(&mut self.values);
+        self.shrunk();
+        values
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.shrunk();
+    }
+
+    fn enter(&mut self) {
+        self.lows.push(self.values.len());
+    }
+
+    fn leave(&mut self) {
+        let low = self.lows.pop().unwrap();
+        self.values.truncate(low);
+        self.shrunk();
+    }
+}
+
+fn main() {
+    let mut _stack = Stack { values: vec![], lows: vec![] };
+    let jia3 = 0.0;
+    let _ans1 = "天";
+    _stack.values.push(Value::from(_ans1));
+    _stack.enter();
+    if jia3 == 0.0 {
+        println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    }
+    _stack.leave();
+    let _ans2 = _stack.pop();
+    let _yi3 = _ans2;
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    let _ans3 = "天";
+    let _ans4 = 1.0 + 2.0;
+    _stack.values.push(Value::from(_ans3));
+    _stack.values.push(Value::from(_ans4));
+    _stack.enter();
+    if true {
+        println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    }
+    _stack.leave();
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    let _ans5 = "地";
+    let _ans6 = 1.0 + 2.0;
+    _stack.values.push(Value::from(_ans5));
+    _stack.values.push(Value::from(_ans6));
+    _stack.enter();
+    if false {
+        println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    }
+    _stack.leave();
+    let _ans7 = _stack.pop();
+    let _ans8 = _stack.pop();
+    let bing3 = _ans8;
+    let ding1 = _ans7;
+    let _ans9 = bing3.clone();
+    let _ans10 = ding1.clone();
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).chain(vec![_ans9.to_string(), _ans10.to_string()]).collect::<Vec<_>>().join(" "));
+    let _ans11 = f64::from(ding1.clone()) + 1.0;
+    println!("{}", _ans11);
+    if bool::from(bing3.clone()) {
+        let _ans12 = "真";
+        println!("{}", _ans12);
+    }
+    if ding1.clone() == Value::from(3.0) {
+        let _ans13 = "三";
+        println!("{}", _ans13);
+    }
+    let _ans14 = true;
+    _stack.values.push(Value::from(_ans14));
+    _stack.enter();
+    if true {
+        println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    }
+    _stack.leave();
+    let _ans15 = _stack.pop();
+    _stack.clear();
+    let _ans16 = f64::from(_ans15) + 1.0;
+    println!("{}", _ans16);
+}
@@ -0,0 +1,125 @@
+const _stack = {
+    values: [],
+    lows: [],
+    shrunk() {
+        this.lows = this.lows.map((low) => Math.min(low, this.values.length));
+    },
+    pop() {
+        const value = this.values.pop();
+        this.shrunk();
+        return value;
+    },
+    take() {
+        const values = this.values.splice(0);
+        this.shrunk();
+        return values;
+    },
+    clear() {
+        this.values.length = 0;
+        this.shrunk();
+    },
+    enter() {
+        this.lows.push(this.values.length);
+    },
+    restore(entry) {
+        this.values = [...entry];
+        this.lows[this.lows.length - 1] = entry.length;
+    },
+    leave() {
+        this.values.length = this.lows.pop();
+        this.shrunk();
+    },
+};
+const _ans1 = 5 + 5;
+_stack.values.push(_ans1);
+_stack.enter();
+if (false) {
+    console.log(..._stack.take());
+}
+_stack.leave();
+console.log(..._stack.take());
+const _ans2 = 1 + 2;
+const _ans3 = 3 + 4;
+_stack.values.push(_ans2, _ans3);
+_stack.enter();
+if (true) {
+    const _ans4 = _stack.pop();
+    var jia3 = _ans4;
+}
+_stack.leave();
+const _ans5 = 7 + 7;
+console.log(..._stack.take(), _ans5);
+var yi3 = 3;
+const _ans6 = 7 + 7;
+const _ans7 = 8 + 8;
+_stack.values.push(_ans6, _ans7);
+_stack.enter();
+const _entry1 = [..._stack.values];
+for (let _rand1 = 0; _rand1 < yi3; _rand1++) {
+    _stack.restore(_entry1);
+    const _ans8 = yi3 - 1;
+    _stack.clear();
+    yi3 = _ans8;
+    if (yi3 == 1) {
+        break;
+    }
+}
+_stack.leave();
+const _ans10 = _stack.pop();
+const _ans9 = _stack.pop();
+var bing3 = _ans9;
+var ding1 = _ans10;
+console.log(..._stack.take());
+const _ans11 = 2 + 2;
+const _ans12 = 2 + 3;
+_stack.values.push(_ans11, _ans12);
+_stack.enter();
+if (false) {
+    const _ans13 = _stack.pop();
+    var wu4 = _ans13;
+}
+_stack.leave();
+const _ans14 = _stack.pop();
+_stack.clear();
+const _ans15 = _ans14 + 1;
+console.log(_ans15);
+const _ans16 = 4 + 4;
+const _ans17 = 6 + 6;
+_stack.values.push(_ans16, _ans17);
+_stack.enter();
+const _entry2 = [..._stack.values];
+for (let _rand2 = 0; _rand2 < 3; _rand2++) {
+    _stack.restore(_entry2);
+    _stack.enter();
+    if (true) {
+        console.log(..._stack.take());
+        _stack.lows.length -= 1;
+        continue;
+    }
+    _stack.leave();
+    const _ans18 = _stack.pop();
+    var ji3 = _ans18;
+}
+_stack.leave();
+const _ans19 = 9 + 9;
+console.log(..._stack.take(), _ans19);
+const _ans20 = 8 + 8;
+_stack.values.push(_ans20);
+_stack.enter();
+if (false) {
+    _stack.clear();
+}
+_stack.leave();
+_stack.enter();
+if (false) {
+    const _ans21 = 1 + 1;
+} else {
+    const _ans22 = _stack.pop();
+    _stack.clear();
+    if (_ans22 == 16) {
+        const _ans23 = 1 + 1;
+        console.log(_ans23);
+    }
+}
+_stack.leave();
+console.log(..._stack.take());
@@ -0,0 +1,238 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+//   「戊」 wu4
+//   「己」 ji3
+/// a value of the runtime stack
+#[derive(Clone, Default, PartialEq, PartialOrd)]
+enum Value {
+    Num(f64),
+    Str(String),
+    Bool(bool),
+    List(Vec<Value>),
+    /// what `其` takes off the stack when it is empty
+    #[default]
+    Undefined,
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Num(x) => write!(f, "{}", x),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::List(elems) => {
+                let elems = elems.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+                write!(f, "[ {} ]", elems.join(", "))
+            }
+            Value::Undefined => write!(f, "undefined"),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Value::Num(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(elems: Vec<T>) -> Self {
+        Value::List(elems.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<Value> for f64 {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Num(x) => x,
+            Value::Bool(b) => f64::from(u8::from(b)),
+            Value::Str(s) if s.trim().is_empty() => 0.0,
+            Value::Str(s) => s.trim().parse().unwrap_or(f64::NAN),
+            Value::List(_) | Value::Undefined => f64::NAN,
+        }
+    }
+}
+
+impl From<Value> for bool {
+    fn from(v: Value) -> Self {
+        match v {
+            Value::Num(x) => x != 0.0 && !x.is_nan(),
+            Value::Str(s) => !s.is_empty(),
+            Value::Bool(b) => b,
+            Value::List(_) => true,
+            Value::Undefined => false,
+        }
+    }
+}
+
+/// the values before the blocks whose ways through leave different numbers of them
+struct Stack {
+    values: Vec<Value>,
+    /// for each of these blocks being run, how many of the values before it are left
+    lows: Vec<usize>,
+}
+
+impl Stack {
+    fn shrunk(&mut self) {
+        let len = self.values.len();
+        for low in &mut self.lows {
+            *low = (*low).min(len);
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        let value = self.values.pop().unwrap_or_default();
+        self.shrunk();
+        value
+    }
+
+    fn take(&mut self) -> Vec<Value> {
+        let values = std::mem::take(&mut self.values);
+        self.shrunk();
+        values
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.shrunk();
+    }
+
+    fn enter(&mut self) {
+        self.lows.push(self.values.len());
+    }
+
+    fn restore(&mut self, entry: &[Value]) {
+        self.values = entry.to_vec();
+        *self.lows.last_mut().unwrap() = entry.len();
+    }
+
+    fn leave(&mut self) {
+        let low = self.lows.pop().unwrap();
+        self.values.truncate(low);
+        self.shrunk();
+    }
+}
+
+fn main() {
+    let mut _stack = Stack { values: vec![], lows: vec![] };
+    let _ans1 = 5.0 + 5.0;
+    _stack.values.push(Value::from(_ans1));
+    _stack.enter();
+    if false {
+        println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    }
+    _stack.leave();
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    let _ans2 = 1.0 + 2.0;
+    let _ans3 = 3.0 + 4.0;
+    _stack.values.push(Value::from(_ans2));
+    _stack.values.push(Value::from(_ans3));
+    _stack.enter();
+    if true {
+        let _ans4 = _stack.pop();
+        let _jia3 = _ans4;
+    }
+    _stack.leave();
+    let _ans5 = 7.0 + 7.0;
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).chain(vec![_ans5.to_string()]).collect::<Vec<_>>().join(" "));
+    let mut yi3 = 3.0;
+    let _ans6 = 7.0 + 7.0;
+    let _ans7 = 8.0 + 8.0;
+    _stack.values.push(Value::from(_ans6));
+    _stack.values.push(Value::from(_ans7));
+    _stack.enter();
+    let _entry1 = _stack.values.clone();
+    let mut _rand1 = 0.0;
+    while _rand1 < yi3 {
+        _rand1 += 1.0;
+        _stack.restore(&_entry1);
+        let _ans8 = yi3 - 1.0;
+        _stack.clear();
+        yi3 = _ans8;
+        if yi3 == 1.0 {
+            break;
+        }
+    }
+    _stack.leave();
+    let _ans9 = _stack.pop();
+    let _ans10 = _stack.pop();
+    let _bing3 = _ans10;
+    let _ding1 = _ans9;
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+    let _ans11 = 2.0 + 2.0;
+    let _ans12 = 2.0 + 3.0;
+    _stack.values.push(Value::from(_ans11));
+    _stack.values.push(Value::from(_ans12));
+    _stack.enter();
+    if false {
+        let _ans13 = _stack.pop();
+        let _wu4 = _ans13;
+    }
+    _stack.leave();
+    let _ans14 = _stack.pop();
+    _stack.clear();
+    let _ans15 = f64::from(_ans14) + 1.0;
+    println!("{}", _ans15);
+    let _ans16 = 4.0 + 4.0;
+    let _ans17 = 6.0 + 6.0;
+    _stack.values.push(Value::from(_ans16));
+    _stack.values.push(Value::from(_ans17));
+    _stack.enter();
+    let _entry2 = _stack.values.clone();
+    for _ in 0..3 {
+        _stack.restore(&_entry2);
+        _stack.enter();
+        if true {
+            println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+            _stack.lows.truncate(_stack.lows.len() - 1);
+            continue;
+        }
+        _stack.leave();
+        let _ans18 = _stack.pop();
+        let _ji3 = _ans18;
+    }
+    _stack.leave();
+    let _ans19 = 9.0 + 9.0;
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).chain(vec![_ans19.to_string()]).collect::<Vec<_>>().join(" "));
+    let _ans20 = 8.0 + 8.0;
+    _stack.values.push(Value::from(_ans20));
+    _stack.enter();
+    if false {
+        _stack.clear();
+    }
+    _stack.leave();
+    _stack.enter();
+    if false {
+        let _ans21 = 1.0 + 1.0;
+    } else {
+        let _ans22 = _stack.pop();
+        _stack.clear();
+        if _ans22 == Value::from(16.0) {
+            let _ans23 = 1.0 + 1.0;
+            println!("{}", _ans23);
+        }
+    }
+    _stack.leave();
+    println!("{}", _stack.take().iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "));
+}
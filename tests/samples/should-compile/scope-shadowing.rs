@@ -0,0 +1,16 @@
+// identifiers:
+//   「甲」 jia3
+fn main() {
+    let jia3 = 1.0;
+    for _ in 0..2 {
+        let jia3;
+        jia3 = 10.0;
+        let _ans1 = jia3;
+        println!("{}", _ans1);
+    }
+    let _ans2 = jia3;
+    println!("{}", _ans2);
+    let jia3 = 2.0;
+    let _ans3 = jia3;
+    println!("{}", _ans3);
+}
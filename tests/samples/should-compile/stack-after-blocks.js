@@ -0,0 +1,23 @@
+var jia3 = [];
+jia3.push(1, 2);
+const _ans1 = 1 + 2;
+if (true) {
+    const _ans2 = 3 + 4;
+}
+console.log(_ans1);
+var _ans3 = 5;
+for (let _rand1 = 0; _rand1 < 3; _rand1++) {
+    const _ans4 = 1 + 2;
+}
+const _ans5 = _ans3 + 10;
+console.log(_ans5);
+for (var yi3 of jia3) {
+    const _ans6 = yi3;
+}
+console.log();
+while (true) {
+    const _ans7 = 1 + 2;
+    break;
+}
+const _ans8 = undefined + 1;
+console.log(_ans8);
@@ -0,0 +1,28 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.append(&mut vec![1.0, 2.0]);
+    let _ans1 = 1.0 + 2.0;
+    if true {
+        let _ans2 = 3.0 + 4.0;
+    }
+    println!("{}", _ans1);
+    let _ans3 = 5.0;
+    for _ in 0..3 {
+        let _ans4 = 1.0 + 2.0;
+    }
+    let _ans5 = _ans3 + 10.0;
+    println!("{}", _ans5);
+    for yi3 in jia3.iter().cloned() {
+        let _ans6 = yi3;
+    }
+    println!("");
+    loop {
+        let _ans7 = 1.0 + 2.0;
+        break;
+    }
+    let _ans8 = f64::NAN + 1.0;
+    println!("{}", _ans8);
+}
@@ -0,0 +1,112 @@
+const _stack = {
+    values: [],
+    lows: [],
+    shrunk() {
+        this.lows = this.lows.map((low) => Math.min(low, this.values.length));
+    },
+    pop() {
+        const value = this.values.pop();
+        this.shrunk();
+        return value;
+    },
+    take() {
+        const values = this.values.splice(0);
+        this.shrunk();
+        return values;
+    },
+    clear() {
+        this.values.length = 0;
+        this.shrunk();
+    },
+    enter() {
+        this.lows.push(this.values.length);
+    },
+    restore(entry) {
+        this.values = [...entry];
+        this.lows[this.lows.length - 1] = entry.length;
+    },
+    leave() {
+        this.values.length = this.lows.pop();
+        this.shrunk();
+    },
+};
+const _ans1 = 5 + 5;
+_stack.values.push(_ans1);
+_stack.enter();
+const _entry1 = [..._stack.values];
+for (let _rand1 = 0; _rand1 < 1; _rand1++) {
+    _stack.restore(_entry1);
+    var jia3 = false;
+    const _ans2 = 1 + 2;
+    _stack.values.push(_ans2);
+    _stack.enter();
+    if (jia3) {
+        console.log(..._stack.take());
+    }
+    _stack.leave();
+    console.log(..._stack.take());
+}
+_stack.leave();
+const _ans3 = 5 + 5;
+_stack.values.push(_ans3);
+_stack.enter();
+const _entry2 = [..._stack.values];
+for (let _rand2 = 0; _rand2 < 2; _rand2++) {
+    _stack.restore(_entry2);
+    const _ans4 = 1 + 2;
+    _stack.values.push(_ans4);
+    _stack.enter();
+    if (false) {
+        console.log(..._stack.take());
+    }
+    _stack.leave();
+    const _ans5 = _stack.pop();
+    _stack.clear();
+    const _ans6 = _ans5 + 1;
+    console.log(_ans6);
+}
+_stack.leave();
+const _ans7 = 5 + 5;
+_stack.values.push(_ans7);
+_stack.enter();
+const _entry3 = [..._stack.values];
+for (let _rand3 = 0; _rand3 < 2; _rand3++) {
+    _stack.restore(_entry3);
+    const _ans8 = 1 + 2;
+    _stack.values.push(_ans8);
+    _stack.enter();
+    if (true) {
+        console.log(..._stack.take());
+    }
+    _stack.leave();
+    const _ans9 = _stack.pop();
+    _stack.clear();
+    const _ans10 = _ans9 + 1;
+    console.log(_ans10);
+}
+_stack.leave();
+console.log(..._stack.take());
+const _ans11 = 2 + 2;
+_stack.values.push(_ans11);
+_stack.enter();
+const _entry4 = [..._stack.values];
+for (let _rand4 = 0; _rand4 < 2; _rand4++) {
+    _stack.restore(_entry4);
+    _stack.enter();
+    const _entry5 = [..._stack.values];
+    for (let _rand5 = 0; _rand5 < 1; _rand5++) {
+        _stack.restore(_entry5);
+        const _ans12 = 1 + 2;
+        _stack.values.push(_ans12);
+        _stack.enter();
+        if (true) {
+            _stack.clear();
+            _stack.lows.length -= 1;
+            break;
+        }
+        _stack.leave();
+    }
+    _stack.leave();
+    console.log(..._stack.take());
+}
+_stack.leave();
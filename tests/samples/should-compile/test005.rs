@@ -0,0 +1,10 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let _jia3 = 3.0;
+    println!("");
+    let yi3 = 5.0;
+    let _ans1 = yi3;
+    println!("{}", _ans1);
+}
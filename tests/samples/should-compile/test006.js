@@ -0,0 +1,5 @@
+var jia3 = 3;
+for (let _rand1 = 0; _rand1 < jia3; _rand1++) {
+    var _ans1 = "問天地好在。";
+    console.log(_ans1);
+}
@@ -0,0 +1,9 @@
+// identifiers:
+//   「甲」 jia3
+fn main() {
+    let jia3 = 3.0;
+    for _ in 0..(f64::ceil(jia3) as usize) {
+        let _ans1 = "問天地好在。";
+        println!("{}", _ans1);
+    }
+}
@@ -0,0 +1,16 @@
+var jia3 = 3;
+var _ans1 = 9;
+var _ans2 = 7;
+console.log(_ans1, _ans2);
+var yi3 = 3;
+var bing3 = 9;
+var _ans3 = 7;
+console.log(_ans3);
+var ding1 = 3;
+var wu4 = 9;
+var ji3 = 7;
+console.log();
+var geng1 = 3;
+var xin1 = 9;
+var ren2 = 0;
+console.log();
@@ -0,0 +1,28 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+//   「戊」 wu4
+//   「己」 ji3
+//   「庚」 geng1
+//   「辛」 xin1
+//   「壬」 ren2
+fn main() {
+    let _jia3 = 3.0;
+    let _ans1 = 9.0;
+    let _ans2 = 7.0;
+    println!("{} {}", _ans1, _ans2);
+    let _yi3 = 3.0;
+    let _bing3 = 9.0;
+    let _ans3 = 7.0;
+    println!("{}", _ans3);
+    let _ding1 = 3.0;
+    let _wu4 = 9.0;
+    let _ji3 = 7.0;
+    println!("");
+    let _geng1 = 3.0;
+    let _xin1 = 9.0;
+    let _ren2 = 0.0;
+    println!("");
+}
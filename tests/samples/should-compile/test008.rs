@@ -0,0 +1,11 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let jia3 = 3.0;
+    let _yi3 = 5.0;
+    for _ in 0..(f64::ceil(jia3) as usize) {
+        let _ans1 = "問天地好在。";
+        println!("{}", _ans1);
+    }
+}
@@ -1,5 +1,7 @@
+// identifiers:
+//   「甲」 jia3
 fn main() {
-    let JIA3 = 3.0;
+    let _jia3 = 3.0;
     let _ans1 = "問天地好在。";
     println!("{}", _ans1);
 }
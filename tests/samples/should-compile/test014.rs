@@ -1,6 +1,8 @@
+// identifiers:
+//   「甲」 jia3
 fn main() {
-    let mut JIA3 = 3.0;
+    let mut _jia3 = 3.0;
     let _ans1 = "問天地好在。";
     println!("{}", _ans1);
-    JIA3 = 4.0;
+    _jia3 = 4.0;
 }
@@ -0,0 +1,9 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+fn main() {
+    let _jia3 = 3.0;
+    let _yi3 = false;
+    let _bing3 = "噫吁戲";
+}
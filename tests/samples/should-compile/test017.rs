@@ -1,5 +1,7 @@
+// identifiers:
+//   「甲」 jia3
 fn main() {
-    let JIA3 = 3.0;
+    let _jia3 = 3.0;
     let _ans1 = 5.0;
     let _ans2 = 2.0;
     let _ans3 = _ans2 + 5.0;
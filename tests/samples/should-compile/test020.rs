@@ -0,0 +1,9 @@
+// identifiers:
+//   「作c」 zuo4_c
+//   「坐a」 zuo4_a
+fn main() {
+    let _zuo4_c = 3.0;
+    println!("");
+    let _zuo4_a = 5.0;
+    println!("");
+}
@@ -1,6 +1,8 @@
+// identifiers:
+//   「作c」 zuo4_c
 fn main() {
     let _ans1 = 4.0;
     let _ans2 = 7.0 - _ans1;
-    let ZUO4_ = 2.0;
+    let _zuo4_c = 2.0;
     println!("{}", _ans2);
 }
@@ -1,3 +1,6 @@
+// identifiers:
+//   「作c」 zuo4_c
+//   「坐a」 zuo4_a
 fn main() {
     let _ans1 = 3.0;
     let _ans2 = _ans1 + 5.0;
@@ -8,8 +11,8 @@ fn main() {
     let _ans7 = 3.0;
     let _ans8 = 0.0;
     let _ans9 = _ans8 - 7.0;
-    let ZUO4_ = 3.0;
+    let _zuo4_c = 3.0;
     println!("{}", _ans9);
-    let ZUO4__ = 5.0;
+    let _zuo4_a = 5.0;
     println!("");
 }
@@ -0,0 +1,6 @@
+// identifiers:
+//   「甲」 jia3
+fn main() {
+    let _jia3 = 3.0;
+    let _jia3 = 3.0;
+}
@@ -0,0 +1,7 @@
+// identifiers:
+//   「甲」 jia3
+fn main() {
+    let _jia3 = 0.0;
+    let _jia3 = 0.0;
+    let _jia3 = 0.0;
+}
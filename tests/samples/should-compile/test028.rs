@@ -0,0 +1,6 @@
+// identifiers:
+//   「丙」 bing3
+fn main() {
+    let _ans1 = 1.0 + 3.0;
+    let _bing3 = _ans1;
+}
@@ -0,0 +1,9 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let _ans1 = 1.0 + 3.0;
+    let _ans2 = 6.0 + 9.0;
+    let _jia3 = _ans1;
+    let _yi3 = _ans2;
+}
@@ -1,8 +1,11 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
 fn main() {
     let _ans1 = 2.0 + 3.0;
     let _ans2 = 1.0 + 3.0;
     let _ans3 = 3.0 + 3.0;
-    let JIA3 = _ans2;
-    let YI3 = _ans3;
+    let _jia3 = _ans2;
+    let _yi3 = _ans3;
     println!("{}", _ans1);
 }
@@ -0,0 +1,21 @@
+// identifiers:
+//   「丙」 bing3
+//   「丁」 ding1
+//   「戊」 wu4
+//   「己」 ji3
+//   「庚」 geng1
+fn main() {
+    let _ans1 = 2.0 + 3.0;
+    let _ans2 = 1.0 + 3.0;
+    let _ans3 = 3.0 + 3.0;
+    let _bing3 = _ans2;
+    let _ding1 = _ans3;
+    println!("{}", _ans1);
+    let _ans4 = 2.0 + 3.0;
+    let _ans5 = 1.0 + 3.0;
+    let _ans6 = 3.0 + 3.0;
+    let _wu4 = _ans4;
+    let _ji3 = _ans5;
+    let _geng1 = _ans6;
+    println!("");
+}
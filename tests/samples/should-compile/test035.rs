@@ -1,14 +1,19 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
 fn main() {
     let _ans1 = 2.0 + 3.0;
     let _ans2 = 1.0 + 3.0;
     let _ans3 = 3.0 + 3.0;
-    let JIA3 = _ans3;
-    let YI3 = _ans2;
+    let _jia3 = _ans3;
+    let _yi3 = _ans2;
     println!("{}", _ans1);
     let _ans4 = 2.0 + 3.0;
     let _ans5 = 1.0 + 3.0;
     let _ans6 = 3.0 + 3.0;
-    let BING3 = _ans5;
-    let DING1 = _ans6;
+    let _bing3 = _ans5;
+    let _ding1 = _ans6;
     println!("{}", _ans4);
 }
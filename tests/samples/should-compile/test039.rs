@@ -0,0 +1,15 @@
+// identifiers:
+//   「負四」 fu4_si4
+//   「負九」 fu4_jiu3
+fn main() {
+    let _ans1 = 0.0 - 4.0;
+    let fu4_si4 = _ans1;
+    let _ans2 = 0.0 - 9.0;
+    let fu4_jiu3 = _ans2;
+    let _ans3 = 9.0 % fu4_si4;
+    println!("{}", _ans3);
+    let _ans4 = fu4_jiu3 % 4.0;
+    println!("{}", _ans4);
+    let _ans5 = fu4_jiu3 % fu4_si4;
+    println!("{}", _ans5);
+}
@@ -0,0 +1,2 @@
+const _ans1 = 2 % 3;
+console.log(_ans1);
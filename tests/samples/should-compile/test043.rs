@@ -0,0 +1,9 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let jia3 = false;
+    let yi3 = true;
+    let _ans1 = jia3 || yi3;
+    println!("{}", _ans1);
+}
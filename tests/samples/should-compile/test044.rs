@@ -0,0 +1,17 @@
+// identifiers:
+//   「頭」 tou2
+//   「足」 zu2
+//   「兔」 tu4
+//   「雉」 zhi4
+fn main() {
+    let tou2 = 35.0;
+    let zu2 = 94.0;
+    let _ans1 = zu2 / 2.0;
+    let _ans2 = _ans1 - tou2;
+    let tu4 = _ans2;
+    let _ans3 = tou2 - tu4;
+    let zhi4 = _ans3;
+    let _ans4 = zhi4;
+    let _ans5 = tu4;
+    println!("{} {}", _ans4, _ans5);
+}
@@ -0,0 +1,40 @@
+// identifiers:
+//   「頭」 tou2
+//   「臂」 bi4
+//   「足」 zu2
+//   「尾」 wei3
+//   「刑天」 xing2_tian1
+//   「許狐和」 xu3_hu2_he2
+//   「十倍許狐和」 shi2_bei4_xu3_hu2_he2
+//   「兩頭蛇」 liang3_tou2_she2
+//   「九尾狐」 jiu3_wei3_hu2
+//   「許德拉」 xu3_de2_la1
+fn main() {
+    let tou2 = 340.0;
+    let bi4 = 32.0;
+    let zu2 = 216.0;
+    let wei3 = 417.0;
+    let _ans1 = bi4 / 2.0;
+    let xing2_tian1 = _ans1;
+    let _ans2 = xing2_tian1 * 2.0;
+    let _ans3 = zu2 - _ans2;
+    let _ans4 = _ans3 / 4.0;
+    let xu3_hu2_he2 = _ans4;
+    let _ans5 = xu3_hu2_he2 * 10.0;
+    let shi2_bei4_xu3_hu2_he2 = _ans5;
+    let _ans6 = tou2 + wei3;
+    let _ans7 = _ans6 - shi2_bei4_xu3_hu2_he2;
+    let _ans8 = _ans7 / 3.0;
+    let liang3_tou2_she2 = _ans8;
+    let _ans9 = wei3 - liang3_tou2_she2;
+    let _ans10 = _ans9 - xu3_hu2_he2;
+    let _ans11 = _ans10 / 8.0;
+    let jiu3_wei3_hu2 = _ans11;
+    let _ans12 = xu3_hu2_he2 - jiu3_wei3_hu2;
+    let xu3_de2_la1 = _ans12;
+    let _ans13 = xu3_de2_la1;
+    let _ans14 = jiu3_wei3_hu2;
+    let _ans15 = xing2_tian1;
+    let _ans16 = liang3_tou2_she2;
+    println!("{} {} {} {}", _ans13, _ans14, _ans15, _ans16);
+}
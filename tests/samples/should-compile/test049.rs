@@ -0,0 +1,12 @@
+// identifiers:
+//   「器量」 qi4_liang4
+//   「酒量」 jiu3_liang4
+fn main() {
+    let qi4_liang4 = 10.0;
+    let mut jiu3_liang4 = 9.0;
+    if jiu3_liang4 > qi4_liang4 {
+        jiu3_liang4 = qi4_liang4;
+    }
+    let _ans1 = jiu3_liang4;
+    println!("{}", _ans1);
+}
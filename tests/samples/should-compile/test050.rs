@@ -0,0 +1,12 @@
+// identifiers:
+//   「貯」 zhu3
+fn main() {
+    let zhu3 = 49.0;
+    if zhu3 > 50.0 {
+        let _ans1 = zhu3 + 1.0;
+        let _ = _ans1;
+    } else {
+        let _ans2 = zhu3 - 1.0;
+        let _ = _ans2;
+    }
+}
@@ -1,6 +1,8 @@
+// identifiers:
+//   「甲」 jia3
 fn main() {
-    let JIA3 = true;
-    if JIA3 {
+    let jia3 = true;
+    if jia3 {
         let _ans1 = "陽者。歲之主也。";
         println!("{}", _ans1);
     } else {
@@ -1,20 +1,22 @@
+// identifiers:
+//   「物」 wu4
 fn main() {
-    let mut WU4 = "人";
-    if WU4 == "禽獸" {
+    let wu4 = "人";
+    if wu4 == "禽獸" {
         let _ans1 = "爾芻狗也。";
         println!("{}", _ans1);
-    } else if WU4 == "草木" {
+    } else if wu4 == "草木" {
         let _ans2 = "爾亦芻狗也。";
         println!("{}", _ans2);
-    } else if WU4 == "人" {
+    } else if wu4 == "人" {
         let _ans3 = "爾雖人。於我實芻狗也。";
         println!("{}", _ans3);
-    } else if WU4 == "芻狗" {
+    } else if wu4 == "芻狗" {
         let _ans4 = "更不待言。";
         println!("{}", _ans4);
     } else {
         let _ans5 = "吾不知爾何物。然爾之為芻狗明也。";
         println!("{}", _ans5);
     }
-    WU4 = "芻狗";
+    let _ = "芻狗";
 }
@@ -0,0 +1,14 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+fn main() {
+    let jia3 = false;
+    let yi3 = true;
+    let _ans1 = jia3 && yi3;
+    let _bing3 = _ans1;
+    let _ans2 = jia3 || yi3;
+    let _bing3 = _ans2;
+    let _ans3 = !jia3;
+    let _bing3 = _ans3;
+}
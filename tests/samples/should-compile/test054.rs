@@ -1,18 +1,22 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
 fn main() {
-    let JIA3 = true;
-    let YI3 = true;
-    let _ans1 = JIA3 && YI3;
-    let BING3 = _ans1;
-    if BING3 {
+    let jia3 = true;
+    let yi3 = true;
+    let _ans1 = jia3 && yi3;
+    let bing3 = _ans1;
+    if bing3 {
         let _ans2 = "古之人誠不我欺。";
         println!("{}", _ans2);
     }
-    let _ans3 = JIA3 && YI3;
+    let _ans3 = jia3 && yi3;
     if _ans3 {
         let _ans4 = "古之人誠不我欺。";
         println!("{}", _ans4);
     }
-    let _ans5 = JIA3 && YI3;
+    let _ans5 = jia3 && yi3;
     if _ans5 {
         let _ans6 = "古之人誠不我欺。";
         println!("{}", _ans6);
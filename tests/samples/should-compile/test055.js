@@ -0,0 +1,15 @@
+var jia3 = false;
+var yi3 = false;
+const _ans1 = jia3 || yi3;
+var bing3 = _ans1;
+const _ans2 = !bing3;
+var ding1 = _ans2;
+if (ding1) {
+    var _ans3 = "古之人誠不我欺。";
+    console.log(_ans3);
+}
+const _ans4 = jia3 || yi3;
+if (!_ans4) {
+    var _ans5 = "古之人誠不我欺。";
+    console.log(_ans5);
+}
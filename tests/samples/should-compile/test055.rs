@@ -0,0 +1,22 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+fn main() {
+    let jia3 = false;
+    let yi3 = false;
+    let _ans1 = jia3 || yi3;
+    let bing3 = _ans1;
+    let _ans2 = !bing3;
+    let ding1 = _ans2;
+    if ding1 {
+        let _ans3 = "古之人誠不我欺。";
+        println!("{}", _ans3);
+    }
+    let _ans4 = jia3 || yi3;
+    if !_ans4 {
+        let _ans5 = "古之人誠不我欺。";
+        println!("{}", _ans5);
+    }
+}
@@ -0,0 +1,24 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丁」 ding1
+fn main() {
+    let jia3 = false;
+    let yi3 = false;
+    let _ans1 = 1.0 + 3.0;
+    let _ans2 = jia3 || yi3;
+    let bing3 = _ans2;
+    let _ans3 = !bing3;
+    let ding1 = _ans3;
+    if ding1 {
+        let _ans4 = "古之人誠不我欺。";
+    }
+    println!("{}", _ans1);
+    let _ans5 = 1.0 + 3.0;
+    let _ans6 = jia3 || yi3;
+    if !_ans6 {
+        let _ans7 = "古之人誠不我欺。";
+    }
+    println!("");
+}
@@ -1,20 +1,30 @@
+// identifiers:
+//   「魯人耶」 lu3_ren2_ye2
+//   「衛人耶」 wei4_ren2_ye2
+//   「德行科耶」 de2_xing2_ke1_ye2
+//   「政事科耶」 zheng4_shi4_ke1_ye2
+//   「複姓耶」 fu4_xing4_ye2
+//   「未仕耶」 wei4_shi4_ye2
+//   「蚤死耶」 zao3_si3_ye2
+//   「病厲耶」 bing4_li4_ye2
+//   「戰死耶」 zhan4_si3_ye2
 fn main() {
-    let LU3REN2YE2 = true;
-    let WEI4REN2YE2 = false;
-    let DE2XING2KE1YE2 = false;
-    let ZHENG4SHI4KE1YE2 = true;
-    let FU4XING4YE2 = false;
-    let WEI4SHI4YE2 = false;
-    let ZAO3SI3YE2 = false;
-    let BING4LI4YE2 = false;
-    let ZHAN4SI3YE2 = true;
-    let _ans1 = LU3REN2YE2;
+    let lu3_ren2_ye2 = true;
+    let wei4_ren2_ye2 = false;
+    let de2_xing2_ke1_ye2 = false;
+    let zheng4_shi4_ke1_ye2 = true;
+    let fu4_xing4_ye2 = false;
+    let wei4_shi4_ye2 = false;
+    let zao3_si3_ye2 = false;
+    let bing4_li4_ye2 = false;
+    let zhan4_si3_ye2 = true;
+    let _ans1 = lu3_ren2_ye2;
     if _ans1 {
-        let _ans2 = DE2XING2KE1YE2;
+        let _ans2 = de2_xing2_ke1_ye2;
         if _ans2 {
-            let _ans3 = WEI4SHI4YE2;
+            let _ans3 = wei4_shi4_ye2;
             if _ans3 {
-                let _ans4 = ZAO3SI3YE2;
+                let _ans4 = zao3_si3_ye2;
                 if _ans4 {
                     let _ans5 = "賢哉。回也。人不堪其憂。回也不改其樂。";
                     println!("{}", _ans5);
@@ -23,7 +33,7 @@ fn main() {
                     println!("{}", _ans6);
                 }
             } else {
-                let _ans7 = BING4LI4YE2;
+                let _ans7 = bing4_li4_ye2;
                 if _ans7 {
                     let _ans8 = "亡之。命也夫。斯人也。而有斯疾也。";
                     println!("{}", _ans8);
@@ -33,9 +43,9 @@ fn main() {
                 }
             }
         } else {
-            let _ans10 = ZHENG4SHI4KE1YE2;
+            let _ans10 = zheng4_shi4_ke1_ye2;
             if _ans10 {
-                let _ans11 = ZHAN4SI3YE2;
+                let _ans11 = zhan4_si3_ye2;
                 if _ans11 {
                     let _ans12 = "若由也。不得其死然。";
                     println!("{}", _ans12);
@@ -49,12 +59,12 @@ fn main() {
             }
         }
     } else {
-        let _ans15 = FU4XING4YE2;
+        let _ans15 = fu4_xing4_ye2;
         if _ans15 {
             let _ans16 = "賜也。始可與言詩已矣。告諸往而知來者。";
             println!("{}", _ans16);
         } else {
-            let _ans17 = WEI4REN2YE2;
+            let _ans17 = wei4_ren2_ye2;
             if _ans17 {
                 let _ans18 = "起予者。商也。始可與言詩已矣。";
                 println!("{}", _ans18);
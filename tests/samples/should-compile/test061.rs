@@ -0,0 +1,6 @@
+// identifiers:
+//   「列」 lie4
+fn main() {
+    let mut lie4 = vec![];
+    lie4.append(&mut vec![1.0, 2.0, 3.0]);
+}
@@ -0,0 +1,12 @@
+// identifiers:
+//   「列」 lie4
+//   「元」 yuan2
+fn main() {
+    let mut lie4 = vec![];
+    lie4.append(&mut vec![1.0, 2.0, 3.0]);
+    for yuan2 in lie4.iter().cloned() {
+        let _ans1 = yuan2;
+        let _ans2 = "者。亦列中之物也。";
+        println!("{} {}", _ans1, _ans2);
+    }
+}
@@ -0,0 +1,32 @@
+// identifiers:
+//   「始」 shi3
+//   「戊」 wu4
+//   「戌」 xu1
+//   「甲」 jia3
+fn main() {
+    let shi3 = 999.0;
+    let mut wu4 = shi3;
+    for _ in 0..(f64::ceil(shi3) as usize) {
+        let mut xu1 = wu4;
+        for _ in 0..(f64::ceil(wu4) as usize) {
+            let _ans1 = wu4 * xu1;
+            let jia3 = _ans1;
+            if jia3 < 10.0 {
+                let _ans2 = xu1;
+                let _ans3 = wu4;
+                let _ans4 = "如";
+                let _ans5 = jia3;
+                println!("{} {} {} {}", _ans2, _ans3, _ans4, _ans5);
+            } else {
+                let _ans6 = xu1;
+                let _ans7 = wu4;
+                let _ans8 = jia3;
+                println!("{} {} {}", _ans6, _ans7, _ans8);
+            }
+            let _ans9 = xu1 - 1.0;
+            xu1 = _ans9;
+        }
+        let _ans10 = wu4 - 1.0;
+        wu4 = _ans10;
+    }
+}
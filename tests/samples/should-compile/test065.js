@@ -0,0 +1,15 @@
+var jia3 = 91;
+var yi3 = 49;
+while (true) {
+    if (jia3 == yi3) {
+        break;
+    } else if (jia3 > yi3) {
+        const _ans1 = jia3 - yi3;
+        jia3 = _ans1;
+    } else {
+        const _ans2 = yi3 - jia3;
+        yi3 = _ans2;
+    }
+}
+const _ans3 = jia3;
+console.log(_ans3);
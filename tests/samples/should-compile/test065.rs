@@ -0,0 +1,20 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = 91.0;
+    let mut yi3 = 49.0;
+    loop {
+        if jia3 == yi3 {
+            break;
+        } else if jia3 > yi3 {
+            let _ans1 = jia3 - yi3;
+            jia3 = _ans1;
+        } else {
+            let _ans2 = yi3 - jia3;
+            yi3 = _ans2;
+        }
+    }
+    let _ans3 = jia3;
+    println!("{}", _ans3);
+}
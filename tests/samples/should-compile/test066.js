@@ -0,0 +1,14 @@
+var jia3 = 0;
+var yi3 = 0;
+for (let _rand1 = 0; _rand1 < 100; _rand1++) {
+    const _ans1 = jia3 + 1;
+    jia3 = _ans1;
+    const _ans2 = jia3 % 2;
+    if (_ans2 == 1) {
+        continue;
+    }
+    const _ans3 = yi3 + jia3;
+    yi3 = _ans3;
+}
+const _ans4 = yi3;
+console.log(_ans4);
@@ -0,0 +1,19 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = 0.0;
+    let mut yi3 = 0.0;
+    for _ in 0..100 {
+        let _ans1 = jia3 + 1.0;
+        jia3 = _ans1;
+        let _ans2 = jia3 % 2.0;
+        if _ans2 == 1.0 {
+            continue;
+        }
+        let _ans3 = yi3 + jia3;
+        yi3 = _ans3;
+    }
+    let _ans4 = yi3;
+    println!("{}", _ans4);
+}
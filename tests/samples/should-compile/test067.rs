@@ -0,0 +1,7 @@
+// identifiers:
+//   「甲」 jia3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+}
@@ -0,0 +1,6 @@
+// identifiers:
+//   「乙」 yi3
+fn main() {
+    let mut yi3 = vec![];
+    yi3.append(&mut vec![2.0, 9.0, 4.0, 22.0]);
+}
@@ -0,0 +1,16 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丙」 bing3
+//   「丑」 chou3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let mut yi3 = vec![];
+    yi3.append(&mut vec![2.0, 9.0, 4.0, 22.0]);
+    let _ans1 = 1.0 + 3.0;
+    let _ans2 = [&jia3[..], &yi3[..]].concat();
+    let _bing3 = _ans1;
+    let _chou3 = _ans2;
+}
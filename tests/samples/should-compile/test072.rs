@@ -0,0 +1,19 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「丁」 ding1
+//   「丙」 bing3
+//   「戊」 wu4
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let mut yi3 = vec![];
+    yi3.append(&mut vec![2.0, 9.0, 4.0, 22.0]);
+    let mut ding1 = vec![];
+    ding1.push(9.0);
+    let _ans1 = [&jia3[..], &yi3[..]].concat();
+    let bing3 = _ans1;
+    let _ans2 = [&jia3[..], &yi3[..], &bing3[..], &ding1[..]].concat();
+    let _wu4 = _ans2;
+}
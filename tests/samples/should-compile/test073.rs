@@ -0,0 +1,17 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let mut yi3 = vec![];
+    yi3.append(&mut vec![2.0, 9.0, 4.0, 22.0]);
+    let _ans1 = jia3[1 - 1];
+    println!("{}", _ans1);
+    let _ans2 = jia3[2 - 1];
+    println!("{}", _ans2);
+    let _ans3 = yi3[4 - 1];
+    let _ans4 = _ans3 + 45.0;
+    println!("{}", _ans4);
+}
@@ -0,0 +1,9 @@
+var jia3 = [];
+jia3.push(3);
+jia3.push(5);
+var yi3 = [];
+yi3.push(2, 9, 4, 22);
+const _ans1 = jia3.concat(yi3);
+var bing3 = _ans1;
+jia3[1 - 1] = 5;
+yi3[3 - 1] = bing3[4 - 1];
@@ -0,0 +1,20 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let _ans1 = jia3.len() as f64;
+    println!("{}", _ans1);
+    let mut yi3 = 1.0;
+    loop {
+        if yi3 > (jia3.len() as f64) {
+            break;
+        }
+        let _ans2 = jia3[(yi3 as usize) - 1];
+        println!("{}", _ans2);
+        let _ans3 = yi3 + 1.0;
+        yi3 = _ans3;
+    }
+}
@@ -0,0 +1,5 @@
+var jia3 = [];
+jia3.push(3);
+jia3.push(5);
+const _ans1 = jia3.slice(1);
+var yi3 = _ans1;
@@ -0,0 +1,10 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let _ans1 = &jia3[1..].to_vec();
+    let _yi3 = _ans1;
+}
@@ -0,0 +1,13 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+//   「元」 yuan2
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let mut yi3 = vec![];
+    for yuan2 in jia3.iter().cloned() {
+        yi3.push(yuan2);
+    }
+}
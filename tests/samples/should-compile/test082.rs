@@ -0,0 +1,27 @@
+// identifiers:
+//   「其所」 qi2_suo3
+//   「所難」 suo3_nan2
+//   「其方」 qi2_fang1
+//   「所沾」 suo3_zhan1
+//   「所贈」 suo3_zeng4
+//   「所報」 suo3_bao4
+//   「所感」 suo3_gan3
+//   「所傷」 suo3_shang1
+fn main() {
+    let mut qi2_suo3 = vec![];
+    let mut suo3_nan2 = vec![];
+    let mut qi2_fang1 = vec![];
+    let mut suo3_zhan1 = vec![];
+    let mut suo3_zeng4 = vec![];
+    let mut suo3_bao4 = vec![];
+    let mut suo3_gan3 = vec![];
+    let mut suo3_shang1 = vec![];
+    qi2_suo3.append(&mut vec!["太山", "桂林", "漢陽", "雁門"]);
+    suo3_nan2.append(&mut vec!["樑父艱", "湘水深", "隴阪長", "雪雰雰"]);
+    qi2_fang1.append(&mut vec!["東", "南", "西", "北"]);
+    suo3_zhan1.append(&mut vec!["翰", "襟", "裳", "巾"]);
+    suo3_zeng4.append(&mut vec!["金錯刀", "琴琅玕", "貂襜褕", "錦繡段"]);
+    suo3_bao4.append(&mut vec!["英瓊瑤", "雙玉盤", "明月珠", "青玉案"]);
+    suo3_gan3.append(&mut vec!["逍遙", "惆悵", "踟躕", "增嘆"]);
+    suo3_shang1.append(&mut vec!["勞", "傷", "紆", "惋"]);
+}
@@ -0,0 +1,41 @@
+// identifiers:
+//   「其所」 qi2_suo3
+//   「所難」 suo3_nan2
+//   「其方」 qi2_fang1
+//   「所沾」 suo3_zhan1
+//   「所贈」 suo3_zeng4
+//   「所報」 suo3_bao4
+//   「所感」 suo3_gan3
+//   「所傷」 suo3_shang1
+//   「地」 di4
+//   「寶」 bao3
+fn main() {
+    let mut qi2_suo3 = vec![];
+    let mut suo3_nan2 = vec![];
+    let mut qi2_fang1 = vec![];
+    let mut suo3_zhan1 = vec![];
+    let mut suo3_zeng4 = vec![];
+    let mut suo3_bao4 = vec![];
+    let mut suo3_gan3 = vec![];
+    let mut suo3_shang1 = vec![];
+    qi2_suo3.append(&mut vec!["太山", "桂林", "漢陽", "雁門"]);
+    suo3_nan2.append(&mut vec!["樑父艱", "湘水深", "隴阪長", "雪雰雰"]);
+    qi2_fang1.append(&mut vec!["東", "南", "西", "北"]);
+    suo3_zhan1.append(&mut vec!["翰", "襟", "裳", "巾"]);
+    suo3_zeng4.append(&mut vec!["金錯刀", "琴琅玕", "貂襜褕", "錦繡段"]);
+    suo3_bao4.append(&mut vec!["英瓊瑤", "雙玉盤", "明月珠", "青玉案"]);
+    suo3_gan3.append(&mut vec!["逍遙", "惆悵", "踟躕", "增嘆"]);
+    suo3_shang1.append(&mut vec!["勞", "傷", "紆", "惋"]);
+    for di4 in qi2_suo3.iter().cloned() {
+        let _ans1 = di4;
+        println!("{}", _ans1);
+    }
+    let _ans2 = "此皆有所思之地也";
+    println!("{}", _ans2);
+    for bao3 in suo3_zeng4.iter().cloned() {
+        let _ans3 = bao3;
+        println!("{}", _ans3);
+    }
+    let _ans4 = "此皆美人之所贈也";
+    println!("{}", _ans4);
+}
@@ -0,0 +1,51 @@
+// identifiers:
+//   「其所」 qi2_suo3
+//   「所難」 suo3_nan2
+//   「其方」 qi2_fang1
+//   「所沾」 suo3_zhan1
+//   「所贈」 suo3_zeng4
+//   「所報」 suo3_bao4
+//   「所感」 suo3_gan3
+//   「所傷」 suo3_shang1
+//   「章」 zhang1
+fn main() {
+    let mut qi2_suo3 = vec![];
+    let mut suo3_nan2 = vec![];
+    let mut qi2_fang1 = vec![];
+    let mut suo3_zhan1 = vec![];
+    let mut suo3_zeng4 = vec![];
+    let mut suo3_bao4 = vec![];
+    let mut suo3_gan3 = vec![];
+    let mut suo3_shang1 = vec![];
+    qi2_suo3.append(&mut vec!["太山", "桂林", "漢陽", "雁門"]);
+    suo3_nan2.append(&mut vec!["樑父艱", "湘水深", "隴阪長", "雪雰雰"]);
+    qi2_fang1.append(&mut vec!["東", "南", "西", "北"]);
+    suo3_zhan1.append(&mut vec!["翰", "襟", "裳", "巾"]);
+    suo3_zeng4.append(&mut vec!["金錯刀", "琴琅玕", "貂襜褕", "錦繡段"]);
+    suo3_bao4.append(&mut vec!["英瓊瑤", "雙玉盤", "明月珠", "青玉案"]);
+    suo3_gan3.append(&mut vec!["逍遙", "惆悵", "踟躕", "增嘆"]);
+    suo3_shang1.append(&mut vec!["勞", "傷", "紆", "惋"]);
+    let mut zhang1 = 1.0;
+    let _ans1 = qi2_suo3.len() as f64;
+    for _ in 0..(f64::ceil(_ans1) as usize) {
+        let _ans2 = "我所思兮在";
+        let _ans3 = qi2_suo3[(zhang1 as usize) - 1];
+        let _ans4 = "。欲往從之";
+        let _ans5 = suo3_nan2[(zhang1 as usize) - 1];
+        let _ans6 = "。側身";
+        let _ans7 = qi2_fang1[(zhang1 as usize) - 1];
+        let _ans8 = "望涕沾";
+        let _ans9 = suo3_zhan1[(zhang1 as usize) - 1];
+        let _ans10 = "。美人贈我";
+        let _ans11 = suo3_zeng4[(zhang1 as usize) - 1];
+        let _ans12 = "。何以報之";
+        let _ans13 = suo3_bao4[(zhang1 as usize) - 1];
+        let _ans14 = "。路遠莫致倚";
+        let _ans15 = suo3_gan3[(zhang1 as usize) - 1];
+        let _ans16 = "。何爲懷憂心煩";
+        let _ans17 = suo3_shang1[(zhang1 as usize) - 1];
+        println!("{} {} {} {} {} {} {} {} {} {} {} {} {} {} {} {}", _ans2, _ans3, _ans4, _ans5, _ans6, _ans7, _ans8, _ans9, _ans10, _ans11, _ans12, _ans13, _ans14, _ans15, _ans16, _ans17);
+        let _ans18 = zhang1 + 1.0;
+        zhang1 = _ans18;
+    }
+}
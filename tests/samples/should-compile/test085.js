@@ -0,0 +1,7 @@
+var jia3 = [];
+jia3.push(3);
+jia3.push(5);
+var yi3 = [];
+for (var yuan2 of jia3) {
+    yi3.push(yuan2);
+}
@@ -0,0 +1,13 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let yi3 = 1.0;
+    if yi3 < jia3[(yi3 as usize) - 1] {
+        let _ans1 = yi3;
+        println!("{}", _ans1);
+    }
+}
@@ -0,0 +1,5 @@
+var jia3 = [];
+jia3.push(3);
+jia3.push(5);
+var yi3 = 1;
+yi3 = jia3.length;
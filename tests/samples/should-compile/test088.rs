@@ -0,0 +1,10 @@
+// identifiers:
+//   「甲」 jia3
+//   「乙」 yi3
+fn main() {
+    let mut jia3 = vec![];
+    jia3.push(3.0);
+    jia3.push(5.0);
+    let mut _yi3 = 1.0;
+    _yi3 = jia3.len() as f64;
+}